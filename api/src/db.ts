@@ -383,7 +383,7 @@ function tallyGame(
   const outcome = parseOutcome(row.outcome);
   if (!outcome) return { win: false, loss: false, draw: false };
 
-  if (outcome.result === 'Stalemate')
+  if (outcome.result !== 'Victory')
     return { win: false, loss: false, draw: true };
 
   const wonAsWhite =
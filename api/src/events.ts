@@ -21,6 +21,16 @@ export const GameOutcomeSchema = z.discriminatedUnion('result', [
       result: z.literal('Stalemate')
     })
     .strict(),
+  z
+    .object({
+      result: z.literal('ThreefoldRepetition')
+    })
+    .strict(),
+  z
+    .object({
+      result: z.literal('FiftyMoveRule')
+    })
+    .strict(),
   z
     .object({
       result: z.literal('Victory'),
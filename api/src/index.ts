@@ -296,6 +296,9 @@ function resultText(game: Awaited<ReturnType<typeof getGame>>): string {
     return game?.status === 'in_progress' ? 'In progress' : 'Waiting';
   }
   if (game.outcome.result === 'Stalemate') return 'Draw — Stalemate';
+  if (game.outcome.result === 'ThreefoldRepetition')
+    return 'Draw — Threefold repetition';
+  if (game.outcome.result === 'FiftyMoveRule') return 'Draw — Fifty-move rule';
   if (game.resigner) return `${game.outcome.color} wins by resignation!`;
   return `${game.outcome.color} wins by checkmate!`;
 }
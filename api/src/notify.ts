@@ -252,6 +252,9 @@ function buildNotifications(
 
 function formatOutcome(outcome: Record<string, unknown>): string {
   if (outcome.result === 'Stalemate') return 'Draw — stalemate';
+  if (outcome.result === 'ThreefoldRepetition')
+    return 'Draw — threefold repetition';
+  if (outcome.result === 'FiftyMoveRule') return 'Draw — fifty-move rule';
   const color = outcome.color as string;
   return `${color} wins!`;
 }
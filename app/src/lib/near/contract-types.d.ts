@@ -112,6 +112,14 @@ export type GameOutcome =
   | {
       result: 'Stalemate';
       [k: string]: unknown;
+    }
+  | {
+      result: 'ThreefoldRepetition';
+      [k: string]: unknown;
+    }
+  | {
+      result: 'FiftyMoveRule';
+      [k: string]: unknown;
     };
 /**
  * This interface was referenced by `ContractTypes`'s JSON-Schema
@@ -50,6 +50,9 @@ export const load: PageServerLoad = async ({ params, fetch }) => {
         : 'Waiting for opponent';
     }
     if (game.outcome.result === 'Stalemate') return 'Draw — Stalemate';
+    if (game.outcome.result === 'ThreefoldRepetition')
+      return 'Draw — Threefold repetition';
+    if (game.outcome.result === 'FiftyMoveRule') return 'Draw — Fifty-move rule';
     if (game.resigner) return `${game.outcome.color} wins by resignation`;
     return `${game.outcome.color} wins by checkmate`;
   })();
@@ -11,7 +11,7 @@ done
 
 mkdir -p res
 
-# Optionally regenerate static data (opening book, Zobrist keys).
+# Optionally regenerate static data (opening book, Zobrist keys, endgame bitbases).
 # This downloads Stockfish and runs a lengthy analysis (~20-30 min).
 # Skip by default; the committed static_book.rs / zobrist_keys.rs are used as-is.
 if [ "$REGEN_DATA" = true ]; then
@@ -19,6 +19,7 @@ if [ "$REGEN_DATA" = true ]; then
     bash scripts/setup.sh
     PYTHONPATH=scripts/.pydeps/chess-1.11.2 python3 scripts/generate_zobrist.py
     PYTHONPATH=scripts/.pydeps/chess-1.11.2 python3 scripts/generate_static_data.py
+    REGEN_BITBASES=1 cargo test -p chess-engine --release --lib bitbase::tests::compiled_tables_match_the_generator
 else
     echo "=== Using committed static data (pass --regen-data to regenerate) ==="
 fi
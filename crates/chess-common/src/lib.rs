@@ -114,6 +114,8 @@ pub struct PlayMove {
     pub game_id: GameId,
     pub color: Color,
     pub mv: MoveStr,
+    /// Standard Algebraic Notation, not present before event version 1.1.0
+    pub san: Option<MoveStr>,
     pub board: [String; 8],
     pub outcome: Option<GameOutcome>,
 }
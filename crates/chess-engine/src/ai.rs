@@ -0,0 +1,127 @@
+//! How the contract's AI picks its moves.
+//!
+//! Kept in the engine rather than the contract so native tools, like the
+//! self-play runner, play exactly like the AI does on-chain.
+
+use crate::{
+    get_endgame_move,
+    static_book::lookup_opening,
+    transposition_table::{TranspositionTable, TT_SIZE},
+    Board, Move, PositionHistory, SearchLimits, SearchResult, SearchStats, FLAG_ASPIRATION_WINDOWS,
+    FLAG_CHECK_EXTENSIONS, FLAG_ENDGAME_HEURISTICS, FLAG_HISTORY_HEURISTIC,
+    FLAG_ITERATIVE_DEEPENING, FLAG_KILLER_HEURISTIC, FLAG_LATE_MOVE_REDUCTION, FLAG_MOVE_ORDERING,
+    FLAG_NULL_MOVE_PRUNING, FLAG_OPENING_BOOK, FLAG_QUIESCENCE,
+};
+
+const AI_PIECE_COUNT_CLAMP_MIN: f64 = 4.0;
+const AI_PIECE_COUNT_CLAMP_MAX: f64 = 32.0;
+const AI_PIECE_SCALE_DIVISOR: f64 = 16.0;
+
+// The search flags of the contract's AI difficulties, each adding to the
+// one before:
+//
+// Easy:       check extensions + move ordering (MVV-LVA)
+// Medium:     + opening book + null-move pruning + quiescence search
+// Hard:       + endgame heuristics + iterative deepening
+//             + aspiration windows
+// Very Hard:  + killer heuristic + late-move reduction
+//             + history heuristic
+//
+// Principal variation search is left off: without the transposition
+// table's best move to search first its re-searches cost more nodes than
+// the zero windows save.
+pub const AI_FLAGS_EASY: u16 = FLAG_CHECK_EXTENSIONS | FLAG_MOVE_ORDERING;
+pub const AI_FLAGS_MEDIUM: u16 =
+    AI_FLAGS_EASY | FLAG_OPENING_BOOK | FLAG_NULL_MOVE_PRUNING | FLAG_QUIESCENCE;
+pub const AI_FLAGS_HARD: u16 =
+    AI_FLAGS_MEDIUM | FLAG_ENDGAME_HEURISTICS | FLAG_ITERATIVE_DEEPENING | FLAG_ASPIRATION_WINDOWS;
+pub const AI_FLAGS_VERY_HARD: u16 =
+    AI_FLAGS_HARD | FLAG_KILLER_HEURISTIC | FLAG_LATE_MOVE_REDUCTION | FLAG_HISTORY_HEURISTIC;
+
+/// Scale up the sampling widths of a difficulty's depth list as pieces come
+/// off the board, since positions with fewer pieces have fewer moves.
+pub fn scale_depths(board: &Board, max_depths: &[u8]) -> Vec<u8> {
+    let piece_count =
+        (board.count_pieces() as f64).clamp(AI_PIECE_COUNT_CLAMP_MIN, AI_PIECE_COUNT_CLAMP_MAX);
+    let scale = (AI_PIECE_SCALE_DIVISOR / piece_count).max(1.0);
+    max_depths
+        .iter()
+        .map(|d| (*d as f64 * scale).round().max(1.0) as u8)
+        .collect()
+}
+
+/// The AI's move: a won endgame is played by the endgame heuristics, the
+/// opening from the book unless the book move loses material, and
+/// everything else is searched with the scaled `max_depths`. Without any
+/// flags the AI plays its first legal move.
+pub fn get_ai_move(
+    board: &Board,
+    max_depths: &[u8],
+    seed: [u8; 32],
+    limits: &impl SearchLimits,
+    flags: u16,
+) -> Move {
+    get_ai_move_with_stats(board, max_depths, seed, limits, flags).0
+}
+
+/// Like [`get_ai_move`], also returning the statistics of the search, or
+/// `None` if the move was not searched.
+pub fn get_ai_move_with_stats(
+    board: &Board,
+    max_depths: &[u8],
+    seed: [u8; 32],
+    limits: &impl SearchLimits,
+    flags: u16,
+) -> (Move, Option<SearchStats>) {
+    let history = PositionHistory::new(board);
+    let mut tt = TranspositionTable::new(TT_SIZE);
+    let (mv, result) =
+        get_ai_move_in_game(board, &history, &mut tt, max_depths, seed, limits, flags);
+    (mv, result.map(|result| result.stats))
+}
+
+/// Like [`get_ai_move`], for a position reached in a game whose earlier
+/// positions are recorded in `history`, searching with the caller's
+/// transposition table `tt`; see [`Board::get_next_move_in_game`]. Returns
+/// the whole search result, or `None` if the move was not searched.
+pub fn get_ai_move_in_game(
+    board: &Board,
+    history: &PositionHistory,
+    tt: &mut TranspositionTable,
+    max_depths: &[u8],
+    seed: [u8; 32],
+    limits: &impl SearchLimits,
+    flags: u16,
+) -> (Move, Option<SearchResult>) {
+    let depths = scale_depths(board, max_depths);
+    let mut search = || {
+        let result = board.get_next_move_in_game(history, tt, &depths, seed, limits, flags);
+        (result.best_move, Some(result))
+    };
+
+    let book_move = if (flags & FLAG_OPENING_BOOK) != 0 {
+        lookup_opening(board.zobrist_key(), seed[0])
+    } else {
+        None
+    };
+
+    let endgame_move = if (flags & FLAG_ENDGAME_HEURISTICS) != 0 {
+        get_endgame_move(board)
+    } else {
+        None
+    };
+
+    if let Some(mv) = endgame_move {
+        (mv, None)
+    } else if let Some(mv) = book_move {
+        if board.see(mv) < 0 {
+            search()
+        } else {
+            (mv, None)
+        }
+    } else if flags == 0 {
+        (board.get_legal_moves().next().unwrap_or(Move::Resign), None)
+    } else {
+        search()
+    }
+}
@@ -1,122 +1,637 @@
+//! UCI front-end for the engine, so the on-chain AI can be used from chess
+//! GUIs and tournament managers.
+//!
+//! Build with `cargo build --release -p chess-engine --features uci`.
+//!
+//! Every search picks its move with the contract's AI, `get_ai_move_in_game`,
+//! with the flags and sampling widths configured via `setoption`, so the
+//! opening book and the endgame heuristics are used as on-chain. The
+//! `Difficulty` option sets the flags of one of the contract's levels at
+//! once. The position's game history is kept from the `position` command's
+//! moves, so moves that draw by threefold repetition or the fifty-move rule
+//! are seen as draws, and the transposition table is kept from one `go` to
+//! the next until `ucinewgame`. `go` prints an `info` line for the deepest
+//! iteration the search completed. `movetime`, `nodes` and `stop` interrupt
+//! the search, which then returns the move of the last completed iteration.
+//! With `IterativeDeepening` off there is only one iteration, to the full
+//! `depth`.
+//!
+//! Chess960 positions can be set up with X-FEN or Shredder-FEN, and their
+//! castling moves are read and written as the king taking its own rook.
+//! With `UCI_Chess960` on, castling in any position is read and written that
+//! way.
+//!
+//! For move-generation checks, `go perft <depth>` prints the perft node count
+//! of the current position split by root move, and `perft [<depth>]` runs
+//! the standard perft suite against its published node counts.
+
 extern crate chess_engine;
-use chess_engine::*;
+use chess_engine::{
+    format_chess960_uci_move, format_uci_move, get_ai_move_in_game, parse_chess960_uci_move,
+    parse_fen, parse_uci_move, perft_divide, transposition_table::TranspositionTable, Board,
+    GameResult, Move, NodeLimit, PositionHistory, TimeLimit, AI_FLAGS_EASY, AI_FLAGS_HARD,
+    AI_FLAGS_MEDIUM, AI_FLAGS_VERY_HARD, FLAG_ASPIRATION_WINDOWS, FLAG_CHECK_EXTENSIONS,
+    FLAG_ENDGAME_HEURISTICS, FLAG_HISTORY_HEURISTIC, FLAG_ITERATIVE_DEEPENING,
+    FLAG_KILLER_HEURISTIC, FLAG_LATE_MOVE_REDUCTION, FLAG_MOVE_ORDERING, FLAG_NULL_MOVE_PRUNING,
+    FLAG_OPENING_BOOK, FLAG_PRINCIPAL_VARIATION_SEARCH, FLAG_QUIESCENCE, PERFT_SUITE, WHITE,
+};
 use std::{
-    convert::TryFrom,
-    io::{stdin, stdout, Write},
+    io::{stdin, BufRead},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
 };
 
-fn input(prompt: impl std::fmt::Display) -> String {
-    let mut s = String::new();
-    print!("{}", prompt);
-    let _ = stdout().flush();
-    stdin()
-        .read_line(&mut s)
-        .expect("Did not enter a correct string");
-    s
+/// Search flags that can be toggled with UCI check options.
+const FLAG_OPTIONS: [(&str, u16); 12] = [
+    ("CheckExtensions", FLAG_CHECK_EXTENSIONS),
+    ("NullMovePruning", FLAG_NULL_MOVE_PRUNING),
+    ("MoveOrdering", FLAG_MOVE_ORDERING),
+    ("Quiescence", FLAG_QUIESCENCE),
+    ("KillerHeuristic", FLAG_KILLER_HEURISTIC),
+    ("LateMoveReduction", FLAG_LATE_MOVE_REDUCTION),
+    ("IterativeDeepening", FLAG_ITERATIVE_DEEPENING),
+    ("OpeningBook", FLAG_OPENING_BOOK),
+    ("EndgameHeuristics", FLAG_ENDGAME_HEURISTICS),
+    ("PrincipalVariationSearch", FLAG_PRINCIPAL_VARIATION_SEARCH),
+    ("AspirationWindows", FLAG_ASPIRATION_WINDOWS),
+    ("HistoryHeuristic", FLAG_HISTORY_HEURISTIC),
+];
+
+/// The flags of the contract's AI difficulties, selectable with the
+/// `Difficulty` combo option. Its default `Custom` leaves the check options
+/// in charge.
+const DIFFICULTIES: [(&str, u16); 4] = [
+    ("Easy", AI_FLAGS_EASY),
+    ("Medium", AI_FLAGS_MEDIUM),
+    ("Hard", AI_FLAGS_HARD),
+    ("VeryHard", AI_FLAGS_VERY_HARD),
+];
+
+/// Deepest search `go` starts on its own.
+const MAX_DEPTH: u8 = 32;
+/// Number of entries of the transposition table kept between searches.
+const HASH_ENTRIES: usize = 1 << 20;
+
+#[derive(Clone)]
+struct Options {
+    flags: u16,
+    /// Number of moves sampled per ply, like the contract's depth lists.
+    /// Plies beyond the list reuse its last entry.
+    widths: Vec<u8>,
+    seed: u64,
+    /// Castling is read and written as the king taking its own rook, as
+    /// GUIs do for Chess960 games. Castling in positions with non-corner
+    /// rooks is always written that way.
+    chess960: bool,
 }
 
-fn get_cpu_move(b: &Board, best: bool) -> Move {
-    let (m, count, _) = if best {
-        b.get_best_next_move(4)
-    } else {
-        b.get_worst_next_move(4)
-    };
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            flags: FLAG_OPTIONS.iter().fold(0, |flags, (_, flag)| flags | flag),
+            widths: vec![u8::MAX],
+            seed: 0,
+            chess960: false,
+        }
+    }
+}
 
-    print!("CPU evaluated {} moves before choosing to ", count);
-    match m {
-        Move::Piece(from, to) | Move::Promotion(from, to, _) => {
-            match (b.get_piece(from), b.get_piece(to)) {
-                (Some(piece), Some(takes)) => println!(
-                    "take {}({}) with {}({})",
-                    takes.get_name(),
-                    to,
-                    piece.get_name(),
-                    from
-                ),
-                (Some(piece), None) => {
-                    println!("move {}({}) to {}", piece.get_name(), from, to)
+impl Options {
+    fn print(&self) {
+        println!(
+            "option name Seed type spin default {} min 0 max {}",
+            self.seed,
+            i64::MAX
+        );
+        println!(
+            "option name Widths type string default {}",
+            format_widths(&self.widths)
+        );
+        println!(
+            "option name UCI_Chess960 type check default {}",
+            self.chess960
+        );
+        let levels: String = DIFFICULTIES
+            .iter()
+            .map(|(name, _)| format!(" var {}", name))
+            .collect();
+        println!(
+            "option name Difficulty type combo default Custom var Custom{}",
+            levels
+        );
+        for (name, flag) in FLAG_OPTIONS {
+            println!(
+                "option name {} type check default {}",
+                name,
+                self.flags & flag != 0
+            );
+        }
+    }
+
+    fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+        if let Some((_, flag)) = FLAG_OPTIONS.iter().find(|(option, _)| *option == name) {
+            match value {
+                "true" => self.flags |= flag,
+                "false" => self.flags &= !flag,
+                _ => return Err(format!("invalid value {} for {}", value, name)),
+            }
+            return Ok(());
+        }
+        match name {
+            "Difficulty" => {
+                if let Some((_, flags)) = DIFFICULTIES.iter().find(|(level, _)| *level == value) {
+                    self.flags = *flags;
+                } else if value != "Custom" {
+                    return Err(format!("invalid value {} for {}", value, name));
                 }
-                _ => println!("move {} to {}", from, to),
             }
+            "Seed" => {
+                self.seed = value
+                    .parse()
+                    .map_err(|_| format!("invalid seed {}", value))?;
+            }
+            "UCI_Chess960" => {
+                self.chess960 = value
+                    .parse()
+                    .map_err(|_| format!("invalid value {} for {}", value, name))?;
+            }
+            "Widths" => {
+                let widths = value
+                    .split(',')
+                    .map(|width| width.trim().parse::<u8>())
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|_| format!("invalid widths {}", value))?;
+                if widths.is_empty() || widths.contains(&0) {
+                    return Err(format!("invalid widths {}", value));
+                }
+                self.widths = widths;
+            }
+            _ => return Err(format!("unknown option {}", name)),
         }
-        Move::KingSideCastle => {
-            println!("castle kingside")
+        Ok(())
+    }
+
+    /// The depth list passed to the AI for a search of `depth` plies.
+    fn depths(&self, depth: u8) -> Vec<u8> {
+        (0..depth as usize)
+            .map(|ply| *self.widths.get(ply).unwrap_or(self.widths.last().unwrap()))
+            .collect()
+    }
+
+    fn seed_bytes(&self) -> [u8; 32] {
+        let mut seed = [0; 32];
+        seed[..8].copy_from_slice(&self.seed.to_le_bytes());
+        seed
+    }
+}
+
+fn format_widths(widths: &[u8]) -> String {
+    widths
+        .iter()
+        .map(|width| width.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[derive(Default)]
+struct GoLimits {
+    depth: Option<u8>,
+    nodes: Option<u64>,
+    movetime: Option<Duration>,
+}
+
+impl GoLimits {
+    fn parse(board: &Board, args: &[&str]) -> Self {
+        let mut limits = Self::default();
+        let (mut time, mut increment) = (None, Duration::ZERO);
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().and_then(|value| value.parse::<u64>().ok());
+            match *arg {
+                "depth" => limits.depth = value().map(|depth| depth.min(MAX_DEPTH as u64) as u8),
+                "nodes" => limits.nodes = value(),
+                "movetime" => limits.movetime = value().map(Duration::from_millis),
+                "wtime" | "btime" => {
+                    let clock = value().map(Duration::from_millis);
+                    if (*arg == "wtime") == (board.get_turn_color() == WHITE) {
+                        time = clock;
+                    }
+                }
+                "winc" | "binc" => {
+                    let clock = value().map(Duration::from_millis);
+                    if (*arg == "winc") == (board.get_turn_color() == WHITE) {
+                        increment = clock.unwrap_or_default();
+                    }
+                }
+                _ => {}
+            }
         }
-        Move::QueenSideCastle => {
-            println!("castle queenside")
+        // use a small share of the remaining clock, but never most of it
+        if let (None, Some(time)) = (limits.movetime, time) {
+            limits.movetime = Some((time / 30 + increment / 2).min(time / 2));
         }
-        Move::Resign => println!("resign"),
+        limits
     }
+}
 
-    m
+/// The UCI notation of `m`, with castling as the king taking its own rook
+/// when `chess960` is set.
+fn format_move(board: &Board, m: Move, chess960: bool) -> Result<String, String> {
+    if chess960 {
+        format_chess960_uci_move(board, m)
+    } else {
+        format_uci_move(board, m)
+    }
 }
 
-fn main() -> Result<(), String> {
-    let mut b = Board::default();
+/// The UCI moves of `pv`, played one after another from `board`.
+fn format_pv(mut board: Board, pv: &[Move], chess960: bool) -> String {
+    let mut moves = vec![];
+    for &m in pv {
+        match format_move(&board, m, chess960) {
+            Ok(m) => moves.push(m),
+            Err(_) => break,
+        }
+        board = board.apply_eval_move(m);
+    }
+    moves.join(" ")
+}
 
-    println!("{}", b);
-    let mut history = vec![];
+fn print_bestmove(board: &Board, m: Move, chess960: bool) {
+    match format_move(board, m, chess960) {
+        Ok(m) => println!("bestmove {}", m),
+        Err(_) => println!("bestmove 0000"),
+    }
+}
 
-    loop {
-        let mut s = input(">>> ");
-        s = s.trim().to_string();
+/// Search `board` for the `go` command and print the best move, keeping
+/// `tt` for the next search of the game.
+fn search(
+    board: Board,
+    history: PositionHistory,
+    tt: Arc<Mutex<TranspositionTable>>,
+    options: Options,
+    limits: GoLimits,
+    stop: Arc<AtomicBool>,
+) {
+    let start = Instant::now();
+    let depths = options.depths(limits.depth.unwrap_or(MAX_DEPTH));
+    let search_limits = (
+        &*stop,
+        (
+            NodeLimit(limits.nodes.unwrap_or(u64::MAX)),
+            limits.movetime.map(TimeLimit::new),
+        ),
+    );
+    let mut tt = tt.lock().unwrap_or_else(PoisonError::into_inner);
+    let (best_move, result) = get_ai_move_in_game(
+        &board,
+        &history,
+        &mut tt,
+        &depths,
+        options.seed_bytes(),
+        &search_limits,
+        options.flags,
+    );
+    match result {
+        Some(result) => {
+            let elapsed = start.elapsed();
+            println!(
+                "info depth {} score {} nodes {} nps {} time {} pv {}",
+                result.stats.completed_depth,
+                result.score,
+                result.board_count,
+                (result.board_count as f64 / elapsed.as_secs_f64().max(0.001)) as u64,
+                elapsed.as_millis(),
+                format_pv(board, &result.pv, options.chess960),
+            );
+        }
+        None => println!("info string book or endgame move"),
+    }
+    print_bestmove(&board, best_move, options.chess960);
+}
 
-        let m = if s.is_empty() {
-            println!("Waiting for CPU to choose best move...");
-            get_cpu_move(&b, true)
-        } else if s == "worst" {
-            println!("Waiting for CPU to choose worst move...");
-            get_cpu_move(&b, false)
-        } else if s == "rate" {
-            continue;
-        } else if s == "pass" {
-            b = b.change_turn();
-            continue;
-        } else if s == "history" {
-            for i in 0..history.len() {
-                if i < history.len() - 1 {
-                    println!("{} {}", history[i], history[i + 1]);
-                } else {
-                    println!("{}", history[i]);
-                }
-            }
-            continue;
+/// Handle `position [startpos | fen <fen>] [moves <move>...]`, returning the
+/// position together with the history of the game that led to it.
+fn parse_position(args: &[&str], chess960: bool) -> Result<(Board, PositionHistory), String> {
+    let moves_at = args
+        .iter()
+        .position(|arg| *arg == "moves")
+        .unwrap_or(args.len());
+    let (mut board, mut history) = match args.first() {
+        Some(&"startpos") => {
+            let board = Board::default();
+            (board, PositionHistory::new(&board))
+        }
+        Some(&"fen") => {
+            let fen = &args[1..moves_at];
+            let board = parse_fen(&fen.join(" "))?;
+            // parse_fen only validates the first four fields
+            let halfmove_clock = match fen.get(4) {
+                Some(clock) => clock
+                    .parse()
+                    .map_err(|_| format!("invalid halfmove clock {}", clock))?,
+                None => 0,
+            };
+            (
+                board,
+                PositionHistory::with_halfmove_clock(&board, halfmove_clock),
+            )
+        }
+        _ => return Err("expected startpos or fen".to_string()),
+    };
+    for uci in args.iter().skip(moves_at + 1) {
+        let m = if chess960 {
+            parse_chess960_uci_move(&board, uci)?
         } else {
-            match Move::try_from(s) {
-                Ok(m) => m,
-                Err(e) => {
-                    eprintln!("{}", e);
-                    continue;
-                }
-            }
+            parse_uci_move(&board, uci)?
         };
+        match board.play_move_with_history(m, &mut history) {
+            GameResult::Continuing(next_turn) => board = next_turn,
+            GameResult::IllegalMove(_) => return Err(format!("illegal move {}", uci)),
+            // the game is over, keep the final position
+            _ => board = board.apply_eval_move(m),
+        }
+    }
+    Ok((board, history))
+}
 
-        match b.play_move(m) {
-            GameResult::Continuing(next_board) => {
-                b = next_board;
-                println!("{}", b);
-                history.push(m);
-            }
+/// Handle `setoption name <name> [value <value>]`.
+fn parse_setoption(options: &mut Options, args: &[&str]) -> Result<(), String> {
+    let value_at = args
+        .iter()
+        .position(|arg| *arg == "value")
+        .unwrap_or(args.len());
+    if args.first() != Some(&"name") {
+        return Err("expected name".to_string());
+    }
+    let name = args[1..value_at].join(" ");
+    let value = args.get(value_at + 1..).unwrap_or_default().join(" ");
+    options.set(&name, &value)
+}
+
+/// Default depth of the `perft` suite command. Deeper published counts run
+/// into the millions of nodes.
+const PERFT_SUITE_DEPTH: u8 = 4;
+
+/// Print the perft breakdown of `board` by root move and return the total.
+fn print_perft_divide(board: &Board, depth: u8, chess960: bool) -> u64 {
+    let mut nodes = 0;
+    for (m, count) in perft_divide(board, depth) {
+        println!(
+            "{}: {}",
+            format_move(board, m, chess960).unwrap_or_else(|_| format!("{:?}", m)),
+            count
+        );
+        nodes += count;
+    }
+    nodes
+}
+
+/// Handle `go perft <depth>`.
+fn go_perft(board: &Board, args: &[&str], chess960: bool) -> Result<(), String> {
+    let depth: u8 = args
+        .get(1)
+        .ok_or("expected a perft depth")?
+        .parse()
+        .map_err(|_| "invalid perft depth".to_string())?;
+    let start = Instant::now();
+    let nodes = print_perft_divide(board, depth, chess960);
+    println!();
+    println!("Nodes searched: {}", nodes);
+    println!("info string perft took {}ms", start.elapsed().as_millis());
+    Ok(())
+}
 
-            GameResult::Victory(winner) => {
-                println!("{}", b);
-                println!("{} loses. {} is victorious.", !winner, winner);
+/// Handle `perft [<depth>]`: run every suite position up to `depth` plies
+/// and print the per-move breakdown of any mismatching count.
+fn perft_suite(args: &[&str]) -> Result<(), String> {
+    let max_depth: u8 = match args.first() {
+        Some(depth) => depth
+            .parse()
+            .map_err(|_| "invalid perft depth".to_string())?,
+        None => PERFT_SUITE_DEPTH,
+    };
+    let mut failures = 0;
+    for position in PERFT_SUITE {
+        let board = parse_fen(position.fen)?;
+        for (depth, &expected) in position.nodes.iter().enumerate().take(max_depth as usize) {
+            let depth = depth as u8 + 1;
+            let divide = perft_divide(&board, depth);
+            let nodes: u64 = divide.iter().map(|(_, count)| count).sum();
+            if nodes == expected {
+                println!("{} depth {}: {} ok", position.name, depth, nodes);
+            } else {
+                failures += 1;
+                println!(
+                    "{} depth {}: {} expected {}",
+                    position.name, depth, nodes, expected
+                );
+                print_perft_divide(&board, depth, false);
+                // deeper counts of this position are wrong as well
                 break;
             }
+        }
+    }
+    if failures == 0 {
+        println!("perft suite passed");
+    } else {
+        println!("perft suite failed for {} position(s)", failures);
+    }
+    Ok(())
+}
 
-            GameResult::IllegalMove(x) => {
-                eprintln!("{} is an illegal move.", x);
-            }
+struct RunningSearch {
+    handle: JoinHandle<()>,
+    stop: Arc<AtomicBool>,
+}
 
-            GameResult::Stalemate => {
-                println!("Drawn game.");
-                break;
+impl RunningSearch {
+    fn stop(self) {
+        self.stop.store(true, Ordering::Relaxed);
+        let _ = self.handle.join();
+    }
+}
+
+fn main() {
+    let mut board = Board::default();
+    let mut history = PositionHistory::new(&board);
+    let tt = Arc::new(Mutex::new(TranspositionTable::new(HASH_ENTRIES)));
+    let mut options = Options::default();
+    let mut running: Option<RunningSearch> = None;
+
+    for line in stdin().lock().lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(_) => break,
+        };
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let Some((command, args)) = words.split_first() else {
+            continue;
+        };
+        match *command {
+            "uci" => {
+                println!("id name Protocol Pawns");
+                println!("id author Protocol Pawns contributors");
+                options.print();
+                println!("uciok");
             }
+            "isready" => println!("readyok"),
+            "setoption" => {
+                if let Err(err) = parse_setoption(&mut options, args) {
+                    println!("info string {}", err);
+                }
+            }
+            "ucinewgame" => {
+                if let Some(search) = running.take() {
+                    search.stop();
+                }
+                board = Board::default();
+                history = PositionHistory::new(&board);
+                tt.lock().unwrap_or_else(PoisonError::into_inner).clear();
+            }
+            "position" => match parse_position(args, options.chess960) {
+                Ok(position) => (board, history) = position,
+                Err(err) => println!("info string {}", err),
+            },
+            "go" if args.first() == Some(&"perft") => {
+                if let Err(err) = go_perft(&board, args, options.chess960) {
+                    println!("info string {}", err);
+                }
+            }
+            "perft" => {
+                if let Err(err) = perft_suite(args) {
+                    println!("info string {}", err);
+                }
+            }
+            "go" => {
+                if let Some(search) = running.take() {
+                    search.stop();
+                }
+                let limits = GoLimits::parse(&board, args);
+                let stop = Arc::new(AtomicBool::new(false));
+                let (search_board, search_history, search_tt, search_options, search_stop) = (
+                    board,
+                    history.clone(),
+                    tt.clone(),
+                    options.clone(),
+                    stop.clone(),
+                );
+                let handle = thread::spawn(move || {
+                    search(
+                        search_board,
+                        search_history,
+                        search_tt,
+                        search_options,
+                        limits,
+                        search_stop,
+                    )
+                });
+                running = Some(RunningSearch { handle, stop });
+            }
+            "stop" => {
+                if let Some(search) = running.take() {
+                    search.stop();
+                }
+            }
+            "quit" => break,
+            // unknown commands are ignored, as the protocol demands
+            _ => {}
         }
     }
 
-    for m in history {
-        println!("{}", m);
+    if let Some(search) = running.take() {
+        search.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn position_keeps_the_game_history() {
+        let (board, history) =
+            parse_position(&["startpos", "moves", "e2e4", "e7e5"], false).unwrap();
+        let expected = "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e6 0 2";
+        assert_eq!(board, parse_fen(expected).unwrap());
+        assert_eq!(history.get_halfmove_clock(), 0);
+
+        // the start position occurs for the third time
+        let shuffle = ["g1f3", "g8f6", "f3g1", "f6g8"];
+        let args: Vec<&str> = ["startpos", "moves"]
+            .into_iter()
+            .chain(shuffle)
+            .chain(shuffle)
+            .collect();
+        let (board, history) = parse_position(&args, false).unwrap();
+        assert_eq!(board, Board::default());
+        assert!(history.is_threefold_repetition());
+
+        // the halfmove clock of the FEN keeps running
+        let fen = ["fen", "k7/8/8/8/8/8/8/KQ6", "w", "-", "-", "99", "80"];
+        let (_, history) = parse_position(&fen, false).unwrap();
+        assert!(!history.is_fifty_move_rule());
+        let args: Vec<&str> = fen.into_iter().chain(["moves", "b1b2"]).collect();
+        let (_, history) = parse_position(&args, false).unwrap();
+        assert!(history.is_fifty_move_rule());
+
+        assert!(parse_position(&["startpos", "moves", "e2e5"], false).is_err());
+        assert!(parse_position(&["fen", "k7/8/8/8/8/8/8/KQ6", "w", "-", "-", "x"], false).is_err());
+        assert!(parse_position(&["moves", "e2e4"], false).is_err());
+    }
+
+    #[test]
+    fn setoption() {
+        let mut options = Options::default();
+        parse_setoption(&mut options, &["name", "Widths", "value", "20,", "10"]).unwrap();
+        assert_eq!(options.widths, vec![20, 10]);
+        assert_eq!(options.depths(3), vec![20, 10, 10]);
+        parse_setoption(&mut options, &["name", "Difficulty", "value", "Hard"]).unwrap();
+        assert_eq!(options.flags, AI_FLAGS_HARD);
+        parse_setoption(&mut options, &["name", "Quiescence", "value", "false"]).unwrap();
+        assert_eq!(options.flags, AI_FLAGS_HARD & !FLAG_QUIESCENCE);
+        parse_setoption(&mut options, &["name", "Seed", "value", "7"]).unwrap();
+        assert_eq!(options.seed_bytes()[0], 7);
+
+        assert!(parse_setoption(&mut options, &["name", "Widths", "value", "0"]).is_err());
+        assert!(parse_setoption(&mut options, &["name", "Quiescence", "value", "yes"]).is_err());
+        assert!(parse_setoption(&mut options, &["name", "Hash", "value", "16"]).is_err());
+        assert!(parse_setoption(&mut options, &["Seed", "value", "7"]).is_err());
+        assert_eq!(options.widths, vec![20, 10]);
+    }
+
+    #[test]
+    fn go_limits() {
+        let white = Board::default();
+        let black =
+            parse_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1").unwrap();
+        let clock = [
+            "wtime", "60000", "btime", "30000", "winc", "1000", "binc", "0",
+        ];
+
+        let limits = GoLimits::parse(&white, &clock);
+        assert_eq!(limits.movetime, Some(Duration::from_millis(2500)));
+        assert_eq!(limits.depth, None);
+        let limits = GoLimits::parse(&black, &clock);
+        assert_eq!(limits.movetime, Some(Duration::from_millis(1000)));
+        // never more than half of what is left
+        let limits = GoLimits::parse(&white, &["wtime", "1000", "winc", "5000"]);
+        assert_eq!(limits.movetime, Some(Duration::from_millis(500)));
+
+        let limits = GoLimits::parse(&white, &["movetime", "300", "wtime", "60000"]);
+        assert_eq!(limits.movetime, Some(Duration::from_millis(300)));
+        let limits = GoLimits::parse(&white, &["depth", "99", "nodes", "5000"]);
+        assert_eq!(limits.depth, Some(MAX_DEPTH));
+        assert_eq!(limits.nodes, Some(5000));
+        let limits = GoLimits::parse(&white, &["infinite"]);
+        assert_eq!(
+            (limits.depth, limits.nodes, limits.movetime),
+            (None, None, None)
+        );
     }
-    Ok(())
 }
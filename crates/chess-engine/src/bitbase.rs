@@ -0,0 +1,1372 @@
+//! Endgame bitbases built by retrograde analysis.
+//!
+//! A bitbase knows, for every placement of a handful of pieces, whether the
+//! side to move wins, draws or loses with perfect play and in how many moves
+//! the game ends in mate. [`Bitbase::generate`] builds one for any material
+//! signature of up to four pieces: it finds every checkmate and then walks
+//! moves backwards from each decided position, so positions are decided in
+//! order of their distance to mate. Captures and promotions leave the table
+//! and are looked up in the smaller tables they lead to, which are generated
+//! first.
+//!
+//! Only positions with the stronger side to move are stored, and only one of
+//! each group of mirrored positions: pawnless tables keep the stronger king
+//! in the a1-d1-d4 triangle, tables with pawns keep it on the queenside. A
+//! position with the weaker side to move is answered by looking one ply
+//! ahead. KQK, KRK and KPK (about 210KB) are compiled into the engine and
+//! used by [`probe`] and [`best_move`].
+//!
+//! At a byte per position a four-piece table takes 2.5MB, so the selected
+//! four-piece endings are compiled in as [`WdlBitbase`]s instead: only win,
+//! draw or loss, in one bit per position where the stronger side to move
+//! never loses and two bits otherwise. KBNK (320KB) and KQKR (640KB) are
+//! answered by [`probe_wdl`], which also covers the three-piece tables.
+//!
+//! The compiled tables live in `bitbases/` and are rebuilt with
+//! `./build.sh --regen-data`, or just
+//! `REGEN_BITBASES=1 cargo test -p chess-engine --release bitbase`.
+//!
+//! Bitbases know nothing about castling or en passant, so positions with
+//! castling rights or a possible en passant capture are never probed. The fifty-move
+//! rule is ignored as well; no compiled ending takes longer than 30 moves.
+
+use std::borrow::Cow;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::bitboard::{
+    bishop_attacks, queen_attacks, rook_attacks, square_index, squares, Bitboard, BISHOP, EMPTY,
+    KING, KING_ATTACKS, KNIGHT, KNIGHT_ATTACKS, PAWN, PAWN_ATTACKS, QUEEN, ROOK,
+};
+use crate::board::MATE;
+use crate::{Board, Color, Move, BLACK, WHITE};
+
+/// Kings included.
+const MAX_PIECES: usize = 4;
+
+/// Promotion choices, strongest first.
+const PROMOTIONS: [usize; 4] = [QUEEN, ROOK, BISHOP, KNIGHT];
+
+// Values in a table under construction: the number of plies to mate for
+// decided positions (odd: the side to move mates, even: it gets mated), or
+// one of these markers.
+const DRAW: u16 = u16::MAX;
+const UNKNOWN: u16 = u16::MAX - 1;
+const ILLEGAL: u16 = u16::MAX - 2;
+
+static KQK_TABLE: Bitbase = Bitbase {
+    material: Material::KQK,
+    data: Cow::Borrowed(include_bytes!("../bitbases/kqk.bin")),
+};
+static KRK_TABLE: Bitbase = Bitbase {
+    material: Material::KRK,
+    data: Cow::Borrowed(include_bytes!("../bitbases/krk.bin")),
+};
+static KPK_TABLE: Bitbase = Bitbase {
+    material: Material::KPK,
+    data: Cow::Borrowed(include_bytes!("../bitbases/kpk.bin")),
+};
+static KBNK_TABLE: WdlBitbase = WdlBitbase {
+    material: Material::KBNK,
+    bits: 1,
+    data: Cow::Borrowed(include_bytes!("../bitbases/kbnk.wdl")),
+};
+static KQKR_TABLE: WdlBitbase = WdlBitbase {
+    material: Material::KQKR,
+    bits: 2,
+    data: Cow::Borrowed(include_bytes!("../bitbases/kqkr.wdl")),
+};
+
+/// The search value of a win known only from a [`WdlBitbase`]: above any
+/// evaluation, below any mate.
+pub(crate) const KNOWN_WIN: i32 = 20_000;
+
+/// The result of a position for the side to move with perfect play.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BitbaseEntry {
+    /// The side to move mates in this many moves.
+    Win(u8),
+    Draw,
+    /// The side to move is mated in this many moves; `Loss(0)` is checkmate.
+    Loss(u8),
+}
+
+impl BitbaseEntry {
+    fn from_plies(plies: u16) -> Self {
+        match plies {
+            DRAW => BitbaseEntry::Draw,
+            p if p % 2 == 1 => BitbaseEntry::Win(p.div_ceil(2) as u8),
+            p => BitbaseEntry::Loss((p / 2) as u8),
+        }
+    }
+
+    fn plies(self) -> u16 {
+        match self {
+            BitbaseEntry::Win(n) => 2 * n as u16 - 1,
+            BitbaseEntry::Draw => DRAW,
+            BitbaseEntry::Loss(n) => 2 * n as u16,
+        }
+    }
+
+    fn from_byte(byte: u8) -> Self {
+        match byte {
+            0 => BitbaseEntry::Draw,
+            1..=127 => BitbaseEntry::Win(byte),
+            _ => BitbaseEntry::Loss(byte - 128),
+        }
+    }
+
+    fn to_byte(self) -> u8 {
+        match self {
+            BitbaseEntry::Win(n) => {
+                assert!((1..128).contains(&n), "mate in {} does not fit a byte", n);
+                n
+            }
+            BitbaseEntry::Draw => 0,
+            BitbaseEntry::Loss(n) => {
+                assert!(n < 128, "mated in {} does not fit a byte", n);
+                128 + n
+            }
+        }
+    }
+
+    /// The search value of this entry for the side to move at `ply`, on the
+    /// same scale as checkmates found by the search.
+    pub(crate) fn search_value(self, ply: u8) -> i32 {
+        match self {
+            BitbaseEntry::Win(n) => MATE - (ply as i32 + 2 * n as i32 - 1),
+            BitbaseEntry::Draw => 0,
+            BitbaseEntry::Loss(n) => -MATE + ply as i32 + 2 * n as i32,
+        }
+    }
+}
+
+/// The result of a position for the side to move with perfect play, without
+/// the distance to mate. Ordered from worst to best.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Wdl {
+    Loss,
+    Draw,
+    Win,
+}
+
+impl Wdl {
+    /// The result for the side that moved into a position with this result.
+    fn parent(self) -> Self {
+        match self {
+            Wdl::Loss => Wdl::Win,
+            Wdl::Draw => Wdl::Draw,
+            Wdl::Win => Wdl::Loss,
+        }
+    }
+
+    /// The search value of this result for the side to move, before the
+    /// evaluation that tells better wins apart.
+    pub(crate) fn search_value(self) -> i32 {
+        match self {
+            Wdl::Win => KNOWN_WIN,
+            Wdl::Draw => 0,
+            Wdl::Loss => -KNOWN_WIN,
+        }
+    }
+
+    fn from_bits(bits: u8) -> Self {
+        match bits {
+            0 => Wdl::Draw,
+            1 => Wdl::Win,
+            _ => Wdl::Loss,
+        }
+    }
+
+    fn to_bits(self) -> u8 {
+        match self {
+            Wdl::Draw => 0,
+            Wdl::Win => 1,
+            Wdl::Loss => 2,
+        }
+    }
+}
+
+impl From<BitbaseEntry> for Wdl {
+    fn from(entry: BitbaseEntry) -> Self {
+        match entry {
+            BitbaseEntry::Win(_) => Wdl::Win,
+            BitbaseEntry::Draw => Wdl::Draw,
+            BitbaseEntry::Loss(_) => Wdl::Loss,
+        }
+    }
+}
+
+/// The pieces of an ending besides the two kings, written like `KQK`,
+/// `KBNK` or `KQKR` (White's pieces first).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Material {
+    /// `(color, kind)` sorted by color then kind; only the first `len` are
+    /// used.
+    pieces: [(Color, usize); MAX_PIECES - 2],
+    len: usize,
+}
+
+impl Material {
+    pub const KQK: Material = Material {
+        pieces: [(WHITE, QUEEN), (WHITE, KING)],
+        len: 1,
+    };
+    pub const KRK: Material = Material {
+        pieces: [(WHITE, ROOK), (WHITE, KING)],
+        len: 1,
+    };
+    pub const KPK: Material = Material {
+        pieces: [(WHITE, PAWN), (WHITE, KING)],
+        len: 1,
+    };
+    pub const KBNK: Material = Material {
+        pieces: [(WHITE, BISHOP), (WHITE, KNIGHT)],
+        len: 2,
+    };
+    pub const KQKR: Material = Material {
+        pieces: [(WHITE, QUEEN), (BLACK, ROOK)],
+        len: 2,
+    };
+
+    /// The material of `pieces` (kings excluded), in any order.
+    pub fn new(pieces: &[(Color, usize)]) -> Result<Self, String> {
+        if pieces.len() > MAX_PIECES - 2 {
+            return Err(format!(
+                "bitbases support at most {} pieces besides the kings",
+                MAX_PIECES - 2
+            ));
+        }
+        if pieces.iter().any(|&(_, kind)| kind == KING || kind > PAWN) {
+            return Err("only the two kings may be kings".to_string());
+        }
+        let mut material = Material {
+            pieces: [(WHITE, KING); MAX_PIECES - 2],
+            len: pieces.len(),
+        };
+        material.pieces[..pieces.len()].copy_from_slice(pieces);
+        material.pieces[..pieces.len()].sort();
+        Ok(material)
+    }
+
+    fn pieces(&self) -> &[(Color, usize)] {
+        &self.pieces[..self.len]
+    }
+
+    /// The number of pieces, kings included.
+    pub fn piece_count(&self) -> usize {
+        self.len + 2
+    }
+
+    fn has_pawns(&self) -> bool {
+        self.pieces().iter().any(|&(_, kind)| kind == PAWN)
+    }
+
+    /// Bare kings, or a single minor piece: nobody can ever be mated.
+    fn is_dead_draw(&self) -> bool {
+        match self.pieces() {
+            [] => true,
+            [(_, kind)] => *kind == BISHOP || *kind == KNIGHT,
+            _ => false,
+        }
+    }
+
+    /// The same ending with the stronger side as White.
+    fn canonical(&self) -> Material {
+        if self.is_canonical() {
+            *self
+        } else {
+            self.flipped()
+        }
+    }
+
+    fn flipped(&self) -> Material {
+        let pieces: Vec<_> = self.pieces().iter().map(|&(c, k)| (!c, k)).collect();
+        Material::new(&pieces).unwrap()
+    }
+
+    /// Tables are stored with the stronger side as White.
+    fn is_canonical(&self) -> bool {
+        self.strength(WHITE) >= self.strength(BLACK)
+    }
+
+    fn strength(&self, color: Color) -> (u32, [usize; MAX_PIECES - 2]) {
+        let mut value = 0;
+        // lower kinds are stronger, so compare the negated kinds
+        let mut kinds = [0; MAX_PIECES - 2];
+        for (i, &(_, kind)) in self
+            .pieces()
+            .iter()
+            .filter(|(c, _)| *c == color)
+            .enumerate()
+        {
+            value += [0, 9, 5, 3, 3, 1][kind];
+            kinds[i] = PAWN + 1 - kind;
+        }
+        (value, kinds)
+    }
+}
+
+impl FromStr for Material {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid material signature: {}", s);
+        let rest = s.strip_prefix('K').ok_or_else(invalid)?;
+        let (white, black) = rest.split_once('K').ok_or_else(invalid)?;
+        let mut pieces = Vec::new();
+        for (color, letters) in [(WHITE, white), (BLACK, black)] {
+            for letter in letters.chars() {
+                let kind = match letter {
+                    'Q' => QUEEN,
+                    'R' => ROOK,
+                    'B' => BISHOP,
+                    'N' => KNIGHT,
+                    'P' => PAWN,
+                    _ => return Err(invalid()),
+                };
+                pieces.push((color, kind));
+            }
+        }
+        Material::new(&pieces)
+    }
+}
+
+impl fmt::Display for Material {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for color in [WHITE, BLACK] {
+            write!(f, "K")?;
+            for &(_, kind) in self.pieces().iter().filter(|(c, _)| *c == color) {
+                write!(f, "{}", ["K", "Q", "R", "B", "N", "P"][kind])?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Where the pieces of a [`Material`] stand: the white king, the black king,
+/// then the other pieces in material order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Placement {
+    squares: [usize; MAX_PIECES],
+    turn: Color,
+}
+
+impl Placement {
+    fn from_index(material: &Material, mut index: usize) -> Self {
+        let turn = if index & 1 == 0 { WHITE } else { BLACK };
+        index >>= 1;
+        let mut squares = [0; MAX_PIECES];
+        for i in (0..material.piece_count()).rev() {
+            squares[i] = index % 64;
+            index /= 64;
+        }
+        Placement { squares, turn }
+    }
+
+    fn index(&self, material: &Material) -> usize {
+        let squares = self.squares[..material.piece_count()].iter();
+        let index = squares.fold(0, |index, &sq| index * 64 + sq);
+        index << 1 | (self.turn == BLACK) as usize
+    }
+
+    /// Mirror the board top to bottom and swap the colors, so the result
+    /// belongs to `material.flipped()`.
+    fn flipped(&self, material: &Material) -> (Material, Placement) {
+        let mut pieces = Vec::with_capacity(material.len);
+        for (i, &(color, kind)) in material.pieces().iter().enumerate() {
+            pieces.push((!color, kind, self.squares[i + 2] ^ 56));
+        }
+        Placement::from_pieces(
+            self.squares[1] ^ 56,
+            self.squares[0] ^ 56,
+            pieces,
+            !self.turn,
+        )
+    }
+
+    fn from_pieces(
+        white_king: usize,
+        black_king: usize,
+        mut pieces: Vec<(Color, usize, usize)>,
+        turn: Color,
+    ) -> (Material, Placement) {
+        pieces.sort();
+        let kinds: Vec<_> = pieces
+            .iter()
+            .map(|&(color, kind, _)| (color, kind))
+            .collect();
+        let mut squares = [0; MAX_PIECES];
+        squares[0] = white_king;
+        squares[1] = black_king;
+        for (i, &(_, _, sq)) in pieces.iter().enumerate() {
+            squares[i + 2] = sq;
+        }
+        (Material::new(&kinds).unwrap(), Placement { squares, turn })
+    }
+
+    /// Read a board, normalised so the stronger side is White.
+    fn of_board(board: &Board) -> Option<(Material, Placement)> {
+        let bitboards = board.bitboards();
+        let turn = board.get_turn_color();
+        if let Some(target) = board.get_en_passant() {
+            let capturers = PAWN_ATTACKS[!turn as usize][square_index(target)];
+            if capturers & bitboards.pieces(PAWN, turn) != EMPTY {
+                return None;
+            }
+        }
+        for color in [WHITE, BLACK] {
+            let rights = board.get_castling_rights(color);
+            if rights.can_kingside_castle() || rights.can_queenside_castle() {
+                return None;
+            }
+        }
+        if bitboards.occupied().count_ones() as usize > MAX_PIECES {
+            return None;
+        }
+        let white_king = squares(bitboards.pieces(KING, WHITE)).next()?;
+        let black_king = squares(bitboards.pieces(KING, BLACK)).next()?;
+        let mut pieces = Vec::new();
+        for color in [WHITE, BLACK] {
+            for kind in QUEEN..=PAWN {
+                for sq in squares(bitboards.pieces(kind, color)) {
+                    pieces.push((color, kind, sq));
+                }
+            }
+        }
+        let (material, placement) = Placement::from_pieces(white_king, black_king, pieces, turn);
+        Some(canonical(material, placement))
+    }
+}
+
+fn canonical(material: Material, placement: Placement) -> (Material, Placement) {
+    if material.is_canonical() {
+        (material, placement)
+    } else {
+        placement.flipped(&material)
+    }
+}
+
+fn piece_color(material: &Material, i: usize) -> Color {
+    match i {
+        0 => WHITE,
+        1 => BLACK,
+        _ => material.pieces[i - 2].0,
+    }
+}
+
+fn piece_kind(material: &Material, i: usize) -> usize {
+    if i < 2 {
+        KING
+    } else {
+        material.pieces[i - 2].1
+    }
+}
+
+fn attacks(kind: usize, color: Color, sq: usize, occupied: Bitboard) -> Bitboard {
+    match kind {
+        KING => KING_ATTACKS[sq],
+        QUEEN => queen_attacks(sq, occupied),
+        ROOK => rook_attacks(sq, occupied),
+        BISHOP => bishop_attacks(sq, occupied),
+        KNIGHT => KNIGHT_ATTACKS[sq],
+        _ => PAWN_ATTACKS[color as usize][sq],
+    }
+}
+
+/// The pieces of a placement with their colors and kinds, minus captured
+/// ones.
+#[derive(Clone, Copy)]
+struct Pieces {
+    list: [(Color, usize, usize); MAX_PIECES],
+    len: usize,
+}
+
+impl Pieces {
+    fn new(material: &Material, placement: &Placement) -> Self {
+        let mut list = [(WHITE, KING, 0); MAX_PIECES];
+        for (i, piece) in list.iter_mut().enumerate().take(material.piece_count()) {
+            *piece = (
+                piece_color(material, i),
+                piece_kind(material, i),
+                placement.squares[i],
+            );
+        }
+        Pieces {
+            list,
+            len: material.piece_count(),
+        }
+    }
+
+    fn occupied(&self) -> Bitboard {
+        self.list[..self.len]
+            .iter()
+            .fold(EMPTY, |bb, &(_, _, sq)| bb | 1 << sq)
+    }
+
+    fn color(&self, color: Color) -> Bitboard {
+        self.list[..self.len]
+            .iter()
+            .filter(|(c, _, _)| *c == color)
+            .fold(EMPTY, |bb, &(_, _, sq)| bb | 1 << sq)
+    }
+
+    /// Whether `color`'s king is attacked. `skip` is a captured piece.
+    fn in_check(&self, color: Color, skip: Option<usize>) -> bool {
+        let king = self.list[if color == WHITE { 0 } else { 1 }].2;
+        let occupied = self.occupied();
+        (0..self.len).any(|i| {
+            let (c, kind, sq) = self.list[i];
+            Some(i) != skip && c != color && attacks(kind, c, sq, occupied) & (1 << king) != EMPTY
+        })
+    }
+}
+
+/// Where a legal move leads.
+enum Successor {
+    /// A quiet move: same material, other side to move.
+    Quiet(Placement),
+    /// A capture or promotion, into another table.
+    Exit(Material, Placement),
+}
+
+/// Whether a placement can occur with its side to move: no two pieces on
+/// one square, no pawns on the back ranks and the side that just moved not
+/// in check.
+fn is_legal(material: &Material, placement: &Placement) -> bool {
+    let n = material.piece_count();
+    let mut occupied = EMPTY;
+    for i in 0..n {
+        let sq = placement.squares[i];
+        if occupied & (1 << sq) != EMPTY {
+            return false;
+        }
+        occupied |= 1 << sq;
+        if piece_kind(material, i) == PAWN && !(8..56).contains(&sq) {
+            return false;
+        }
+    }
+    !Pieces::new(material, placement).in_check(!placement.turn, None)
+}
+
+/// Call `f` with the successor of every legal move of the side to move.
+fn for_each_move(material: &Material, placement: &Placement, mut f: impl FnMut(Successor)) {
+    let pieces = Pieces::new(material, placement);
+    let turn = placement.turn;
+    let occupied = pieces.occupied();
+    let own = pieces.color(turn);
+    for i in 0..material.piece_count() {
+        let (color, kind, from) = pieces.list[i];
+        if color != turn {
+            continue;
+        }
+        let targets = if kind == PAWN {
+            let forward = if turn == WHITE { 8 } else { -8i32 };
+            let one = (from as i32 + forward) as usize;
+            let mut pushes = EMPTY;
+            if occupied & (1 << one) == EMPTY {
+                pushes |= 1 << one;
+                let start_row = if turn == WHITE { 1 } else { 6 };
+                let two = (one as i32 + forward) as usize;
+                if from / 8 == start_row && occupied & (1 << two) == EMPTY {
+                    pushes |= 1 << two;
+                }
+            }
+            pushes | (PAWN_ATTACKS[turn as usize][from] & occupied & !own)
+        } else {
+            attacks(kind, color, from, occupied) & !own
+        };
+        for to in squares(targets) {
+            let captured = (0..pieces.len).find(|&j| pieces.list[j].2 == to);
+            let promotes = kind == PAWN && !(8..56).contains(&to);
+            let mut moved = pieces;
+            moved.list[i].2 = to;
+            if moved.in_check(turn, captured) {
+                continue;
+            }
+            if captured.is_none() && !promotes {
+                let mut next = *placement;
+                next.squares[i] = to;
+                next.turn = !turn;
+                f(Successor::Quiet(next));
+                continue;
+            }
+            let kinds: &[usize] = if promotes { &PROMOTIONS } else { &[kind] };
+            for &new_kind in kinds {
+                let mut rest = Vec::with_capacity(MAX_PIECES - 2);
+                for (j, &(c, k, sq)) in moved.list[..moved.len].iter().enumerate().skip(2) {
+                    if Some(j) != captured {
+                        rest.push((c, if j == i { new_kind } else { k }, sq));
+                    }
+                }
+                let (material, next) =
+                    Placement::from_pieces(moved.list[0].2, moved.list[1].2, rest, !turn);
+                f(Successor::Exit(material, next));
+            }
+        }
+    }
+}
+
+/// Call `f` with every placement from which the side not to move could have
+/// reached `placement` with a quiet move.
+fn for_each_unmove(material: &Material, placement: &Placement, mut f: impl FnMut(Placement)) {
+    let pieces = Pieces::new(material, placement);
+    let mover = !placement.turn;
+    let occupied = pieces.occupied();
+    for i in 0..material.piece_count() {
+        let (color, kind, to) = pieces.list[i];
+        if color != mover {
+            continue;
+        }
+        let origins = if kind == PAWN {
+            let back = if mover == WHITE { -8 } else { 8i32 };
+            let one = to as i32 + back;
+            let mut origins = EMPTY;
+            if (8..56).contains(&one) && occupied & (1 << one) == EMPTY {
+                origins |= 1 << one;
+                let double_row = if mover == WHITE { 3 } else { 4 };
+                let two = (one + back) as usize;
+                if to / 8 == double_row && occupied & (1 << two) == EMPTY {
+                    origins |= 1 << two;
+                }
+            }
+            origins
+        } else {
+            attacks(kind, color, to, occupied) & !occupied
+        };
+        for from in squares(origins) {
+            let mut previous = *placement;
+            previous.squares[i] = from;
+            previous.turn = mover;
+            f(previous);
+        }
+    }
+}
+
+/// The best of two results for the side choosing between them: the fastest
+/// win, else a draw, else the slowest loss.
+fn better(a: u16, b: u16) -> u16 {
+    let rank = |v: u16| match v {
+        UNKNOWN => (0, 0),
+        DRAW => (2, 0),
+        p if p % 2 == 1 => (3, u16::MAX - p),
+        p => (1, p),
+    };
+    if rank(b) > rank(a) {
+        b
+    } else {
+        a
+    }
+}
+
+/// The result for the side that made a move, from the result of the
+/// position it leads to.
+fn parent(child: u16) -> u16 {
+    match child {
+        DRAW => DRAW,
+        plies => plies + 1,
+    }
+}
+
+/// Full tables (both sides to move, every placement) of the materials
+/// generated so far, in plies.
+#[derive(Default)]
+struct Generator {
+    tables: Vec<(Material, Vec<u16>)>,
+}
+
+impl Generator {
+    fn table(&mut self, material: Material) -> &[u16] {
+        if !self.tables.iter().any(|(m, _)| *m == material) {
+            for exit in exit_materials(&material) {
+                let exit = exit.canonical();
+                if !exit.is_dead_draw() {
+                    self.table(exit);
+                }
+            }
+            let table = self.generate(&material);
+            self.tables.push((material, table));
+        }
+        &self.tables.iter().find(|(m, _)| *m == material).unwrap().1
+    }
+
+    fn lookup(&self, material: Material, placement: Placement) -> u16 {
+        let (material, placement) = canonical(material, placement);
+        if material.is_dead_draw() {
+            return DRAW;
+        }
+        let table = &self.tables.iter().find(|(m, _)| *m == material).unwrap().1;
+        match table[placement.index(&material)] {
+            UNKNOWN | ILLEGAL => DRAW,
+            value => value,
+        }
+    }
+
+    fn generate(&self, material: &Material) -> Vec<u16> {
+        let size = 1 << (6 * material.piece_count() + 1);
+        let mut value = vec![UNKNOWN; size];
+        // quiet moves not yet known to lose
+        let mut open = vec![0u8; size];
+        // the best result reachable by leaving the table
+        let mut exit = vec![UNKNOWN; size];
+        // positions decided at each ply, and positions to decide at a later
+        // ply once nothing better turned up
+        let mut decided: Vec<Vec<u32>> = vec![Vec::new()];
+        let mut pending: Vec<Vec<u32>> = Vec::new();
+        let schedule = |lists: &mut Vec<Vec<u32>>, plies: u16, index: usize| {
+            if lists.len() <= plies as usize {
+                lists.resize(plies as usize + 1, Vec::new());
+            }
+            lists[plies as usize].push(index as u32);
+        };
+
+        for index in 0..size {
+            let placement = Placement::from_index(material, index);
+            if !is_legal(material, &placement) {
+                value[index] = ILLEGAL;
+                continue;
+            }
+            let mut moves = 0;
+            let mut best_exit = UNKNOWN;
+            for_each_move(material, &placement, |successor| {
+                moves += 1;
+                match successor {
+                    Successor::Quiet(_) => open[index] += 1,
+                    Successor::Exit(m, p) => {
+                        best_exit = better(best_exit, parent(self.lookup(m, p)))
+                    }
+                }
+            });
+            if moves == 0 {
+                if Pieces::new(material, &placement).in_check(placement.turn, None) {
+                    value[index] = 0;
+                    decided[0].push(index as u32);
+                } else {
+                    value[index] = DRAW;
+                }
+                continue;
+            }
+            exit[index] = best_exit;
+            match best_exit {
+                UNKNOWN => {}
+                DRAW if open[index] == 0 => value[index] = DRAW,
+                DRAW => {}
+                plies if open[index] == 0 || plies % 2 == 1 => schedule(&mut pending, plies, index),
+                _ => {}
+            }
+        }
+
+        let mut plies = 0;
+        while plies < decided.len() || plies < pending.len() {
+            if plies >= decided.len() {
+                decided.resize(plies + 1, Vec::new());
+            }
+            if let Some(list) = pending.get_mut(plies) {
+                for index in std::mem::take(list) {
+                    if value[index as usize] == UNKNOWN {
+                        value[index as usize] = plies as u16;
+                        decided[plies].push(index);
+                    }
+                }
+            }
+            let next = plies as u16 + 1;
+            for index in std::mem::take(&mut decided[plies]) {
+                let placement = Placement::from_index(material, index as usize);
+                for_each_unmove(material, &placement, |previous| {
+                    let previous = previous.index(material);
+                    if value[previous] != UNKNOWN {
+                        return;
+                    }
+                    if plies % 2 == 0 {
+                        // moving here mates or wins
+                        value[previous] = next;
+                        schedule(&mut decided, next, previous);
+                        return;
+                    }
+                    open[previous] -= 1;
+                    if open[previous] > 0 {
+                        return;
+                    }
+                    // every quiet move loses; leaving the table may be better
+                    match exit[previous] {
+                        DRAW => value[previous] = DRAW,
+                        UNKNOWN => {
+                            value[previous] = next;
+                            schedule(&mut decided, next, previous);
+                        }
+                        p if p % 2 == 1 => {}
+                        p if p <= next => {
+                            value[previous] = next;
+                            schedule(&mut decided, next, previous);
+                        }
+                        p => schedule(&mut pending, p, previous),
+                    }
+                });
+            }
+            plies += 1;
+        }
+
+        for v in value.iter_mut() {
+            if *v == UNKNOWN {
+                *v = DRAW;
+            }
+        }
+        value
+    }
+}
+
+/// The materials a capture or promotion can lead to.
+fn exit_materials(material: &Material) -> Vec<Material> {
+    let pieces = material.pieces();
+    let mut exits = Vec::new();
+    let without = |skip: usize, promoted: Option<(usize, usize)>| {
+        let rest: Vec<_> = (0..pieces.len())
+            .filter(|&j| j != skip)
+            .map(|j| match promoted {
+                Some((i, kind)) if i == j => (pieces[j].0, kind),
+                _ => pieces[j],
+            })
+            .collect();
+        Material::new(&rest).unwrap()
+    };
+    for captured in 0..pieces.len() {
+        exits.push(without(captured, None));
+    }
+    for (i, &(_, kind)) in pieces.iter().enumerate() {
+        if kind != PAWN {
+            continue;
+        }
+        for &promoted in &PROMOTIONS {
+            exits.push(without(usize::MAX, Some((i, promoted))));
+            for captured in (0..pieces.len()).filter(|&j| j != i) {
+                exits.push(without(captured, Some((i, promoted))));
+            }
+        }
+    }
+    exits
+}
+
+/// The placement's position in a compact table: which of the stored
+/// mirror images it is, and where that image is stored.
+fn compact_index(material: &Material, placement: &Placement) -> usize {
+    let n = material.piece_count();
+    let king = placement.squares[0];
+    let mirror_file = king % 8 > 3;
+    let mirror_rank = !material.has_pawns() && king / 8 > 3;
+    let transform = |sq: usize| {
+        let mut sq = sq;
+        if mirror_file {
+            sq ^= 7;
+        }
+        if mirror_rank {
+            sq ^= 56;
+        }
+        sq
+    };
+    let mut squares = [0; MAX_PIECES];
+    for (i, sq) in squares.iter_mut().enumerate().take(n) {
+        *sq = transform(placement.squares[i]);
+    }
+    let (row, col) = (squares[0] / 8, squares[0] % 8);
+    let region = if material.has_pawns() {
+        row * 4 + col
+    } else {
+        if row > col {
+            // reflect in the a1-h8 diagonal
+            for sq in squares.iter_mut().take(n) {
+                *sq = (*sq % 8) * 8 + *sq / 8;
+            }
+        }
+        let (row, col) = (row.min(col), row.max(col));
+        // the triangle a1-d1-d4, row by row
+        [0, 4, 7, 9][row] + col - row
+    };
+    squares[1..n]
+        .iter()
+        .fold(region, |index, &sq| index * 64 + sq)
+}
+
+fn region_squares(material: &Material) -> Vec<usize> {
+    if material.has_pawns() {
+        (0..8)
+            .flat_map(|row| (0..4).map(move |col| row * 8 + col))
+            .collect()
+    } else {
+        (0..4)
+            .flat_map(|row| (row..4).map(move |col| row * 8 + col))
+            .collect()
+    }
+}
+
+/// A compact endgame table: positions with the stronger side (White) to
+/// move, one mirror image each, one byte per position.
+pub struct Bitbase {
+    material: Material,
+    data: Cow<'static, [u8]>,
+}
+
+impl Bitbase {
+    /// Build the table for `material` (normalised so the stronger side is
+    /// White) by retrograde analysis. Four-piece tables take a while and a
+    /// few hundred MB; build them with optimizations.
+    pub fn generate(material: Material) -> Self {
+        let material = material.canonical();
+        let mut generator = Generator::default();
+        let table = generator.table(material);
+        let rest = 1 << (6 * (material.piece_count() - 1));
+        let mut data = Vec::new();
+        for king in region_squares(&material) {
+            for index in 0..rest {
+                let index = (king * rest + index) << 1;
+                let byte = match table[index] {
+                    ILLEGAL | UNKNOWN => 0,
+                    plies => BitbaseEntry::from_plies(plies).to_byte(),
+                };
+                data.push(byte);
+            }
+        }
+        Bitbase {
+            material,
+            data: Cow::Owned(data),
+        }
+    }
+
+    /// A table previously returned by [`Bitbase::as_bytes`].
+    pub fn from_bytes(material: Material, bytes: Vec<u8>) -> Result<Self, String> {
+        let material = material.canonical();
+        let expected = region_squares(&material).len() << (6 * (material.piece_count() - 1));
+        if bytes.len() != expected {
+            return Err(format!(
+                "a {} bitbase has {} bytes, got {}",
+                material,
+                expected,
+                bytes.len()
+            ));
+        }
+        Ok(Bitbase {
+            material,
+            data: Cow::Owned(bytes),
+        })
+    }
+
+    pub fn material(&self) -> Material {
+        self.material
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// The result for the side to move, if `board` has this table's
+    /// material or one of the compiled-in endings.
+    pub fn probe(&self, board: &Board) -> Option<BitbaseEntry> {
+        let (material, placement) = Placement::of_board(board)?;
+        let plies = if material == self.material {
+            self.probe_placement(&placement)?
+        } else {
+            probe_compiled(material, placement)?
+        };
+        Some(BitbaseEntry::from_plies(plies))
+    }
+
+    /// The move that keeps the best result for the side to move, like
+    /// [`best_move`].
+    pub fn best_move(&self, board: &Board) -> Option<Move> {
+        best_move_by(board, |board| self.probe(board))
+    }
+
+    /// The same table without the distances to mate.
+    pub fn to_wdl(&self) -> WdlBitbase {
+        let entries: Vec<_> = self
+            .data
+            .iter()
+            .map(|&byte| Wdl::from(BitbaseEntry::from_byte(byte)))
+            .collect();
+        let bits = if entries.contains(&Wdl::Loss) { 2 } else { 1 };
+        let per_byte = 8 / bits;
+        let data = entries
+            .chunks(per_byte)
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .enumerate()
+                    .fold(0, |byte, (i, wdl)| byte | wdl.to_bits() << (i * bits))
+            })
+            .collect();
+        WdlBitbase {
+            material: self.material,
+            bits,
+            data: Cow::Owned(data),
+        }
+    }
+
+    fn probe_placement(&self, placement: &Placement) -> Option<u16> {
+        if placement.turn == WHITE {
+            let byte = self.data[compact_index(&self.material, placement)];
+            return Some(BitbaseEntry::from_byte(byte).plies());
+        }
+        // the weaker side to move: try every move
+        let mut best = UNKNOWN;
+        let mut complete = true;
+        for_each_move(&self.material, placement, |successor| {
+            let child = match successor {
+                Successor::Quiet(next) => self.probe_placement(&next),
+                Successor::Exit(m, p) => {
+                    let (m, p) = canonical(m, p);
+                    probe_compiled(m, p)
+                }
+            };
+            match child {
+                Some(child) => best = better(best, parent(child)),
+                None => complete = false,
+            }
+        });
+        if !complete {
+            return None;
+        }
+        if best == UNKNOWN {
+            let pieces = Pieces::new(&self.material, placement);
+            best = if pieces.in_check(placement.turn, None) {
+                0
+            } else {
+                DRAW
+            };
+        }
+        Some(best)
+    }
+}
+
+/// A compact win/draw/loss table: positions with the stronger side (White)
+/// to move, one mirror image each, in one bit per position if that side
+/// never loses and two bits otherwise.
+pub struct WdlBitbase {
+    material: Material,
+    bits: usize,
+    data: Cow<'static, [u8]>,
+}
+
+impl WdlBitbase {
+    /// A table previously returned by [`WdlBitbase::as_bytes`], with `bits`
+    /// bits per position.
+    pub fn from_bytes(material: Material, bits: usize, bytes: Vec<u8>) -> Result<Self, String> {
+        let material = material.canonical();
+        if bits != 1 && bits != 2 {
+            return Err(format!(
+                "a bitbase has 1 or 2 bits per position, not {}",
+                bits
+            ));
+        }
+        let positions = region_squares(&material).len() << (6 * (material.piece_count() - 1));
+        let expected = (positions * bits).div_ceil(8);
+        if bytes.len() != expected {
+            return Err(format!(
+                "a {} bitbase has {} bytes, got {}",
+                material,
+                expected,
+                bytes.len()
+            ));
+        }
+        Ok(WdlBitbase {
+            material,
+            bits,
+            data: Cow::Owned(bytes),
+        })
+    }
+
+    pub fn material(&self) -> Material {
+        self.material
+    }
+
+    /// The number of bits per position.
+    pub fn bits(&self) -> usize {
+        self.bits
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// The result for the side to move, if `board` has this table's
+    /// material or one of the compiled-in endings.
+    pub fn probe(&self, board: &Board) -> Option<Wdl> {
+        let (material, placement) = Placement::of_board(board)?;
+        if material == self.material {
+            self.probe_placement(&placement)
+        } else {
+            probe_compiled_wdl(material, placement)
+        }
+    }
+
+    fn probe_placement(&self, placement: &Placement) -> Option<Wdl> {
+        if placement.turn == WHITE {
+            let index = compact_index(&self.material, placement) * self.bits;
+            let bits = self.data[index / 8] >> (index % 8) & ((1 << self.bits) - 1);
+            return Some(Wdl::from_bits(bits));
+        }
+        // the weaker side to move: try every move
+        let mut best = None;
+        let mut complete = true;
+        for_each_move(&self.material, placement, |successor| {
+            let child = match successor {
+                Successor::Quiet(next) => self.probe_placement(&next),
+                Successor::Exit(m, p) => {
+                    let (m, p) = canonical(m, p);
+                    probe_compiled_wdl(m, p)
+                }
+            };
+            match child {
+                Some(child) => best = best.max(Some(child.parent())),
+                None => complete = false,
+            }
+        });
+        if !complete {
+            return None;
+        }
+        best.or_else(|| {
+            let pieces = Pieces::new(&self.material, placement);
+            Some(if pieces.in_check(placement.turn, None) {
+                Wdl::Loss
+            } else {
+                Wdl::Draw
+            })
+        })
+    }
+}
+
+fn probe_compiled(material: Material, placement: Placement) -> Option<u16> {
+    if material.is_dead_draw() {
+        return Some(DRAW);
+    }
+    let table = [&KQK_TABLE, &KRK_TABLE, &KPK_TABLE]
+        .into_iter()
+        .find(|table| table.material == material)?;
+    table.probe_placement(&placement)
+}
+
+fn probe_compiled_wdl(material: Material, placement: Placement) -> Option<Wdl> {
+    if let Some(plies) = probe_compiled(material, placement) {
+        return Some(BitbaseEntry::from_plies(plies).into());
+    }
+    let table = [&KBNK_TABLE, &KQKR_TABLE]
+        .into_iter()
+        .find(|table| table.material == material)?;
+    table.probe_placement(&placement)
+}
+
+/// The result for the side to move from the compiled-in bitbases, or `None`
+/// if the position is not one of their endings.
+pub fn probe(board: &Board) -> Option<BitbaseEntry> {
+    let (material, placement) = Placement::of_board(board)?;
+    probe_compiled(material, placement).map(BitbaseEntry::from_plies)
+}
+
+/// Whether the side to move wins, draws or loses according to the
+/// compiled-in bitbases, four-piece ones included, or `None` if the position
+/// is not one of their endings.
+pub fn probe_wdl(board: &Board) -> Option<Wdl> {
+    let (material, placement) = Placement::of_board(board)?;
+    probe_compiled_wdl(material, placement)
+}
+
+/// The move that keeps the best result for the side to move according to
+/// the compiled-in bitbases: the fastest mate, else a draw, else the
+/// slowest loss.
+pub fn best_move(board: &Board) -> Option<Move> {
+    best_move_by(board, probe)
+}
+
+fn best_move_by(board: &Board, probe: impl Fn(&Board) -> Option<BitbaseEntry>) -> Option<Move> {
+    // only positions a table covers, so a position the tables do not know
+    // is never played into by accident
+    probe(board)?;
+    let mut best: Option<(Move, u16)> = None;
+    for mv in board.get_legal_moves() {
+        let child = probe(&board.apply_eval_move(mv))?;
+        let value = parent(child.plies());
+        if best.is_none_or(|(_, b)| better(b, value) != b) {
+            best = Some((mv, value));
+        }
+    }
+    best.map(|(mv, _)| mv)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_fen, GameResult};
+
+    fn entry(fen: &str) -> BitbaseEntry {
+        probe(&parse_fen(fen).unwrap()).expect("position should be in a bitbase")
+    }
+
+    #[test]
+    fn material_signatures() {
+        for signature in ["KQK", "KRK", "KPK", "KBNK", "KQKR", "KPKP", "KK"] {
+            assert_eq!(
+                signature.parse::<Material>().unwrap().to_string(),
+                signature
+            );
+        }
+        assert_eq!("KQK".parse::<Material>().unwrap(), Material::KQK);
+        assert_eq!("KBNK".parse::<Material>().unwrap(), Material::KBNK);
+        assert_eq!("KQKR".parse::<Material>().unwrap(), Material::KQKR);
+        assert_eq!("KNBK".parse::<Material>().unwrap().to_string(), "KBNK");
+        assert!("KQ".parse::<Material>().is_err());
+        assert!("KQRBK".parse::<Material>().is_err());
+        assert!("KXK".parse::<Material>().is_err());
+        assert!(!"KKQ".parse::<Material>().unwrap().is_canonical());
+    }
+
+    /// The checked-in tables must be what the generator builds; set
+    /// `REGEN_BITBASES=1` to rewrite them instead. The four-piece tables take
+    /// minutes to generate, so they are only built when rewriting.
+    #[test]
+    fn compiled_tables_match_the_generator() {
+        for (table, file) in [
+            (&KQK_TABLE, "kqk"),
+            (&KRK_TABLE, "krk"),
+            (&KPK_TABLE, "kpk"),
+        ] {
+            let generated = Bitbase::generate(table.material);
+            if std::env::var_os("REGEN_BITBASES").is_some() {
+                let path = format!("{}/bitbases/{}.bin", env!("CARGO_MANIFEST_DIR"), file);
+                std::fs::write(path, generated.as_bytes()).unwrap();
+            } else {
+                assert!(
+                    generated.as_bytes() == table.as_bytes(),
+                    "bitbases/{}.bin is stale",
+                    file
+                );
+            }
+        }
+        if std::env::var_os("REGEN_BITBASES").is_some() {
+            for (table, file) in [(&KBNK_TABLE, "kbnk"), (&KQKR_TABLE, "kqkr")] {
+                let generated = Bitbase::generate(table.material).to_wdl();
+                assert_eq!(generated.bits(), table.bits(), "{}", file);
+                let path = format!("{}/bitbases/{}.wdl", env!("CARGO_MANIFEST_DIR"), file);
+                std::fs::write(path, generated.as_bytes()).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn longest_mates() {
+        let longest = |table: &Bitbase| {
+            table
+                .as_bytes()
+                .iter()
+                .filter(|&&b| (1..128).contains(&b))
+                .max()
+                .copied()
+        };
+        assert_eq!(longest(&KQK_TABLE), Some(10));
+        assert_eq!(longest(&KRK_TABLE), Some(16));
+    }
+
+    #[test]
+    fn probes_known_positions() {
+        assert_eq!(entry("k7/8/1K6/8/8/8/7Q/8 w - - 0 1"), BitbaseEntry::Win(1));
+        assert_eq!(
+            entry("k6Q/8/1K6/8/8/8/8/8 b - - 0 1"),
+            BitbaseEntry::Loss(0)
+        );
+        // colors reversed
+        assert_eq!(
+            entry("6q1/8/8/8/8/1k6/8/K7 b - - 0 1"),
+            BitbaseEntry::Win(1)
+        );
+        // the queen hangs
+        assert_eq!(entry("8/8/8/8/8/8/1Q6/2k4K b - - 0 1"), BitbaseEntry::Draw);
+        // stalemate
+        assert_eq!(entry("k7/2Q5/1K6/8/8/8/8/8 b - - 0 1"), BitbaseEntry::Draw);
+        // the side to move in KPK decides the opposition
+        assert_eq!(entry("8/4k3/8/4K3/4P3/8/8/8 w - - 0 1"), BitbaseEntry::Draw);
+        assert!(matches!(
+            entry("8/4k3/8/4K3/4P3/8/8/8 b - - 0 1"),
+            BitbaseEntry::Loss(_)
+        ));
+        // a rook pawn with the defender in the corner
+        assert_eq!(entry("k7/8/K7/P7/8/8/8/8 w - - 0 1"), BitbaseEntry::Draw);
+        // the black king is outside the square of the pawn
+        assert!(matches!(
+            entry("7k/8/8/8/8/8/P7/K7 w - - 0 1"),
+            BitbaseEntry::Win(_)
+        ));
+        // not covered: castling rights, or too many pieces
+        assert_eq!(
+            probe(&parse_fen("4k3/8/8/8/8/8/8/R3K3 w Q - 0 1").unwrap()),
+            None
+        );
+        assert_eq!(
+            probe(&parse_fen("4k3/8/8/8/8/8/8/R3K2R w - - 0 1").unwrap()),
+            None
+        );
+    }
+
+    #[test]
+    fn best_moves_mate_in_the_announced_number_of_moves() {
+        for fen in [
+            "8/8/8/3k4/8/8/8/R3K3 w - - 0 1",
+            "7k/8/8/8/8/8/P7/K7 w - - 0 1",
+        ] {
+            let mut board = parse_fen(fen).unwrap();
+            let BitbaseEntry::Win(moves) = probe(&board).unwrap() else {
+                panic!("{} should be won", fen);
+            };
+            for ply in 1.. {
+                let mv = best_move(&board).unwrap();
+                match board.play_move(mv) {
+                    GameResult::Victory(WHITE) => {
+                        assert_eq!(ply, 2 * moves as usize - 1, "{}", fen);
+                        break;
+                    }
+                    GameResult::Continuing(next) => board = next,
+                    result => panic!("{}: unexpected {:?}", fen, result),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn search_returns_bitbase_mates() {
+        use crate::{DepthLimit, Score, FLAG_ENDGAME_HEURISTICS};
+        let board = parse_fen("8/8/8/3k4/8/8/8/R3K3 w - - 0 1").unwrap();
+        let BitbaseEntry::Win(moves) = probe(&board).unwrap() else {
+            panic!("KRK should be won");
+        };
+        let result = board.get_next_move(&[2], [0; 32], &DepthLimit(2), FLAG_ENDGAME_HEURISTICS);
+        assert_eq!(result.score, Score::Mate(moves as i32));
+
+        // KQKR is only known to be won
+        let board = parse_fen("4k3/8/8/8/8/2r5/8/3QK3 w - - 0 1").unwrap();
+        let result = board.get_next_move(&[1], [0; 32], &DepthLimit(1), FLAG_ENDGAME_HEURISTICS);
+        assert!(matches!(result.score, Score::Centipawns(cp) if cp > KNOWN_WIN));
+    }
+
+    #[test]
+    fn probes_four_piece_tables() {
+        let wdl = |fen: &str| probe_wdl(&parse_fen(fen).unwrap());
+        assert_eq!(wdl("8/8/8/8/8/2k5/8/KBN5 w - - 0 1"), Some(Wdl::Win));
+        let board = parse_fen("8/8/8/8/8/2k5/8/KBN5 w - - 0 1").unwrap();
+        let mv = crate::get_endgame_move(&board).unwrap();
+        assert_eq!(probe_wdl(&board.apply_eval_move(mv)), Some(Wdl::Loss));
+        // checkmate
+        assert_eq!(wdl("k7/B1N5/1K6/8/8/8/8/8 b - - 0 1"), Some(Wdl::Loss));
+        // the king takes the checking bishop
+        assert_eq!(wdl("7K/8/8/8/8/8/1k6/B1N5 b - - 0 1"), Some(Wdl::Draw));
+        // the queen takes a loose rook
+        assert_eq!(wdl("7k/8/8/8/8/8/8/r2QK3 w - - 0 1"), Some(Wdl::Win));
+        // the queen must give itself up for the checking rook
+        assert_eq!(wdl("8/8/8/8/8/8/4k3/K2r3Q w - - 0 1"), Some(Wdl::Draw));
+        // the rook skewers the king and the queen
+        assert_eq!(wdl("r7/7k/8/8/K7/8/8/Q7 w - - 0 1"), Some(Wdl::Loss));
+        // colors reversed
+        assert_eq!(wdl("q7/8/8/8/k7/8/7K/R7 b - - 0 1"), Some(Wdl::Loss));
+        // the three-piece tables answer too
+        assert_eq!(wdl("k7/8/1K6/8/8/8/7Q/8 w - - 0 1"), Some(Wdl::Win));
+        assert_eq!(wdl("4k3/8/8/8/8/8/8/R3K2R w - - 0 1"), None);
+        assert_eq!(wdl("4k3/8/8/8/8/8/3PP3/4K3 w - - 0 1"), None);
+        assert_eq!(KBNK_TABLE.bits(), 1);
+        assert_eq!(KQKR_TABLE.bits(), 2);
+    }
+}
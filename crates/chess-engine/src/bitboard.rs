@@ -0,0 +1,356 @@
+//! Bitboards and precomputed attack tables.
+//!
+//! A bitboard is a `u64` with one bit per square, where bit `row * 8 + col`
+//! is the square at that [`Position`] (a1 = 0, h1 = 7, a8 = 56, h8 = 63).
+//! The [`Board`](crate::Board) keeps one bitboard per color and one per piece
+//! type next to its `squares` array, so attack and occupancy questions are a
+//! handful of bitwise operations instead of a scan over all 64 squares.
+//!
+//! Knight, king and pawn attacks are looked up directly. Slider attacks use
+//! the classical ray approach: the ray in each direction is cut off behind
+//! the first blocker, found with a single bit scan. All tables are built at
+//! compile time, so they cost no gas to initialize.
+
+use crate::{Color, Piece, Position};
+
+pub type Bitboard = u64;
+
+pub const EMPTY: Bitboard = 0;
+pub const RANK_1: Bitboard = 0xff;
+pub const RANK_8: Bitboard = 0xff << 56;
+pub const FILE_A: Bitboard = 0x0101_0101_0101_0101;
+pub const FILE_H: Bitboard = FILE_A << 7;
+
+/// Piece type indices into [`Bitboards`], in the same order as
+/// [`Piece::zobrist_indices`].
+pub const KING: usize = 0;
+pub const QUEEN: usize = 1;
+pub const ROOK: usize = 2;
+pub const BISHOP: usize = 3;
+pub const KNIGHT: usize = 4;
+pub const PAWN: usize = 5;
+
+/// Ray directions as (row, col) steps. The first four point towards higher
+/// square indices, the last four towards lower ones.
+const DIRECTIONS: [(i32, i32); 8] = [
+    (1, 0),
+    (0, 1),
+    (1, 1),
+    (1, -1),
+    (-1, 0),
+    (0, -1),
+    (-1, -1),
+    (-1, 1),
+];
+const ORTHOGONAL_DIRECTIONS: [usize; 4] = [0, 1, 4, 5];
+const DIAGONAL_DIRECTIONS: [usize; 4] = [2, 3, 6, 7];
+
+const KNIGHT_STEPS: [(i32, i32); 8] = [
+    (1, 2),
+    (2, 1),
+    (2, -1),
+    (1, -2),
+    (-1, -2),
+    (-2, -1),
+    (-2, 1),
+    (-1, 2),
+];
+const KING_STEPS: [(i32, i32); 8] = [
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+];
+
+const fn step_table(steps: &[(i32, i32); 8]) -> [Bitboard; 64] {
+    let mut table = [EMPTY; 64];
+    let mut sq = 0;
+    while sq < 64 {
+        let (row, col) = ((sq / 8) as i32, (sq % 8) as i32);
+        let mut i = 0;
+        while i < steps.len() {
+            let (r, c) = (row + steps[i].0, col + steps[i].1);
+            if r >= 0 && r < 8 && c >= 0 && c < 8 {
+                table[sq] |= 1 << (r * 8 + c);
+            }
+            i += 1;
+        }
+        sq += 1;
+    }
+    table
+}
+
+const fn pawn_table() -> [[Bitboard; 64]; 2] {
+    let mut table = [[EMPTY; 64]; 2];
+    let mut sq = 0;
+    while sq < 64 {
+        let (row, col) = ((sq / 8) as i32, (sq % 8) as i32);
+        // white pawns attack towards rank 8, black pawns towards rank 1
+        let mut color = 0;
+        while color < 2 {
+            let r = if color == 0 { row + 1 } else { row - 1 };
+            if r >= 0 && r < 8 {
+                if col > 0 {
+                    table[color][sq] |= 1 << (r * 8 + col - 1);
+                }
+                if col < 7 {
+                    table[color][sq] |= 1 << (r * 8 + col + 1);
+                }
+            }
+            color += 1;
+        }
+        sq += 1;
+    }
+    table
+}
+
+const fn ray_table() -> [[Bitboard; 64]; 8] {
+    let mut table = [[EMPTY; 64]; 8];
+    let mut dir = 0;
+    while dir < 8 {
+        let mut sq = 0;
+        while sq < 64 {
+            let (mut r, mut c) = ((sq / 8) as i32, (sq % 8) as i32);
+            loop {
+                r += DIRECTIONS[dir].0;
+                c += DIRECTIONS[dir].1;
+                if r < 0 || r >= 8 || c < 0 || c >= 8 {
+                    break;
+                }
+                table[dir][sq] |= 1 << (r * 8 + c);
+            }
+            sq += 1;
+        }
+        dir += 1;
+    }
+    table
+}
+
+pub const KNIGHT_ATTACKS: [Bitboard; 64] = step_table(&KNIGHT_STEPS);
+pub const KING_ATTACKS: [Bitboard; 64] = step_table(&KING_STEPS);
+/// Squares attacked by a pawn, indexed by `[color as usize][square]`.
+pub const PAWN_ATTACKS: [[Bitboard; 64]; 2] = pawn_table();
+const RAYS: [[Bitboard; 64]; 8] = ray_table();
+
+/// The bit index of a position on the board.
+#[inline]
+pub fn square_index(pos: Position) -> usize {
+    (pos.get_row() * 8 + pos.get_col()) as usize
+}
+
+/// The position of a bit index.
+#[inline]
+pub fn square_position(sq: usize) -> Position {
+    Position::new((sq / 8) as i32, (sq % 8) as i32)
+}
+
+/// A bitboard with only the square of `pos` set.
+#[inline]
+pub fn square_bit(pos: Position) -> Bitboard {
+    1 << square_index(pos)
+}
+
+/// Iterate over the indices of the set bits, lowest first.
+#[inline]
+pub fn squares(mut bb: Bitboard) -> impl Iterator<Item = usize> {
+    core::iter::from_fn(move || {
+        if bb == EMPTY {
+            return None;
+        }
+        let sq = bb.trailing_zeros() as usize;
+        bb &= bb - 1;
+        Some(sq)
+    })
+}
+
+#[inline]
+fn ray_attacks(dir: usize, sq: usize, occupied: Bitboard) -> Bitboard {
+    let ray = RAYS[dir][sq];
+    let blockers = ray & occupied;
+    if blockers == EMPTY {
+        return ray;
+    }
+    let blocker = if dir < 4 {
+        blockers.trailing_zeros() as usize
+    } else {
+        63 - blockers.leading_zeros() as usize
+    };
+    ray ^ RAYS[dir][blocker]
+}
+
+/// Squares a rook on `sq` attacks, up to and including the first blocker in
+/// each direction.
+#[inline]
+pub fn rook_attacks(sq: usize, occupied: Bitboard) -> Bitboard {
+    ORTHOGONAL_DIRECTIONS
+        .iter()
+        .fold(EMPTY, |bb, &dir| bb | ray_attacks(dir, sq, occupied))
+}
+
+/// Squares a bishop on `sq` attacks, up to and including the first blocker
+/// in each direction.
+#[inline]
+pub fn bishop_attacks(sq: usize, occupied: Bitboard) -> Bitboard {
+    DIAGONAL_DIRECTIONS
+        .iter()
+        .fold(EMPTY, |bb, &dir| bb | ray_attacks(dir, sq, occupied))
+}
+
+#[inline]
+pub fn queen_attacks(sq: usize, occupied: Bitboard) -> Bitboard {
+    rook_attacks(sq, occupied) | bishop_attacks(sq, occupied)
+}
+
+/// The squares strictly between `a` and `b` if they share a rank, file or
+/// diagonal, otherwise an empty bitboard.
+pub fn between(a: usize, b: usize) -> Bitboard {
+    for rays in &RAYS {
+        if rays[a] & (1 << b) != EMPTY {
+            return rays[a] & !rays[b] & !(1 << b);
+        }
+    }
+    EMPTY
+}
+
+/// The whole rank, file or diagonal through `a` and `b`, including both, or
+/// an empty bitboard if they are not aligned.
+pub fn line(a: usize, b: usize) -> Bitboard {
+    for dir in 0..4 {
+        // the opposite of direction `dir` is `dir + 4`
+        let line = RAYS[dir][a] | RAYS[dir + 4][a];
+        if line & (1 << b) != EMPTY {
+            return line | (1 << a);
+        }
+    }
+    EMPTY
+}
+
+/// Occupancy of a position by color and by piece type.
+///
+/// This is derived from the board's `squares` and never stored in contract
+/// state; it is rebuilt whenever a board is deserialized.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Bitboards {
+    colors: [Bitboard; 2],
+    pieces: [Bitboard; 6],
+}
+
+impl Bitboards {
+    /// Set the square of `piece` to hold it. The square must be empty.
+    #[inline]
+    pub(crate) fn put(&mut self, piece: Piece) {
+        let (kind, color) = piece.zobrist_indices();
+        let bit = square_bit(piece.get_pos());
+        self.colors[color] |= bit;
+        self.pieces[kind] |= bit;
+    }
+
+    /// Clear the square at `pos`, whatever it holds.
+    #[inline]
+    pub(crate) fn remove(&mut self, pos: Position) {
+        let mask = !square_bit(pos);
+        for bb in self.colors.iter_mut().chain(self.pieces.iter_mut()) {
+            *bb &= mask;
+        }
+    }
+
+    /// All occupied squares.
+    #[inline]
+    pub fn occupied(&self) -> Bitboard {
+        self.colors[0] | self.colors[1]
+    }
+
+    /// The squares occupied by pieces of `color`.
+    #[inline]
+    pub fn color(&self, color: Color) -> Bitboard {
+        self.colors[color as usize]
+    }
+
+    /// The squares occupied by pieces of type `kind` (e.g. [`KNIGHT`]) of
+    /// either color.
+    #[inline]
+    pub fn kind(&self, kind: usize) -> Bitboard {
+        self.pieces[kind]
+    }
+
+    /// The squares occupied by pieces of type `kind` and `color`.
+    #[inline]
+    pub fn pieces(&self, kind: usize, color: Color) -> Bitboard {
+        self.pieces[kind] & self.colors[color as usize]
+    }
+
+    /// The pieces of `color` that attack `sq`, given the `occupied` squares.
+    /// Passing an occupancy different from the board's lets callers look
+    /// through pieces, e.g. the king itself when checking its escape squares.
+    #[inline]
+    pub fn attackers_to(&self, sq: usize, color: Color, occupied: Bitboard) -> Bitboard {
+        let diagonal = self.pieces[BISHOP] | self.pieces[QUEEN];
+        let orthogonal = self.pieces[ROOK] | self.pieces[QUEEN];
+        self.colors[color as usize]
+            & ((PAWN_ATTACKS[!color as usize][sq] & self.pieces[PAWN])
+                | (KNIGHT_ATTACKS[sq] & self.pieces[KNIGHT])
+                | (KING_ATTACKS[sq] & self.pieces[KING])
+                | (bishop_attacks(sq, occupied) & diagonal)
+                | (rook_attacks(sq, occupied) & orthogonal))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bb(squares: &[&str]) -> Bitboard {
+        squares
+            .iter()
+            .map(|s| square_bit(Position::pgn(s).unwrap()))
+            .fold(EMPTY, |acc, bit| acc | bit)
+    }
+
+    fn sq(s: &str) -> usize {
+        square_index(Position::pgn(s).unwrap())
+    }
+
+    #[test]
+    fn test_step_attacks() {
+        assert_eq!(KNIGHT_ATTACKS[sq("a1")], bb(&["b3", "c2"]));
+        assert_eq!(KING_ATTACKS[sq("h8")], bb(&["g8", "g7", "h7"]));
+        assert_eq!(PAWN_ATTACKS[Color::White as usize][sq("a2")], bb(&["b3"]));
+        assert_eq!(
+            PAWN_ATTACKS[Color::Black as usize][sq("e7")],
+            bb(&["d6", "f6"])
+        );
+        assert_eq!(PAWN_ATTACKS[Color::White as usize][sq("e8")], EMPTY);
+    }
+
+    #[test]
+    fn test_slider_attacks() {
+        let occupied = bb(&["d6", "f4", "b2"]);
+        assert_eq!(
+            rook_attacks(sq("d4"), occupied),
+            bb(&["d5", "d6", "e4", "f4", "c4", "b4", "a4", "d3", "d2", "d1"])
+        );
+        assert_eq!(
+            bishop_attacks(sq("d4"), occupied),
+            bb(&["e5", "f6", "g7", "h8", "c5", "b6", "a7", "e3", "f2", "g1", "c3", "b2"])
+        );
+        assert_eq!(rook_attacks(sq("a1"), EMPTY), (FILE_A | RANK_1) ^ 1);
+    }
+
+    #[test]
+    fn test_between() {
+        assert_eq!(between(sq("a1"), sq("d4")), bb(&["b2", "c3"]));
+        assert_eq!(between(sq("e8"), sq("e5")), bb(&["e7", "e6"]));
+        assert_eq!(between(sq("e1"), sq("f1")), EMPTY);
+        assert_eq!(between(sq("a1"), sq("b3")), EMPTY);
+        assert_eq!(
+            line(sq("c3"), sq("b2")),
+            bb(&["a1", "b2", "c3", "d4", "e5", "f6", "g7", "h8"])
+        );
+        assert_eq!(line(sq("e1"), sq("e4")), FILE_A << 4);
+        assert_eq!(line(sq("a1"), sq("b3")), EMPTY);
+    }
+}
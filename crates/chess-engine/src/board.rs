@@ -1,11 +1,10 @@
+use super::bitboard::*;
+use super::search::{is_mate_value, limit_depths, value_from_tt, value_to_tt};
 use super::transposition_table::*;
 use super::zobrist_keys::*;
 use super::*;
 use either::Either;
-use near_sdk::{
-    borsh::{BorshDeserialize, BorshSerialize},
-    env, Gas,
-};
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
 use rand::{seq::IndexedRandom, SeedableRng};
 use rand_chacha::ChaCha20Rng;
 use std::cmp::Ordering;
@@ -19,18 +18,30 @@ pub const FLAG_LATE_MOVE_REDUCTION: u16 = 0b0010_0000;
 pub const FLAG_ITERATIVE_DEEPENING: u16 = 0b1000_0000;
 pub const FLAG_OPENING_BOOK: u16 = 0b0000_0001_0000_0000;
 pub const FLAG_ENDGAME_HEURISTICS: u16 = 0b0000_0010_0000_0000;
+pub const FLAG_PRINCIPAL_VARIATION_SEARCH: u16 = 0b0000_0100_0000_0000;
+pub const FLAG_ASPIRATION_WINDOWS: u16 = 0b0000_1000_0000_0000;
+pub const FLAG_HISTORY_HEURISTIC: u16 = 0b0001_0000_0000_0000;
 
 const MAX_PLY: usize = 64;
 
-/// Mate score. Chosen well above any reachable material/positional eval (both
-/// kings are always on the board in legal play, so their ~999990 weighted
-/// values cancel out and `value_for` stays in the low thousands). Mate scores
-/// are encoded as `MATE - ply` (sooner mates score higher), and draws as 0.
-const MATE: f64 = 1_000_000.0;
+/// Mate score, in the centipawns of [`Board::value_for`]. Chosen well above
+/// any reachable material/positional eval (both kings are always on the board
+/// in legal play, so their ~9999900 weighted values cancel out and
+/// `value_for` stays in the low ten thousands). Mate scores are encoded as
+/// `MATE - ply` (sooner mates score higher), and draws as 0.
+pub const MATE: i32 = 1_000_000;
+/// Half the width of the aspiration window around the previous iteration's
+/// score, in centipawns.
+const ASPIRATION_WINDOW: i32 = 50;
+/// History scores are halved once one of them reaches this.
+const HISTORY_MAX: i32 = 1 << 14;
+/// The most a history score adds to a quiet move's ordering score, keeping
+/// it below countermoves and killers.
+const HISTORY_ORDERING_MAX: i32 = 250;
 /// Sentinel "worst/best possible, no move considered yet" values. These sit
 /// beyond any real score (including mate) so they never falsely match.
-const NEG_INFINITY: f64 = -1_000_000_000.0;
-const POS_INFINITY: f64 = 1_000_000_000.0;
+const NEG_INFINITY: i32 = -1_000_000_000;
+const POS_INFINITY: i32 = 1_000_000_000;
 
 pub struct BoardBuilder {
     board: Board,
@@ -59,7 +70,7 @@ impl BoardBuilder {
         }
 
         for _ in 0..8 {
-            *self.board.get_square(pos) = Square::from(piece.move_to(pos));
+            self.board.add_piece(piece.move_to(pos));
             pos = pos.next_right();
         }
 
@@ -73,7 +84,7 @@ impl BoardBuilder {
         }
 
         for _ in 0..8 {
-            *self.board.get_square(pos) = Square::from(piece.move_to(pos));
+            self.board.add_piece(piece.move_to(pos));
             pos = pos.next_above();
         }
 
@@ -81,8 +92,7 @@ impl BoardBuilder {
     }
 
     pub fn piece(mut self, piece: Piece) -> Self {
-        let pos = piece.get_pos();
-        *self.board.get_square(pos) = Square::from(piece);
+        self.board.add_piece(piece);
         self
     }
 
@@ -130,6 +140,28 @@ impl BoardBuilder {
         self
     }
 
+    /// Enable kingside castling with the rook on file `col`, as in Chess960.
+    pub fn enable_kingside_castle_with(mut self, color: Color, col: i32) -> Self {
+        let rights = match color {
+            WHITE => &mut self.board.white_castling_rights,
+            BLACK => &mut self.board.black_castling_rights,
+        };
+        rights.enable_kingside();
+        rights.set_kingside_rook_col(col);
+        self
+    }
+
+    /// Enable queenside castling with the rook on file `col`, as in Chess960.
+    pub fn enable_queenside_castle_with(mut self, color: Color, col: i32) -> Self {
+        let rights = match color {
+            WHITE => &mut self.board.white_castling_rights,
+            BLACK => &mut self.board.black_castling_rights,
+        };
+        rights.enable_queenside();
+        rights.set_queenside_rook_col(col);
+        self
+    }
+
     pub fn set_en_passant(mut self, position: Option<Position>) -> Self {
         self.board.en_passant = position;
         self
@@ -145,11 +177,73 @@ impl BoardBuilder {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, BorshDeserialize, BorshSerialize)]
-#[borsh(crate = "near_sdk::borsh")]
+/// Castling rights of one player, together with the files of the rooks they
+/// castle with. The files are the corner files in standard chess and can be
+/// any file on the king's side in Chess960.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub struct CastlingRights {
     kingside: bool,
     queenside: bool,
+    kingside_rook_col: u8,
+    queenside_rook_col: u8,
+}
+
+/// Leading byte of the stored [`CastlingRights`] when the rook files are not
+/// the corner files. The standard layout starts with a `bool`, so it is
+/// always 0 or 1.
+const CASTLING_RIGHTS_WITH_FILES: u8 = 2;
+
+impl BorshSerialize for CastlingRights {
+    fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        // Boards with corner rooks keep the original two-bool layout, so
+        // stored games stay byte-identical.
+        if !self.has_rook_files() {
+            return BorshSerialize::serialize(&(self.kingside, self.queenside), writer);
+        }
+        BorshSerialize::serialize(
+            &(
+                CASTLING_RIGHTS_WITH_FILES,
+                self.kingside,
+                self.queenside,
+                self.kingside_rook_col,
+                self.queenside_rook_col,
+            ),
+            writer,
+        )
+    }
+}
+
+impl BorshDeserialize for CastlingRights {
+    fn deserialize_reader<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self> {
+        let tag = u8::deserialize_reader(reader)?;
+        match tag {
+            0 | 1 => Ok(Self {
+                kingside: tag == 1,
+                queenside: bool::deserialize_reader(reader)?,
+                ..Self::default()
+            }),
+            CASTLING_RIGHTS_WITH_FILES => {
+                let (kingside, queenside, kingside_rook_col, queenside_rook_col) =
+                    <(bool, bool, u8, u8)>::deserialize_reader(reader)?;
+                if kingside_rook_col > 7 || queenside_rook_col > 7 {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        "invalid castling rook file",
+                    ));
+                }
+                Ok(Self {
+                    kingside,
+                    queenside,
+                    kingside_rook_col,
+                    queenside_rook_col,
+                })
+            }
+            _ => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "invalid castling rights",
+            )),
+        }
+    }
 }
 
 impl Default for CastlingRights {
@@ -157,6 +251,8 @@ impl Default for CastlingRights {
         Self {
             kingside: true,
             queenside: true,
+            kingside_rook_col: 7,
+            queenside_rook_col: 0,
         }
     }
 }
@@ -170,6 +266,30 @@ impl CastlingRights {
         self.queenside
     }
 
+    /// The file (column) of the rook used for kingside castling.
+    pub fn kingside_rook_col(&self) -> i32 {
+        self.kingside_rook_col as i32
+    }
+
+    /// The file (column) of the rook used for queenside castling.
+    pub fn queenside_rook_col(&self) -> i32 {
+        self.queenside_rook_col as i32
+    }
+
+    /// Do the castling rooks start somewhere other than the corners, as in
+    /// most Chess960 positions?
+    fn has_rook_files(&self) -> bool {
+        self.kingside_rook_col != 7 || self.queenside_rook_col != 0
+    }
+
+    fn set_kingside_rook_col(&mut self, col: i32) {
+        self.kingside_rook_col = col as u8
+    }
+
+    fn set_queenside_rook_col(&mut self, col: i32) {
+        self.queenside_rook_col = col as u8
+    }
+
     fn disable_kingside(&mut self) {
         self.kingside = false
     }
@@ -195,6 +315,19 @@ impl CastlingRights {
         self.enable_kingside();
         self.enable_queenside()
     }
+
+    /// Give up castling with the rook starting on `pos`, if there is one.
+    fn disable_rook_at(&mut self, pos: Position, color: Color) {
+        if pos.get_row() != Position::king_pos(color).get_row() {
+            return;
+        }
+        if pos.get_col() == self.kingside_rook_col() {
+            self.disable_kingside();
+        }
+        if pos.get_col() == self.queenside_rook_col() {
+            self.disable_queenside();
+        }
+    }
 }
 
 impl Default for Board {
@@ -224,7 +357,7 @@ impl Default for Board {
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, BorshDeserialize, BorshSerialize)]
-#[borsh(crate = "near_sdk::borsh")]
+#[borsh(crate = "near_sdk::borsh", init = rebuild_bitboards)]
 pub struct Board {
     squares: [Square; 64],
 
@@ -234,73 +367,55 @@ pub struct Board {
     black_castling_rights: CastlingRights,
 
     turn: Color,
+
+    /// Mirrors `squares` for fast attack and occupancy queries. It is not part
+    /// of the stored layout, so games saved before it existed stay readable.
+    #[borsh(skip)]
+    bitboards: Bitboards,
 }
 
 impl Board {
-    pub fn value_for(&self, ally_color: Color) -> f64 {
+    /// Evaluate the position for `ally_color` in centipawns: the tapered
+    /// midgame/endgame evaluation of [`eval`](crate::eval) plus a penalty for
+    /// pieces an enemy pawn attacks.
+    pub fn value_for(&self, ally_color: Color) -> i32 {
+        let score = match ally_color {
+            WHITE => eval::evaluate(self),
+            BLACK => -eval::evaluate(self),
+        };
+
         // Build two cheap "attacked by a pawn" maps. A piece sitting on a
         // square attacked by an enemy pawn is tactically fragile — most
         // famously, a knight grabbed by a pawn (the exact blunder we want the
         // eval to dislike even when the search is too shallow to see the
-        // recapture). This is O(64) and keeps `value_for` cheap enough for
-        // every leaf / quiescence stand-pat.
-        let mut wp_atk = [false; 64];
-        let mut bp_atk = [false; 64];
-        for square in &self.squares {
-            if let Some(Piece::Pawn(c, pos)) = square.get_piece() {
-                let row = pos.get_row();
-                let col = pos.get_col();
-                // White pawns advance toward rank 8 (increasing row), so they
-                // attack row + 1. Black pawns advance toward rank 1, attacking
-                // row - 1.
-                let target_row = if c == WHITE { row + 1 } else { row - 1 };
-                if (0..=7).contains(&target_row) {
-                    for target_col in [col - 1, col + 1] {
-                        if (0..=7).contains(&target_col) {
-                            let idx = ((7 - target_row) * 8 + target_col) as usize;
-                            if c == WHITE {
-                                wp_atk[idx] = true;
-                            } else {
-                                bp_atk[idx] = true;
-                            }
-                        }
-                    }
-                }
-            }
-        }
-
-        self.squares
-            .iter()
-            .enumerate()
-            .map(|(i, square)| match square.get_piece() {
-                Some(piece) => {
-                    let sign = if piece.get_color() == ally_color {
-                        1.0
-                    } else {
-                        -1.0
-                    };
-                    let mut v = sign * piece.get_weighted_value();
-                    // Soft penalty for knights/bishops/rooks/queens (not pawns,
-                    // not kings) on a square attacked by an enemy pawn. Defended
-                    // or not, the search + quiescence resolves the real tactics;
-                    // this term just biases equal-looking lines away from
-                    // leaving such pieces en-prise.
-                    let mat = piece.get_material_value();
-                    if (3..=9).contains(&mat) {
-                        let enemy_pawn_atk = if piece.get_color() == WHITE {
-                            bp_atk[i]
-                        } else {
-                            wp_atk[i]
-                        };
-                        if enemy_pawn_atk {
-                            v -= sign * (mat as f64) * 10.0 * 0.25;
-                        }
-                    }
-                    v
+        // recapture). Both maps are two shifts of the pawn bitboards.
+        let white_pawns = self.bitboards.pieces(PAWN, WHITE);
+        let black_pawns = self.bitboards.pieces(PAWN, BLACK);
+        // White pawns advance toward rank 8 (up the bit indices), black pawns
+        // toward rank 1. The file masks drop captures that wrapped around.
+        let wp_atk = ((white_pawns << 7) & !FILE_H) | ((white_pawns << 9) & !FILE_A);
+        let bp_atk = ((black_pawns >> 9) & !FILE_H) | ((black_pawns >> 7) & !FILE_A);
+
+        // Soft penalty for knights/bishops/rooks/queens (not pawns, not
+        // kings) on a square attacked by an enemy pawn. Defended or not, the
+        // search + quiescence resolves the real tactics; this term just
+        // biases equal-looking lines away from leaving such pieces en-prise.
+        let pieces = !(self.bitboards.kind(PAWN) | self.bitboards.kind(KING));
+        [(WHITE, bp_atk), (BLACK, wp_atk)]
+            .into_iter()
+            .flat_map(|(color, enemy_pawn_atk)| {
+                squares(self.bitboards.color(color) & pieces & enemy_pawn_atk)
+                    .filter_map(|sq| self.get_piece(square_position(sq)))
+            })
+            .fold(score, |score, piece| {
+                // a quarter of the piece
+                let penalty = piece.get_material_value() * 100 / 4;
+                if piece.get_color() == ally_color {
+                    score - penalty
+                } else {
+                    score + penalty
                 }
-                None => 0.0,
             })
-            .sum()
     }
 
     /// Compute a Zobrist hash for the current position.
@@ -308,35 +423,37 @@ impl Board {
     /// stored in contract state).
     pub fn zobrist_key(&self) -> u64 {
         let mut key: u64 = 0;
-        for (sq, square) in self.squares.iter().enumerate() {
-            if let Some(piece) = square.get_piece() {
-                let (pt, color) = piece.zobrist_indices();
-                // self.squares uses FEN order (a8=0, h1=63). Convert to
-                // standard chess order (a1=0, h8=63) to match the
-                // Python-generated Zobrist keys in the opening book.
-                let std_sq = (7 - sq / 8) * 8 + sq % 8;
-                key ^= PIECE_ZOBRIST_KEYS[pt][color][std_sq];
+        // Bitboard squares already use standard chess order (a1=0, h8=63),
+        // matching the Python-generated Zobrist keys in the opening book.
+        for sq in squares(self.bitboards.occupied()) {
+            if let Some(piece) = self.get_piece(square_position(sq)) {
+                key ^= piece_zobrist_key(piece);
             }
         }
         if self.turn == Color::Black {
             key ^= BLACK_TO_MOVE_ZOBRIST_KEY;
         }
-        if self.white_castling_rights.can_kingside_castle() {
-            key ^= CASTLING_ZOBRIST_KEYS[0];
-        }
-        if self.white_castling_rights.can_queenside_castle() {
-            key ^= CASTLING_ZOBRIST_KEYS[1];
-        }
-        if self.black_castling_rights.can_kingside_castle() {
-            key ^= CASTLING_ZOBRIST_KEYS[2];
-        }
-        if self.black_castling_rights.can_queenside_castle() {
-            key ^= CASTLING_ZOBRIST_KEYS[3];
-        }
-        if let Some(ep) = self.en_passant {
-            key ^= EN_PASSANT_FILE_ZOBRIST_KEYS[ep.get_col() as usize];
-        }
-        key
+        key ^ self.castling_zobrist_key() ^ self.en_passant_zobrist_key()
+    }
+
+    /// The part of the Zobrist key that hashes the castling rights.
+    fn castling_zobrist_key(&self) -> u64 {
+        [
+            self.white_castling_rights.can_kingside_castle(),
+            self.white_castling_rights.can_queenside_castle(),
+            self.black_castling_rights.can_kingside_castle(),
+            self.black_castling_rights.can_queenside_castle(),
+        ]
+        .into_iter()
+        .zip(CASTLING_ZOBRIST_KEYS)
+        .filter(|&(allowed, _)| allowed)
+        .fold(0, |key, (_, castling_key)| key ^ castling_key)
+    }
+
+    /// The part of the Zobrist key that hashes the en-passant square.
+    fn en_passant_zobrist_key(&self) -> u64 {
+        self.en_passant
+            .map_or(0, |ep| EN_PASSANT_FILE_ZOBRIST_KEYS[ep.get_col() as usize])
     }
 
     #[inline]
@@ -368,67 +485,35 @@ impl Board {
     }
 
     /// Find pieces that are pinned to the king (moving them would expose the king
-    /// to a sliding attacker). Returns a [bool; 64] indexed by board square index.
-    fn find_pinned_pieces(&self, color: Color) -> [bool; 64] {
-        let mut pinned = [false; 64];
+    /// to a sliding attacker).
+    fn find_pinned_pieces(&self, color: Color) -> Bitboard {
+        let mut pinned = EMPTY;
         let king_pos = match self.get_king_pos(color) {
             Some(pos) => pos,
             None => return pinned,
         };
-        let kr = king_pos.get_row();
-        let kc = king_pos.get_col();
-
-        let directions: [(i32, i32); 8] = [
-            (0, 1),
-            (0, -1),
-            (1, 0),
-            (-1, 0),
-            (1, 1),
-            (1, -1),
-            (-1, 1),
-            (-1, -1),
-        ];
-
-        for (dr, dc) in directions {
-            let is_orthogonal = dr == 0 || dc == 0;
-            let mut candidate: Option<Position> = None;
-            let mut r = kr + dr;
-            let mut c = kc + dc;
-            while (0..=7).contains(&r) && (0..=7).contains(&c) {
-                let pos = Position::new(r, c);
-                if let Some(piece) = self.get_piece(pos) {
-                    if piece.get_color() == color {
-                        if candidate.is_none() {
-                            candidate = Some(pos);
-                        } else {
-                            break;
-                        }
-                    } else {
-                        if let Some(cp) = candidate {
-                            let can_attack = if is_orthogonal {
-                                piece.is_rook() || piece.is_queen()
-                            } else {
-                                piece.is_bishop() || piece.is_queen()
-                            };
-                            if can_attack {
-                                let idx = ((7 - cp.get_row()) * 8 + cp.get_col()) as usize;
-                                pinned[idx] = true;
-                            }
-                        }
-                        break;
-                    }
-                }
-                r += dr;
-                c += dc;
+        let king = square_index(king_pos);
+        let enemy = !color;
+        // Enemy sliders that would attack the king on an empty board...
+        let snipers = (rook_attacks(king, EMPTY)
+            & (self.bitboards.pieces(ROOK, enemy) | self.bitboards.pieces(QUEEN, enemy)))
+            | (bishop_attacks(king, EMPTY)
+                & (self.bitboards.pieces(BISHOP, enemy) | self.bitboards.pieces(QUEEN, enemy)));
+        // ...pin the one piece between them and the king, if it is ours.
+        for sniper in squares(snipers) {
+            let blockers = between(king, sniper) & self.bitboards.occupied();
+            if blockers.count_ones() == 1 {
+                pinned |= blockers & self.bitboards.color(color);
             }
         }
         pinned
     }
 
-    /// Generate legal moves using pin detection: skip the expensive
-    /// `apply_move + is_in_check` legality verification for non-pinned,
-    /// non-king pieces. King moves, pinned-piece moves, en-passant, and
-    /// all moves when in check still get full verification.
+    /// Generate legal moves from the bitboards, with pin detection to skip
+    /// the `apply_move + is_in_check` legality verification for non-pinned,
+    /// non-king pieces. King moves are checked against the attack tables,
+    /// and pinned-piece moves, en-passant, and all moves when in check still
+    /// get full verification.
     fn get_legal_moves_fast(&self) -> Vec<Move> {
         let color = self.get_current_player_color();
 
@@ -437,39 +522,71 @@ impl Board {
         }
 
         let pinned = self.find_pinned_pieces(color);
-        let ep = self.en_passant;
+        let occupied = self.bitboards.occupied();
+        let own = self.bitboards.color(color);
+        let enemy = self.bitboards.color(!color);
 
         let mut moves = Vec::new();
-        for (i, square) in self.squares.iter().enumerate() {
-            let piece = match square.get_piece() {
-                Some(p) => p,
-                None => continue,
-            };
-            if piece.get_color() != color {
+        for from in squares(own) {
+            let from_pos = square_position(from);
+            let bit = 1 << from;
+            if self.bitboards.kind(KING) & bit != EMPTY {
+                // Look through the king, so the squares behind it on a
+                // slider's line count as attacked.
+                let without_king = occupied & !bit;
+                for to in squares(KING_ATTACKS[from] & !own) {
+                    if self.bitboards.attackers_to(to, !color, without_king) == EMPTY {
+                        moves.push(Move::Piece(from_pos, square_position(to)));
+                    }
+                }
+                if self.can_kingside_castle(color) {
+                    moves.push(Move::KingSideCastle);
+                }
+                if self.can_queenside_castle(color) {
+                    moves.push(Move::QueenSideCastle);
+                }
                 continue;
             }
-            let is_king = piece.is_king();
-            let is_pinned = pinned[i];
 
-            for m in piece.get_moves(self) {
-                if is_king || is_pinned {
-                    if self.is_legal_move(m, color) {
-                        moves.push(m);
+            let mut targets = if self.bitboards.kind(PAWN) & bit != EMPTY {
+                let up = from_pos.pawn_up(color);
+                let mut targets = PAWN_ATTACKS[color as usize][from] & enemy;
+                if up.is_on_board() && self.has_no_piece(up) {
+                    targets |= square_bit(up);
+                    let next_up = up.pawn_up(color);
+                    if from_pos.is_starting_pawn(color) && self.has_no_piece(next_up) {
+                        targets |= square_bit(next_up);
                     }
-                } else {
-                    let is_ep = ep.is_some()
-                        && piece.is_pawn()
-                        && match m {
-                            Move::Piece(_, to) | Move::Promotion(_, to, _) => {
-                                to == ep.unwrap() && self.get_piece(to).is_none()
-                            }
-                            _ => false,
-                        };
-                    if is_ep {
-                        if self.is_legal_move(m, color) {
-                            moves.push(m);
-                        }
-                    } else {
+                }
+                targets
+            } else if self.bitboards.kind(KNIGHT) & bit != EMPTY {
+                KNIGHT_ATTACKS[from] & !own
+            } else if self.bitboards.kind(BISHOP) & bit != EMPTY {
+                bishop_attacks(from, occupied) & !own
+            } else if self.bitboards.kind(ROOK) & bit != EMPTY {
+                rook_attacks(from, occupied) & !own
+            } else {
+                queen_attacks(from, occupied) & !own
+            };
+
+            if pinned & bit != EMPTY {
+                // A pinned piece may only move along the pin line.
+                if let Some(king) = self.get_king_pos(color) {
+                    targets &= line(square_index(king), from);
+                }
+            }
+            for to in squares(targets) {
+                moves.push(Move::Piece(from_pos, square_position(to)));
+            }
+
+            // En-passant can expose the king along the rank of both pawns,
+            // which the pin mask does not see, so verify it in full.
+            if let Some(ep) = self.en_passant {
+                if self.bitboards.kind(PAWN) & bit != EMPTY
+                    && PAWN_ATTACKS[color as usize][from] & square_bit(ep) != EMPTY
+                {
+                    let m = Move::Piece(from_pos, ep);
+                    if self.is_legal_move(m, color) {
                         moves.push(m);
                     }
                 }
@@ -484,7 +601,8 @@ impl Board {
     /// This method returns
     /// 1. The best move
     /// 2. The number of boards evaluated to come to a conclusion
-    /// 3. The rating of the best move
+    /// 3. The rating of the best move, on the float scale of
+    ///    [`SearchResult::value`]
     ///
     /// It's best not to use the rating value by itself for anything, as it
     /// is relative to the other player's move ratings as well.
@@ -496,7 +614,7 @@ impl Board {
         let color = self.get_current_player_color();
 
         let mut board_count = 0;
-        let mut killers = [[None; 2]; MAX_PLY];
+        let mut ordering = MoveOrdering::new();
         let mut tt = TranspositionTable::new(0);
         for m in legal_moves {
             let child_board_value = self.apply_eval_move(m).minimax(
@@ -509,7 +627,7 @@ impl Board {
                 &mut board_count,
                 0,
                 1,
-                &mut killers,
+                &mut ordering,
             );
             if child_board_value > best_move_value {
                 best_move = m;
@@ -517,121 +635,359 @@ impl Board {
             }
         }
 
-        (best_move, board_count, best_move_value)
+        (best_move, board_count, value_to_float(best_move_value))
     }
 
     pub fn get_next_move(
         &self,
         depths: &[u8],
         seed: [u8; 32],
-        gas_budget: Gas,
+        limits: &impl SearchLimits,
         flags: u16,
-    ) -> (Move, u64, f64) {
+    ) -> SearchResult {
+        let history = PositionHistory::new(self);
+        let mut tt = TranspositionTable::new(TT_SIZE);
+        self.get_next_move_in_game(&history, &mut tt, depths, seed, limits, flags)
+    }
+
+    /// Like [`Board::get_next_move`], for a position reached in a game.
+    /// Root moves that draw by threefold repetition or the fifty-move rule,
+    /// as far as `history` knows the game, are scored as draws. `tt` is the
+    /// caller's transposition table, so the entries of earlier searches of
+    /// the game carry over to this one.
+    pub fn get_next_move_in_game(
+        &self,
+        history: &PositionHistory,
+        tt: &mut TranspositionTable,
+        depths: &[u8],
+        seed: [u8; 32],
+        limits: &impl SearchLimits,
+        flags: u16,
+    ) -> SearchResult {
+        let depths = limit_depths(depths, limits);
         let rng = ChaCha20Rng::from_seed(seed);
         let mut legal_moves: Vec<Move> = self.get_legal_moves().collect();
-        let mut tt = TranspositionTable::new(8192);
+        let drawing_moves: Vec<Move> = legal_moves
+            .iter()
+            .copied()
+            .filter(|&m| self.draws_by_history(m, history))
+            .collect();
+        tt.new_search();
         if legal_moves.is_empty() {
-            return (Move::Resign, 0, 0.0);
+            return SearchResult {
+                best_move: Move::Resign,
+                board_count: 0,
+                value: 0.0,
+                pv: vec![],
+                score: Score::Centipawns(0),
+                stats: SearchStats::default(),
+            };
         }
         if (flags & FLAG_MOVE_ORDERING) != 0 {
-            self.order_moves(&mut legal_moves, 0, flags, &[[None; 2]; MAX_PLY]);
+            self.order_moves(&mut legal_moves, 0, flags, &MoveOrdering::new());
         }
         let mut best_move = legal_moves[0];
         let mut best_move_value = NEG_INFINITY;
 
         let color = self.get_current_player_color();
+        // Hash the root once; the search keeps the key up to date from here.
+        let mut root = SearchBoard::new(*self);
 
-        let mut board_count = 0;
+        let mut stats = SearchStats::default();
+        // Did at least one full search of the root moves finish within the
+        // limits?
+        let mut completed = false;
+        // The depth list of the search whose result is returned, to read its
+        // principal variation back from the transposition table.
+        let mut pv_depths = depths;
 
         if (flags & FLAG_ITERATIVE_DEEPENING) != 0 {
-            let max_depth = depths.len().saturating_sub(1).max(1);
+            // A single-entry depth list only searches the root moves.
+            let first_iter = if depths.len() > 1 { 1 } else { 0 };
+            let max_depth = depths.len().saturating_sub(1);
             let mut last_best: Option<Move> = None;
-            for iter in 1..=max_depth {
-                if env::used_gas() >= gas_budget {
+            let mut ordering = MoveOrdering::new();
+            for iter in first_iter..=max_depth {
+                if limits.is_exhausted(stats.nodes) {
                     break;
                 }
+                let gas_before = limits.used_gas();
                 // Search the previous iteration's best move first.
                 if let Some(prev) = last_best {
                     if let Some(pos) = legal_moves.iter().position(|&m| m == prev) {
                         legal_moves.swap(0, pos);
                     }
                 }
-                let mut iter_best = best_move;
-                let mut iter_best_value = NEG_INFINITY;
-                let mut killers = [[None; 2]; MAX_PLY];
+                ordering.new_iteration();
                 let iter_depths = &depths[..=iter];
-                for m in &legal_moves {
-                    if env::used_gas() >= gas_budget {
-                        break;
+                // Aspiration windows: this iteration's score is likely close
+                // to the last one's, so search a narrow window around it, and
+                // search again with that side opened up if the score falls
+                // outside.
+                let (mut lo, mut hi) = if (flags & FLAG_ASPIRATION_WINDOWS) != 0
+                    && completed
+                    && !is_mate_value(best_move_value)
+                {
+                    (
+                        best_move_value - ASPIRATION_WINDOW,
+                        best_move_value + ASPIRATION_WINDOW,
+                    )
+                } else {
+                    (NEG_INFINITY, POS_INFINITY)
+                };
+                let (iter_best, iter_best_value) = loop {
+                    let mut iter_best = best_move;
+                    let mut iter_best_value = NEG_INFINITY;
+                    for m in &legal_moves {
+                        if limits.is_exhausted(stats.nodes) {
+                            break;
+                        }
+                        let child_board_value = if drawing_moves.contains(m) {
+                            0
+                        } else {
+                            let undo = root.make_move(*m);
+                            ordering.set_line(0, Some(*m));
+                            let value = root.minimax(
+                                tt,
+                                Either::Right((&iter_depths[1..], rng.clone())),
+                                lo,
+                                hi,
+                                false,
+                                color,
+                                &mut stats,
+                                flags,
+                                1,
+                                &mut ordering,
+                            );
+                            root.unmake_move(undo);
+                            value
+                        };
+                        if child_board_value > iter_best_value {
+                            iter_best = *m;
+                            iter_best_value = child_board_value;
+                        }
                     }
-                    let child_board_value = self.apply_eval_move(*m).minimax(
-                        &mut tt,
-                        Either::Right((&iter_depths[1..], rng.clone())),
-                        NEG_INFINITY,
-                        POS_INFINITY,
-                        false,
-                        color,
-                        &mut board_count,
-                        flags,
-                        1,
-                        &mut killers,
-                    );
-                    if child_board_value > iter_best_value {
-                        iter_best = *m;
-                        iter_best_value = child_board_value;
+                    if limits.is_exhausted(stats.nodes) {
+                        break (iter_best, iter_best_value);
+                    }
+                    if iter_best_value <= lo && lo != NEG_INFINITY {
+                        lo = NEG_INFINITY;
+                    } else if iter_best_value >= hi && hi != POS_INFINITY {
+                        hi = POS_INFINITY;
+                    } else {
+                        break (iter_best, iter_best_value);
                     }
+                };
+                if let (Some(before), Some(after)) = (gas_before, limits.used_gas()) {
+                    stats
+                        .iteration_gas
+                        .push(after.saturating_sub(before).as_gas());
                 }
-                // Only commit this iteration's result if it completed without
-                // hitting the gas budget.
-                if env::used_gas() < gas_budget {
+                // Only commit this iteration's result if it completed within
+                // the limits.
+                if !limits.is_exhausted(stats.nodes) {
                     best_move = iter_best;
                     best_move_value = iter_best_value;
                     last_best = Some(iter_best);
+                    completed = true;
+                    pv_depths = iter_depths;
+                    stats.completed_depth = iter_depths.len() as u8;
                 }
             }
         } else {
-            let mut killers = [[None; 2]; MAX_PLY];
+            let gas_before = limits.used_gas();
+            let mut ordering = MoveOrdering::new();
             for &m in &legal_moves {
-                if env::used_gas() >= gas_budget {
+                if limits.is_exhausted(stats.nodes) {
                     break;
                 }
-                let child_board_value = self.apply_eval_move(m).minimax(
-                    &mut tt,
-                    Either::Right((&depths[1..], rng.clone())),
-                    NEG_INFINITY,
-                    POS_INFINITY,
-                    false,
-                    color,
-                    &mut board_count,
-                    flags,
-                    1,
-                    &mut killers,
-                );
+                let child_board_value = if drawing_moves.contains(&m) {
+                    0
+                } else {
+                    let undo = root.make_move(m);
+                    ordering.set_line(0, Some(m));
+                    let value = root.minimax(
+                        tt,
+                        Either::Right((&depths[1..], rng.clone())),
+                        NEG_INFINITY,
+                        POS_INFINITY,
+                        false,
+                        color,
+                        &mut stats,
+                        flags,
+                        1,
+                        &mut ordering,
+                    );
+                    root.unmake_move(undo);
+                    value
+                };
                 if child_board_value > best_move_value {
                     best_move = m;
                     best_move_value = child_board_value;
                 }
             }
+            if let (Some(before), Some(after)) = (gas_before, limits.used_gas()) {
+                stats
+                    .iteration_gas
+                    .push(after.saturating_sub(before).as_gas());
+            }
+            completed = !limits.is_exhausted(stats.nodes);
+            if completed {
+                stats.completed_depth = depths.len() as u8;
+            }
         }
-
-        // Safety net: when the gas budget aborts the search early the chosen
-        // move is often just the first ordered move (a capture), which is how
-        // the AI blunders material in the opening — e.g. Nxe4 grabbing a pawn
-        // and getting recaptured. If the chosen move is a 1-ply material
-        // blunder, replace it with the best-ordered move that is NOT. Skipped
-        // when in check (escaping check may legitimately require giving
-        // material). Never deadlocks: if every move blunders, `best_move`
-        // is left unchanged.
-        if !self.is_in_check(color) && self.move_blunders_material(best_move) {
+        stats.aborted = (stats.completed_depth as usize) < depths.len();
+
+        // Safety net: when the limits abort the search early the chosen
+        // move is often just the first ordered move, which is how the AI
+        // blunders material in the opening — e.g. Nxe4 grabbing a pawn and
+        // getting recaptured. If the chosen move loses material by static
+        // exchange evaluation, replace it with the best-ordered move that
+        // does NOT. A completed search is trusted, so sacrifices it found
+        // are kept. Skipped when in check (escaping check may legitimately
+        // require giving material). Never deadlocks: if every move loses
+        // material, `best_move` is left unchanged.
+        let mut pv = None;
+        if !completed && !self.is_in_check(color) && self.see(best_move) < 0 {
             for &m in &legal_moves {
-                if !self.move_blunders_material(m) {
+                if self.see(m) >= 0 {
                     best_move = m;
+                    // the search has no line for this move
+                    pv = Some(vec![m]);
                     break;
                 }
             }
         }
+        let pv =
+            pv.unwrap_or_else(|| root.principal_variation(tt, best_move, &pv_depths[1..], flags));
+
+        SearchResult {
+            best_move,
+            board_count: stats.nodes,
+            value: value_to_float(best_move_value),
+            pv,
+            score: Score::from_value(best_move_value),
+            stats,
+        }
+    }
+
+    /// Does playing `m` draw the game by threefold repetition or the
+    /// fifty-move rule?
+    fn draws_by_history(&self, m: Move, history: &PositionHistory) -> bool {
+        matches!(
+            self.play_move_with_history(m, &mut history.clone()),
+            GameResult::ThreefoldRepetition | GameResult::FiftyMoveRule
+        )
+    }
 
-        (best_move, board_count, best_move_value)
+    /// Rank the root moves for analysis, returning up to `lines` of them
+    /// best first, each with its score and principal variation.
+    ///
+    /// Every root move is searched with a full window, so the scores of the
+    /// moves after the best one are exact too, not just bounds. The search
+    /// honours `flags` like [`Board::get_next_move`]; with
+    /// `FLAG_ITERATIVE_DEEPENING` every prefix of `depths` is searched in
+    /// turn and the moves are re-ranked after each iteration. Once `limits`
+    /// are exhausted no further root moves are started, and the last
+    /// completed iteration is returned. If not even the
+    /// first iteration completed, only the moves it got to are ranked, but
+    /// at least one.
+    pub fn get_multi_pv(
+        &self,
+        lines: usize,
+        depths: &[u8],
+        seed: [u8; 32],
+        limits: &impl SearchLimits,
+        flags: u16,
+    ) -> Vec<PvLine> {
+        let mut tt = TranspositionTable::new(TT_SIZE);
+        self.get_multi_pv_with_tt(&mut tt, lines, depths, seed, limits, flags)
+    }
+
+    /// Like [`Board::get_multi_pv`], searching with the caller's
+    /// transposition table.
+    pub fn get_multi_pv_with_tt(
+        &self,
+        tt: &mut TranspositionTable,
+        lines: usize,
+        depths: &[u8],
+        seed: [u8; 32],
+        limits: &impl SearchLimits,
+        flags: u16,
+    ) -> Vec<PvLine> {
+        let depths = limit_depths(depths, limits);
+        let rng = ChaCha20Rng::from_seed(seed);
+        let mut legal_moves: Vec<Move> = self.get_legal_moves().collect();
+        if legal_moves.is_empty() || lines == 0 || depths.is_empty() {
+            return vec![];
+        }
+        if (flags & FLAG_MOVE_ORDERING) != 0 {
+            self.order_moves(&mut legal_moves, 0, flags, &MoveOrdering::new());
+        }
+
+        tt.new_search();
+        let color = self.get_current_player_color();
+        let mut root = SearchBoard::new(*self);
+        let mut stats = SearchStats::default();
+
+        let max_depth = depths.len() - 1;
+        let first_iter = if (flags & FLAG_ITERATIVE_DEEPENING) != 0 && max_depth > 0 {
+            1
+        } else {
+            max_depth
+        };
+        let mut ranked: Vec<(Move, i32)> = vec![];
+        let mut ranked_depths = depths;
+        let mut ordering = MoveOrdering::new();
+        for iter in first_iter..=max_depth {
+            let iter_depths = &depths[..=iter];
+            ordering.new_iteration();
+            let mut scored = Vec::with_capacity(legal_moves.len());
+            for &m in &legal_moves {
+                if !scored.is_empty() && limits.is_exhausted(stats.nodes) {
+                    break;
+                }
+                let undo = root.make_move(m);
+                ordering.set_line(0, Some(m));
+                let value = root.minimax(
+                    tt,
+                    Either::Right((&iter_depths[1..], rng.clone())),
+                    NEG_INFINITY,
+                    POS_INFINITY,
+                    false,
+                    color,
+                    &mut stats,
+                    flags,
+                    1,
+                    &mut ordering,
+                );
+                root.unmake_move(undo);
+                scored.push((m, value));
+            }
+            // stable, so equal moves keep their search order
+            scored.sort_by_key(|&(_, value)| core::cmp::Reverse(value));
+            let completed = scored.len() == legal_moves.len();
+            if completed || ranked.is_empty() {
+                ranked = scored;
+                ranked_depths = iter_depths;
+            }
+            if !completed {
+                break;
+            }
+            // the next iteration searches the best moves first
+            legal_moves = ranked.iter().map(|&(m, _)| m).collect();
+        }
+
+        ranked
+            .into_iter()
+            .take(lines)
+            .map(|(m, value)| PvLine {
+                mv: m,
+                value: value_to_float(value),
+                score: Score::from_value(value),
+                pv: root.principal_variation(tt, m, &ranked_depths[1..], flags),
+            })
+            .collect()
     }
 
     /// Get the best move for the current player with `depth` number of moves
@@ -640,7 +996,8 @@ impl Board {
     /// This method returns
     /// 1. The best move
     /// 2. The number of boards evaluated to come to a conclusion
-    /// 3. The rating of the best move
+    /// 3. The rating of the best move, on the float scale of
+    ///    [`SearchResult::value`]
     ///
     /// It's best not to use the rating value by itself for anything, as it
     /// is relative to the other player's move ratings as well.
@@ -652,7 +1009,7 @@ impl Board {
         let color = self.get_current_player_color();
 
         let mut board_count = 0;
-        let mut killers = [[None; 2]; MAX_PLY];
+        let mut ordering = MoveOrdering::new();
         let mut tt = TranspositionTable::new(0);
         for m in legal_moves {
             let child_board_value = self.apply_eval_move(m).minimax(
@@ -665,7 +1022,7 @@ impl Board {
                 &mut board_count,
                 0,
                 1,
-                &mut killers,
+                &mut ordering,
             );
 
             if child_board_value >= best_move_value {
@@ -674,41 +1031,55 @@ impl Board {
             }
         }
 
-        (best_move, board_count, best_move_value)
+        (best_move, board_count, value_to_float(best_move_value))
     }
 
     /// Score a move for ordering (higher = search first).
-    /// Captures/promotions first by MVV-LVA, then killer moves, then a cheap
-    /// productive-move tiebreaker for quiet moves.
+    /// Captures that don't lose material by static exchange evaluation and
+    /// promotions first by MVV-LVA, then killer moves and countermoves, then
+    /// quiet moves by history and a cheap productive-move tiebreaker, and
+    /// losing captures last.
     fn score_move_for_ordering(
         &self,
         m: Move,
         ply: u8,
         flags: u16,
-        killers: &[[Option<Move>; 2]; MAX_PLY],
+        ordering: &MoveOrdering,
     ) -> i32 {
         match m {
             Move::Piece(from, to) => {
-                if let Some(victim) = self.get_piece(to) {
+                if self.is_capture(m) {
+                    let see = self.see(m);
+                    if see < 0 {
+                        return see;
+                    }
+                    // en-passant: the captured pawn isn't on `to`.
+                    let victim = self.get_piece(to).map_or(1, |v| v.get_material_value());
                     let attacker = self.get_piece(from).unwrap();
-                    victim.get_material_value() * 1000 - attacker.get_material_value()
-                } else if (flags & FLAG_KILLER_HEURISTIC) != 0
-                    && (ply as usize) < MAX_PLY
-                    && killers[ply as usize][0] == Some(m)
+                    victim * 1000 - attacker.get_material_value()
+                } else if (flags & FLAG_KILLER_HEURISTIC) != 0 && ordering.killer(ply, 0) == Some(m)
                 {
                     500
-                } else if (flags & FLAG_KILLER_HEURISTIC) != 0
-                    && (ply as usize) < MAX_PLY
-                    && killers[ply as usize][1] == Some(m)
+                } else if (flags & FLAG_KILLER_HEURISTIC) != 0 && ordering.killer(ply, 1) == Some(m)
                 {
                     400
+                } else if (flags & FLAG_HISTORY_HEURISTIC) != 0
+                    && ordering.countermove(ply) == Some(m)
+                {
+                    300
                 } else {
                     // Quiet, non-killer move: a small positional tiebreaker so
                     // move ordering — and the gas-abort fallback that relies on
                     // it — prefers purposeful moves (central squares,
                     // developing back-rank minors, advancing pawns) over aimless
-                    // shuffling such as rook a8-b8-a8.
-                    self.quiet_move_score(from, to)
+                    // shuffling such as rook a8-b8-a8. Moves that caused
+                    // cutoffs elsewhere in the tree come first.
+                    let history = if (flags & FLAG_HISTORY_HEURISTIC) != 0 {
+                        ordering.history_score(self.turn, m)
+                    } else {
+                        0
+                    };
+                    self.quiet_move_score(from, to) + history
                 }
             }
             Move::Promotion(_, to, piece) => {
@@ -726,7 +1097,9 @@ impl Board {
 
     /// Cheap positional score for a quiet (non-capture) move, used only as a
     /// tiebreaker below captures and killers. Stays in the low tens so it can
-    /// never outrank a capture (>= ~999) or killer (400-500).
+    /// never outrank a winning or even capture (>= ~999), killer (400-500) or
+    /// countermove (300), even with the history score (up to 250) added,
+    /// while losing captures score below zero.
     fn quiet_move_score(&self, from: Position, to: Position) -> i32 {
         let mut s = 0;
         // Central squares (d4, e4, d5, e5) are good for almost every piece.
@@ -752,17 +1125,11 @@ impl Board {
     }
 
     /// Sort moves in-place so alpha-beta cuts early.
-    fn order_moves(
-        &self,
-        moves: &mut [Move],
-        ply: u8,
-        flags: u16,
-        killers: &[[Option<Move>; 2]; MAX_PLY],
-    ) {
-        moves.sort_by(|a, b| {
-            let sa = self.score_move_for_ordering(*a, ply, flags, killers);
-            let sb = self.score_move_for_ordering(*b, ply, flags, killers);
-            sb.cmp(&sa)
+    fn order_moves(&self, moves: &mut [Move], ply: u8, flags: u16, ordering: &MoveOrdering) {
+        // Scoring runs a static exchange evaluation for captures, so score
+        // each move once instead of on every comparison.
+        moves.sort_by_cached_key(|m| {
+            core::cmp::Reverse(self.score_move_for_ordering(*m, ply, flags, ordering))
         });
     }
 
@@ -794,129 +1161,106 @@ impl Board {
         kept
     }
 
-    /// Is `pos` attacked by a pawn of `attacker_color`? O(2) — used by the
-    /// hanging-piece book guard below.
+    /// Is `pos` attacked by a pawn of `attacker_color`? O(2).
     pub fn square_attacked_by_pawn(&self, pos: Position, attacker_color: Color) -> bool {
-        let row = pos.get_row();
-        let col = pos.get_col();
-        // A white pawn attacks the square one rank above it (toward rank 8),
-        // so it sits one rank below the square it attacks (row - 1). Black is
-        // mirrored (row + 1).
-        let from_row = if attacker_color == WHITE {
-            row - 1
-        } else {
-            row + 1
-        };
-        if !(0..=7).contains(&from_row) {
-            return false;
-        }
-        for from_col in [col - 1, col + 1] {
-            if !(0..=7).contains(&from_col) {
-                continue;
-            }
-            if let Some(Piece::Pawn(c, _)) = self.get_piece(Position::new(from_row, from_col)) {
-                if c == attacker_color {
-                    return true;
-                }
-            }
-        }
-        false
-    }
-
-    /// Smallest material value among enemy pieces (of `attacker_color`) that
-    /// legally attack `pos`, or `None` if `pos` is not attacked. Mirrors
-    /// `is_threatened` but returns the cheapest attacker's value — used by the
-    /// static-exchange blunder check below.
-    fn least_attacker_value(&self, pos: Position, attacker_color: Color) -> Option<i32> {
-        let mut best: Option<i32> = None;
-        for (i, square) in self.squares.iter().enumerate() {
-            let row = 7 - i / 8;
-            let col = i % 8;
-            let square_pos = Position::new(row as i32, col as i32);
-            if !square_pos.is_orthogonal_to(pos)
-                && !square_pos.is_diagonal_to(pos)
-                && !square_pos.is_knight_move(pos)
-            {
-                continue;
-            }
-            if let Some(piece) = square.get_piece() {
-                if piece.get_color() != attacker_color {
-                    continue;
-                }
-                // `is_legal_attack` encodes piece-specific geometry (e.g. a
-                // pawn only attacks diagonally), refining the prefilter.
-                if piece.is_legal_attack(pos, self) {
-                    let v = piece.get_material_value();
-                    match best {
-                        Some(b) if b <= v => {}
-                        _ => best = Some(v),
-                    }
-                }
-            }
-        }
-        best
-    }
-
-    /// Would playing `m` immediately lose material — a 1-ply blunder such as
-    /// grabbing a pawn with a knight that the opponent then recaptures for
-    /// free? This generalises the old pawn-only guard: it considers attackers
-    /// of every type, so a knight grabbed by a knight/bishop/rook/queen is
-    /// also rejected (the classic "loses knight for a pawn" opening blunder).
-    /// It only fires on a *net* loss — an even trade (knight-for-knight) is
-    /// NOT a blunder. Used to guard both opening-book replies and the
-    /// gas-budget-abort fallback move in `get_next_move`.
-    pub fn move_blunders_material(&self, m: Move) -> bool {
-        let (from, to) = match m {
-            Move::Piece(f, t) | Move::Promotion(f, t, _) => (f, t),
-            _ => return false,
+        // A pawn attacks `pos` exactly when a pawn of the other color on
+        // `pos` would attack the pawn's square.
+        pos.is_on_board()
+            && PAWN_ATTACKS[!attacker_color as usize][square_index(pos)]
+                & self.bitboards.pieces(PAWN, attacker_color)
+                != EMPTY
+    }
+
+    /// Static exchange evaluation: the material the player to move wins (or,
+    /// if negative, loses) by playing `m` and then resolving the whole
+    /// sequence of captures on its target square, each side always
+    /// recapturing with its cheapest attacker and free to stop when going on
+    /// would lose more. Sliders lined up behind a capturing piece join in
+    /// once it leaves, but pins and checks are ignored.
+    ///
+    /// Values are those of [`Piece::get_material_value`], so a knight taken
+    /// by a pawn after grabbing a pawn scores -2. Quiet moves score 0 unless
+    /// the moved piece can be won, which is how a move hanging a piece is
+    /// recognised. Used for move ordering, to prune losing captures in the
+    /// quiescence search, and to veto opening-book moves and the gas-abort
+    /// fallback move that give away material.
+    pub fn see(&self, m: Move) -> i32 {
+        let (from, to, promotion) = match m {
+            Move::Piece(from, to) => (from, to, None),
+            Move::Promotion(from, to, promotion) => (from, to, Some(promotion)),
+            _ => return 0,
         };
         let piece = match self.get_piece(from) {
-            Some(p) => p,
-            None => return false,
+            Some(piece) => piece,
+            None => return 0,
         };
-        let color = piece.get_color();
-        let enemy = !color;
-
-        // The mover is the potential victim; never classify a king move.
-        let victim = piece.get_material_value();
-        if victim >= 99_990 {
-            return false;
-        }
-
-        // Material the move captured (0 if it captured nothing / en-passant
-        // pawn counts as a pawn via the destination occupancy below).
-        let captured = self
-            .get_piece(to)
-            .map(|p| p.get_material_value())
-            .unwrap_or(0);
-
-        let nb = self.apply_eval_move(m);
-        // Confirm our mover actually landed on `to`.
-        if !matches!(nb.get_piece(to), Some(p) if p.get_color() == color) {
-            return false;
-        }
-
-        // Cheapest enemy attacker on the landing square, in the new position.
-        let attacker = match nb.least_attacker_value(to, enemy) {
-            Some(v) => v,
-            None => return false,
+        let target = square_index(to);
+        let mut occupied = self.bitboards.occupied() & !square_bit(from);
+
+        let mut gain = [0i32; 32];
+        gain[0] = match self.get_piece(to) {
+            Some(victim) => victim.get_material_value(),
+            None if self.is_capture(m) => {
+                // en-passant: the captured pawn is behind `to`
+                occupied &= !square_bit(to.pawn_back(piece.get_color()));
+                1
+            }
+            None => 0,
         };
-        // If the cheapest attacker costs more than the victim, recapturing
-        // loses material for the opponent — not a blunder for us.
-        if attacker > victim {
-            return false;
+        // The piece standing on the target square, which is what the next
+        // capture wins.
+        let mut on_target = piece.get_material_value();
+        if piece.is_pawn() && (to.get_row() == 0 || to.get_row() == 7) {
+            let promoted = promotion
+                .filter(|p| !(p.is_king() || p.is_pawn()))
+                .map_or(9, |p| p.get_material_value());
+            gain[0] += promoted - 1;
+            on_target = promoted;
+        }
+
+        let mut side = !piece.get_color();
+        let mut depth = 0;
+        loop {
+            let attackers = self.bitboards.attackers_to(target, side, occupied) & occupied;
+            let Some((kind, value)) = [
+                (PAWN, 1),
+                (KNIGHT, 3),
+                (BISHOP, 3),
+                (ROOK, 5),
+                (QUEEN, 9),
+                (KING, 99999),
+            ]
+            .into_iter()
+            .find(|&(kind, _)| attackers & self.bitboards.kind(kind) != EMPTY) else {
+                break;
+            };
+            depth += 1;
+            // What `side` stands at if it captures and the exchange ends there.
+            gain[depth] = on_target - gain[depth - 1];
+            if (-gain[depth - 1]).max(gain[depth]) < 0 {
+                // `side` is behind whether it captures or not, so this
+                // capture can't change the outcome.
+                depth -= 1;
+                break;
+            }
+            if depth == gain.len() - 1 {
+                break;
+            }
+            let attacker = attackers & self.bitboards.kind(kind);
+            occupied &= !(attacker & attacker.wrapping_neg());
+            on_target = value;
+            side = !side;
         }
-        // If `to` is defended by a friend, it's at worst an even trade.
-        // `is_threatened(to, enemy)` is true when a friend (!enemy) attacks `to`.
-        if nb.is_threatened(to, enemy) {
-            return false;
+        // Either side may stop capturing when that is better for it.
+        while depth > 0 {
+            gain[depth - 1] = -(-gain[depth - 1]).max(gain[depth]);
+            depth -= 1;
         }
-        // Only a real net loss counts: we grabbed less than we stand to lose.
-        captured < victim
+        gain[0]
     }
 
     /// Is this move a capture (including en-passant)?
-    fn is_capture(&self, m: Move) -> bool {
+    pub(crate) fn is_capture(&self, m: Move) -> bool {
         match m {
             Move::Piece(from, to) => {
                 if let Some(en_passant) = self.en_passant {
@@ -935,18 +1279,19 @@ impl Board {
 
     /// Quiet stand-pat: evaluate then search only captures.
     fn quiesce(
-        &self,
-        mut alpha: f64,
-        mut beta: f64,
+        &mut self,
+        mut alpha: i32,
+        mut beta: i32,
         is_maximizing: bool,
         getting_move_for: Color,
-        board_count: &mut u64,
+        stats: &mut SearchStats,
         depth: u8,
         ply: u8,
         flags: u16,
-        killers: &[[Option<Move>; 2]; MAX_PLY],
-    ) -> f64 {
-        *board_count += 1;
+        ordering: &MoveOrdering,
+    ) -> i32 {
+        stats.nodes += 1;
+        stats.qnodes += 1;
 
         let stand_pat = self.value_for(getting_move_for);
 
@@ -971,10 +1316,11 @@ impl Board {
         }
 
         let mut captures: Vec<Move> = self
-            .get_legal_moves()
+            .get_legal_moves_fast()
+            .into_iter()
             .filter(|m| self.is_capture(*m))
             .collect();
-        self.order_moves(&mut captures, ply, flags, killers);
+        self.order_moves(&mut captures, ply, flags, ordering);
 
         let mut best = if is_maximizing {
             NEG_INFINITY
@@ -982,9 +1328,9 @@ impl Board {
             POS_INFINITY
         };
 
-        // Delta-pruning margin (weighted units). If even winning the captured
+        // Delta-pruning margin (centipawns). If even winning the captured
         // piece for free can't reach alpha / beat beta, skip the capture.
-        const DELTA_MARGIN: f64 = 200.0;
+        const DELTA_MARGIN: i32 = 2000;
 
         for m in &captures {
             let to = match m {
@@ -992,10 +1338,10 @@ impl Board {
                 _ => continue,
             };
             let victim_weighted = match self.get_piece(to) {
-                Some(v) => v.get_material_value() as f64 * 10.0,
+                Some(v) => v.get_material_value() * 100,
                 // en-passant: the captured pawn isn't on `to`.
-                None if self.is_capture(*m) => 10.0,
-                None => 0.0,
+                None if self.is_capture(*m) => 100,
+                None => 0,
             };
 
             // Delta pruning: bail out of captures that obviously can't matter.
@@ -1006,19 +1352,25 @@ impl Board {
             } else if stand_pat - victim_weighted - DELTA_MARGIN >= beta {
                 continue;
             }
+            // A capture that loses material once the recaptures are played
+            // out can't improve on standing pat.
+            if self.see(*m) < 0 {
+                continue;
+            }
 
-            let child = self.apply_eval_move(*m);
-            let val = child.quiesce(
+            let undo = self.make_move(*m);
+            let val = self.quiesce(
                 alpha,
                 beta,
                 !is_maximizing,
                 getting_move_for,
-                board_count,
+                stats,
                 depth + 1,
                 ply + 1,
                 flags,
-                killers,
+                ordering,
             );
+            self.unmake_move(undo);
 
             if is_maximizing {
                 if val > best {
@@ -1061,95 +1413,464 @@ impl Board {
     /// `flags` enables optional features per difficulty (check extensions,
     /// null-move pruning, move ordering, quiescence search, etc.).
     /// `ply` is the distance from the root (0 = root).
-    /// `killers` stores quiet moves that caused beta cutoffs per ply.
+    /// `ordering` collects the killer moves and history that order the moves.
+    ///
+    /// This hashes the position once and then searches it as a
+    /// [`SearchBoard`], which keeps the Zobrist key up to date incrementally.
+    #[allow(clippy::too_many_arguments)]
     pub fn minimax(
         &self,
         tt: &mut TranspositionTable,
         depth: Either<u8, (&[u8], ChaCha20Rng)>,
-        mut alpha: f64,
-        mut beta: f64,
+        alpha: i32,
+        beta: i32,
         is_maximizing: bool,
         getting_move_for: Color,
         board_count: &mut u64,
         flags: u16,
         ply: u8,
-        killers: &mut [[Option<Move>; 2]; MAX_PLY],
-    ) -> f64 {
-        *board_count += 1;
-
-        // Leaf nodes: evaluate immediately WITHOUT computing the Zobrist hash.
-        // Leaves are ~4x more numerous than internal nodes and can never benefit
-        // from the transposition table, so hashing them is pure gas waste.
-        match &depth {
-            Either::Left(0) => {
-                if (flags & FLAG_QUIESCENCE) != 0 {
-                    return self.quiesce(
-                        alpha,
-                        beta,
-                        is_maximizing,
-                        getting_move_for,
-                        board_count,
-                        0,
-                        ply,
-                        flags,
-                        killers,
-                    );
+        ordering: &mut MoveOrdering,
+    ) -> i32 {
+        let mut stats = SearchStats::default();
+        let value = SearchBoard::new(*self).minimax(
+            tt,
+            depth,
+            alpha,
+            beta,
+            is_maximizing,
+            getting_move_for,
+            &mut stats,
+            flags,
+            ply,
+            ordering,
+        );
+        *board_count += stats.nodes;
+        value
+    }
+
+    /// Reduce depth by one ply for LMR.
+    fn reduce_depth(depth: Either<u8, (&[u8], ChaCha20Rng)>) -> Either<u8, (&[u8], ChaCha20Rng)> {
+        match depth {
+            Either::Left(d) => Either::Left(d.saturating_sub(1).max(1)),
+            Either::Right((d, rng)) => {
+                if d.len() <= 1 {
+                    Either::Right((d, rng))
+                } else {
+                    Either::Right((&d[1..], rng))
                 }
-                return self.value_for(getting_move_for);
-            }
-            Either::Right(([], _)) => {
-                if (flags & FLAG_QUIESCENCE) != 0 {
-                    return self.quiesce(
-                        alpha,
-                        beta,
-                        is_maximizing,
-                        getting_move_for,
-                        board_count,
-                        0,
-                        ply,
-                        flags,
-                        killers,
-                    );
+            }
+        }
+    }
+
+    /// Is there enough remaining depth to safely apply LMR?
+    fn has_enough_depth_for_lmr(depth: &Either<u8, (&[u8], ChaCha20Rng)>) -> bool {
+        match depth {
+            Either::Left(d) => *d >= 3,
+            Either::Right((d, _)) => !d.is_empty() && d[0] >= 3,
+        }
+    }
+}
+
+/// The Zobrist key of `piece` on its square.
+#[inline]
+fn piece_zobrist_key(piece: Piece) -> u64 {
+    let (pt, color) = piece.zobrist_indices();
+    PIECE_ZOBRIST_KEYS[pt][color][square_index(piece.get_pos())]
+}
+
+/// `piece`, promoted if it is a pawn moving to `to` on the last rank.
+fn promote(piece: Piece, to: Position, promotion: Option<Piece>) -> Piece {
+    if !piece.is_pawn() || (to.get_row() != 0 && to.get_row() != 7) {
+        return piece;
+    }
+    match promotion {
+        // promotion only required to specify piece type
+        Some(promotion) => {
+            // promoting to a king is only legal in Antichess, see
+            // `Variant::is_legal_move`
+            if promotion.is_pawn() {
+                // invalid promotion, use default
+                Piece::Queen(piece.get_color(), piece.get_pos())
+            } else {
+                promotion
+                    .with_color(piece.get_color())
+                    .move_to(piece.get_pos())
+            }
+        }
+        // queen by default
+        None => Piece::Queen(piece.get_color(), piece.get_pos()),
+    }
+}
+
+/// The from and to squares of a move, if it has them.
+fn move_squares(m: Move) -> Option<(usize, usize)> {
+    match m {
+        Move::Piece(from, to) | Move::Promotion(from, to, _) => {
+            Some((square_index(from), square_index(to)))
+        }
+        _ => None,
+    }
+}
+
+/// What the search learns about move ordering as it goes: killer moves, and
+/// with `FLAG_HISTORY_HEURISTIC` the history and countermove tables.
+#[derive(Clone, Debug)]
+pub struct MoveOrdering {
+    /// Quiet moves that caused beta cutoffs, per ply.
+    killers: [[Option<Move>; 2]; MAX_PLY],
+    /// How often and how deep quiet moves caused beta cutoffs, by side to
+    /// move, from square and to square.
+    history: Vec<i32>,
+    /// The quiet move that last refuted each move, by the from and to square
+    /// of the refuted move.
+    countermoves: Vec<Option<Move>>,
+    /// The move played to reach each ply, so a node knows what it answers.
+    line: [Option<Move>; MAX_PLY],
+}
+
+impl Default for MoveOrdering {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MoveOrdering {
+    pub fn new() -> Self {
+        Self {
+            killers: [[None; 2]; MAX_PLY],
+            history: vec![0; 2 * 64 * 64],
+            countermoves: vec![None; 64 * 64],
+            line: [None; MAX_PLY],
+        }
+    }
+
+    /// Start the next iteration of iterative deepening. Killers only hold
+    /// for the depth they were found at, so they are forgotten, while the
+    /// history is halved, so the new iteration's cutoffs count for more.
+    fn new_iteration(&mut self) {
+        self.killers = [[None; 2]; MAX_PLY];
+        for score in &mut self.history {
+            *score /= 2;
+        }
+    }
+
+    fn killer(&self, ply: u8, slot: usize) -> Option<Move> {
+        self.killers
+            .get(ply as usize)
+            .and_then(|killers| killers[slot])
+    }
+
+    /// The quiet move that last refuted the move played before `ply`.
+    fn countermove(&self, ply: u8) -> Option<Move> {
+        let previous = self.line.get((ply as usize).checked_sub(1)?)?;
+        let (from, to) = move_squares((*previous)?)?;
+        self.countermoves[from * 64 + to]
+    }
+
+    /// The history of a quiet move, scaled to at most
+    /// [`HISTORY_ORDERING_MAX`].
+    fn history_score(&self, color: Color, m: Move) -> i32 {
+        match move_squares(m) {
+            Some((from, to)) => {
+                self.history[color as usize * 4096 + from * 64 + to] * HISTORY_ORDERING_MAX
+                    / HISTORY_MAX
+            }
+            None => 0,
+        }
+    }
+
+    /// Record the move played at `ply`, `None` for a null move.
+    fn set_line(&mut self, ply: u8, m: Option<Move>) {
+        if let Some(entry) = self.line.get_mut(ply as usize) {
+            *entry = m;
+        }
+    }
+
+    /// Remember the quiet move `m` of `color`, which caused a beta cutoff at
+    /// `ply` with `depth` plies left to search.
+    fn record_cutoff(&mut self, m: Move, color: Color, ply: u8, depth: u8, flags: u16) {
+        if (flags & FLAG_KILLER_HEURISTIC) != 0 && (ply as usize) < MAX_PLY {
+            let slot = &mut self.killers[ply as usize];
+            if slot[0] != Some(m) {
+                slot[1] = slot[0];
+                slot[0] = Some(m);
+            }
+        }
+        if (flags & FLAG_HISTORY_HEURISTIC) == 0 {
+            return;
+        }
+        if let Some(previous) = (ply as usize)
+            .checked_sub(1)
+            .and_then(|ply| self.line.get(ply).copied().flatten())
+            .and_then(move_squares)
+        {
+            self.countermoves[previous.0 * 64 + previous.1] = Some(m);
+        }
+        if let Some((from, to)) = move_squares(m) {
+            let score = &mut self.history[color as usize * 4096 + from * 64 + to];
+            *score += depth as i32 * depth as i32;
+            if *score >= HISTORY_MAX {
+                for score in &mut self.history {
+                    *score /= 2;
                 }
-                return self.value_for(getting_move_for);
             }
-            _ => {}
         }
+    }
+}
 
-        // Internal node: compute Zobrist hash and probe the transposition table.
-        let zobrist = self.zobrist_key();
-        let tt_key = tt_context_key(zobrist, &depth);
+/// What [`Board::make_move`] changed, so that [`Board::unmake_move`] can
+/// restore it: the moved and captured pieces, the castling squares, and the
+/// en-passant square and castling rights from before the move.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MoveUndo {
+    m: Move,
+    /// The piece that moved, on its square before the move.
+    moved: Option<Piece>,
+    /// The captured piece, on its own square, which differs from the
+    /// destination for en-passant.
+    captured: Option<Piece>,
+    /// `(king, rook, king destination, rook destination)` of a castling move.
+    castling: Option<(Position, Position, Position, Position)>,
+    en_passant: Option<Position>,
+    white_castling_rights: CastlingRights,
+    black_castling_rights: CastlingRights,
+    /// The Zobrist key of the position before the move XOR the key after it.
+    key: u64,
+}
+
+impl MoveUndo {
+    /// The move that was made.
+    #[inline]
+    pub fn get_move(&self) -> Move {
+        self.m
+    }
+
+    /// The piece the move captured, if any.
+    #[inline]
+    pub fn get_captured(&self) -> Option<Piece> {
+        self.captured
+    }
+
+    /// The Zobrist key of the position before the move XOR the key after
+    /// it, to update a key without rehashing the position.
+    #[inline]
+    pub fn get_zobrist_delta(&self) -> u64 {
+        self.key
+    }
+}
+
+/// A [`Board`] paired with its Zobrist key.
+///
+/// Applying a move rehashes only what the move changed — the moved,
+/// captured and castling pieces, the castling rights, the en-passant square
+/// and the side to move — so transposition-table probes during search don't recompute the key
+/// from all pieces. This wrapper only lives for the duration of a search and
+/// is never stored, so the `Board` layout in contract state is unaffected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SearchBoard {
+    board: Board,
+    key: u64,
+}
+
+impl From<Board> for SearchBoard {
+    fn from(board: Board) -> Self {
+        Self::new(board)
+    }
+}
+
+impl core::ops::Deref for SearchBoard {
+    type Target = Board;
+
+    fn deref(&self) -> &Board {
+        &self.board
+    }
+}
+
+impl SearchBoard {
+    pub fn new(board: Board) -> Self {
+        Self {
+            key: board.zobrist_key(),
+            board,
+        }
+    }
+
+    #[inline]
+    pub fn get_board(&self) -> &Board {
+        &self.board
+    }
+
+    /// The Zobrist key of the position, without recomputing it.
+    #[inline]
+    pub fn zobrist_key(&self) -> u64 {
+        self.key
+    }
+
+    /// Like [`Board::apply_eval_move`], updating the key incrementally.
+    #[inline]
+    pub fn apply_eval_move(&self, m: Move) -> Self {
+        let mut next = *self;
+        next.make_move(m);
+        next
+    }
+
+    /// Like [`Board::make_move`], updating the key incrementally.
+    #[inline]
+    pub fn make_move(&mut self, m: Move) -> MoveUndo {
+        let undo = self.board.make_move(m);
+        self.key ^= undo.key;
+        undo
+    }
+
+    /// Like [`Board::unmake_move`], restoring the key as well.
+    #[inline]
+    pub fn unmake_move(&mut self, undo: MoveUndo) {
+        self.board.unmake_move(undo);
+        self.key ^= undo.key;
+    }
+
+    /// Pass the turn in place, for a null move. Passing it again takes the
+    /// null move back.
+    #[inline]
+    fn pass_turn(&mut self) {
+        self.board.turn = !self.board.turn;
+        self.key ^= BLACK_TO_MOVE_ZOBRIST_KEY;
+    }
+
+    /// Pass the turn (a null move), updating the key incrementally.
+    #[inline]
+    pub fn change_turn(self) -> Self {
+        Self {
+            key: self.key ^ BLACK_TO_MOVE_ZOBRIST_KEY,
+            board: self.board.change_turn(),
+        }
+    }
+
+    /// Follow the best moves the search stored in `tt`, starting with
+    /// `first`, which was searched with the depth list `depths`. Stops where
+    /// the table has no move, at the search horizon or at an illegal move,
+    /// which can only come from a hash collision.
+    fn principal_variation(
+        &self,
+        tt: &TranspositionTable,
+        first: Move,
+        mut depths: &[u8],
+        flags: u16,
+    ) -> Vec<Move> {
+        let mut pv = vec![first];
+        let mut board = self.apply_eval_move(first);
+        while !depths.is_empty() && pv.len() < MAX_PLY {
+            let Some(m) = tt
+                .get(board.zobrist_key())
+                .and_then(|entry| entry.best_move)
+            else {
+                break;
+            };
+            let color = board.get_current_player_color();
+            if !board.is_legal_move(m, color) {
+                break;
+            }
+            // mirror the depth bookkeeping of `minimax`, including check
+            // extensions
+            if (flags & FLAG_CHECK_EXTENSIONS) == 0 || !board.is_in_check(color) {
+                depths = &depths[1..];
+            }
+            pv.push(m);
+            board = board.apply_eval_move(m);
+        }
+        pv
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn minimax(
+        &mut self,
+        tt: &mut TranspositionTable,
+        depth: Either<u8, (&[u8], ChaCha20Rng)>,
+        mut alpha: i32,
+        mut beta: i32,
+        is_maximizing: bool,
+        getting_move_for: Color,
+        stats: &mut SearchStats,
+        flags: u16,
+        ply: u8,
+        ordering: &mut MoveOrdering,
+    ) -> i32 {
+        stats.nodes += 1;
+
+        // With three pieces left the bitbases know the exact result, with
+        // four some of them know who wins. A known win still takes the
+        // evaluation so the search makes progress towards mate.
+        if (flags & FLAG_ENDGAME_HEURISTICS) != 0 && self.bitboards().occupied().count_ones() <= 4 {
+            let value = match bitbase::probe(self) {
+                Some(entry) => Some(entry.search_value(ply)),
+                None => bitbase::probe_wdl(self).map(|wdl| match wdl {
+                    bitbase::Wdl::Draw => 0,
+                    _ => wdl.search_value() + self.value_for(self.get_current_player_color()),
+                }),
+            };
+            if let Some(value) = value {
+                return if self.get_current_player_color() == getting_move_for {
+                    value
+                } else {
+                    -value
+                };
+            }
+        }
+
+        // Leaf nodes: evaluate immediately WITHOUT probing the transposition
+        // table. Leaves are ~4x more numerous than internal nodes and can never
+        // benefit from it.
+        if matches!(depth, Either::Left(0) | Either::Right(([], _))) {
+            if (flags & FLAG_QUIESCENCE) != 0 {
+                return self.board.quiesce(
+                    alpha,
+                    beta,
+                    is_maximizing,
+                    getting_move_for,
+                    stats,
+                    0,
+                    ply,
+                    flags,
+                    ordering,
+                );
+            }
+            return self.value_for(getting_move_for);
+        }
+
+        // Internal node: probe the transposition table with the incrementally
+        // maintained Zobrist key.
+        let tt_key = self.zobrist_key();
         let tt_depth: u8 = match &depth {
             Either::Left(d) => *d,
             Either::Right((d, _)) => d.len() as u8,
         };
         let original_alpha = alpha;
         let original_beta = beta;
+        stats.tt_probes += 1;
         if let Some(entry) = tt.get(tt_key) {
+            stats.tt_hits += 1;
             if entry.depth >= tt_depth {
+                let value = value_from_tt(entry.value, ply);
                 match entry.flag {
-                    TtFlag::Exact => return entry.value,
-                    TtFlag::LowerBound if entry.value >= beta => return beta,
-                    TtFlag::UpperBound if entry.value <= alpha => return alpha,
+                    TtFlag::Exact => return value,
+                    TtFlag::LowerBound if value >= beta => return beta,
+                    TtFlag::UpperBound if value <= alpha => return alpha,
                     _ => {}
                 }
             }
         }
 
+        let side_to_move = self.get_current_player_color();
+        let in_check = self.is_in_check(side_to_move);
         let (mut next_depth, max_moves) = match depth {
             Either::Left(d) => {
-                if (flags & FLAG_CHECK_EXTENSIONS) != 0
-                    && self.is_in_check(self.get_current_player_color())
-                {
+                if (flags & FLAG_CHECK_EXTENSIONS) != 0 && in_check {
                     (Either::Left(d), None)
                 } else {
                     (Either::Left(d - 1), None)
                 }
             }
             Either::Right((d, rng)) => {
-                if (flags & FLAG_CHECK_EXTENSIONS) != 0
-                    && self.is_in_check(self.get_current_player_color())
-                {
+                if (flags & FLAG_CHECK_EXTENSIONS) != 0 && in_check {
                     (Either::Right((d, rng)), Some(d[0]))
                 } else {
                     (Either::Right((&d[1..], rng)), Some(d[0]))
@@ -1161,37 +1882,31 @@ impl Board {
         // an empty move list just caused the loop below to return its init
         // sentinel (+/-999999), so the search scored a drawing stalemate
         // identically to a winning checkmate — and the AI happily stalemated
-        // won games. Also precompute & order the move list once so we don't
-        // regenerate legal moves four times below.
-        let side_to_move = self.get_current_player_color();
+        // won games. Also precompute & order the move list once.
         let mut legal_moves: Vec<Move> = self.get_legal_moves_fast();
         if legal_moves.is_empty() {
-            if self.is_in_check(side_to_move) {
+            if in_check {
                 // `side_to_move` is checkmated. Prefer faster mates via ply.
                 if side_to_move == getting_move_for {
-                    return -MATE + ply as f64;
+                    return -MATE + ply as i32;
                 } else {
-                    return MATE - ply as f64;
+                    return MATE - ply as i32;
                 }
             }
             // Stalemate: a draw for both sides.
-            return 0.0;
+            return 0;
         }
         if (flags & FLAG_MOVE_ORDERING) != 0 {
-            self.order_moves(&mut legal_moves, ply, flags, killers);
+            self.order_moves(&mut legal_moves, ply, flags, ordering);
         }
 
         // Null-move pruning (skip turn, see if position is still crushing)
-        if (flags & FLAG_NULL_MOVE_PRUNING) != 0
-            && self.count_pieces() >= 6
-            && !self.is_in_check(self.get_current_player_color())
-        {
+        if (flags & FLAG_NULL_MOVE_PRUNING) != 0 && self.count_pieces() >= 6 && !in_check {
             let enough_depth = match next_depth {
                 Either::Left(d) => d >= 3,
                 Either::Right((d, _)) => !d.is_empty() && d[0] >= 3,
             };
             if enough_depth {
-                let null_board = self.change_turn();
                 let null_depth: Either<u8, (&[u8], ChaCha20Rng)> = match next_depth {
                     Either::Left(d) => Either::Left(d - 2),
                     Either::Right((d, ref rng)) => {
@@ -1202,371 +1917,159 @@ impl Board {
                         }
                     }
                 };
-                if is_maximizing {
-                    let null_score = null_board.minimax(
-                        tt,
-                        null_depth,
-                        beta - 1.0,
-                        beta,
-                        false,
-                        getting_move_for,
-                        board_count,
-                        flags,
-                        ply + 1,
-                        killers,
-                    );
-                    if null_score >= beta {
-                        return beta;
-                    }
+                let (null_alpha, null_beta) = if is_maximizing {
+                    (beta - 1, beta)
                 } else {
-                    let null_score = null_board.minimax(
-                        tt,
-                        null_depth,
-                        alpha,
-                        alpha + 1.0,
-                        true,
-                        getting_move_for,
-                        board_count,
-                        flags,
-                        ply + 1,
-                        killers,
-                    );
-                    if null_score <= alpha {
-                        return alpha;
-                    }
-                }
-            }
-        }
-
-        let mut best_move_value;
-
-        if is_maximizing {
-            best_move_value = NEG_INFINITY;
-
-            if let Some(max_moves) = max_moves {
-                let Either::Right((_, rng)) = &mut next_depth else {
-                    panic!();
+                    (alpha, alpha + 1)
                 };
-                let sampled = self.sample_moves(&legal_moves, rng, max_moves as usize);
-                for (move_idx, m) in sampled.iter().enumerate() {
-                    let m = *m;
-                    let child = self.apply_eval_move(m);
-                    let child_board_value = if (flags & FLAG_LATE_MOVE_REDUCTION) != 0
-                        && move_idx >= 4
-                        && !self.is_capture(m)
-                        && !matches!(m, Move::Promotion(_, _, _))
-                        && !self.is_in_check(self.get_current_player_color())
-                        && !child.is_in_check(child.get_current_player_color())
-                        && Self::has_enough_depth_for_lmr(&next_depth)
-                    {
-                        let reduced = Self::reduce_depth(next_depth.clone());
-                        let reduced_value = child.minimax(
-                            tt,
-                            reduced,
-                            alpha,
-                            beta,
-                            !is_maximizing,
-                            getting_move_for,
-                            board_count,
-                            flags,
-                            ply + 1,
-                            killers,
-                        );
-                        if reduced_value > alpha {
-                            child.minimax(
-                                tt,
-                                next_depth.clone(),
-                                alpha,
-                                beta,
-                                !is_maximizing,
-                                getting_move_for,
-                                board_count,
-                                flags,
-                                ply + 1,
-                                killers,
-                            )
-                        } else {
-                            reduced_value
-                        }
-                    } else {
-                        child.minimax(
-                            tt,
-                            next_depth.clone(),
-                            alpha,
-                            beta,
-                            !is_maximizing,
-                            getting_move_for,
-                            board_count,
-                            flags,
-                            ply + 1,
-                            killers,
-                        )
-                    };
-
-                    if child_board_value > best_move_value {
-                        best_move_value = child_board_value;
-                    }
-
-                    if best_move_value > alpha {
-                        alpha = best_move_value
-                    }
-
-                    if beta <= alpha {
-                        if (flags & FLAG_KILLER_HEURISTIC) != 0
-                            && (ply as usize) < MAX_PLY
-                            && !self.is_capture(m)
-                        {
-                            let slot = &mut killers[ply as usize];
-                            if slot[0] != Some(m) {
-                                slot[1] = slot[0];
-                                slot[0] = Some(m);
-                            }
-                        }
-                        return best_move_value;
-                    }
+                self.pass_turn();
+                ordering.set_line(ply, None);
+                let null_score = self.minimax(
+                    tt,
+                    null_depth,
+                    null_alpha,
+                    null_beta,
+                    !is_maximizing,
+                    getting_move_for,
+                    stats,
+                    flags,
+                    ply + 1,
+                    ordering,
+                );
+                self.pass_turn();
+                if is_maximizing && null_score >= beta {
+                    return beta;
                 }
-            } else {
-                for (move_idx, m) in legal_moves.iter().enumerate() {
-                    let m = *m;
-                    let child = self.apply_eval_move(m);
-                    let child_board_value = if (flags & FLAG_LATE_MOVE_REDUCTION) != 0
-                        && move_idx >= 4
-                        && !self.is_capture(m)
-                        && !matches!(m, Move::Promotion(_, _, _))
-                        && !self.is_in_check(self.get_current_player_color())
-                        && !child.is_in_check(child.get_current_player_color())
-                        && Self::has_enough_depth_for_lmr(&next_depth)
-                    {
-                        let reduced = Self::reduce_depth(next_depth.clone());
-                        let reduced_value = child.minimax(
-                            tt,
-                            reduced,
-                            alpha,
-                            beta,
-                            !is_maximizing,
-                            getting_move_for,
-                            board_count,
-                            flags,
-                            ply + 1,
-                            killers,
-                        );
-                        if reduced_value > alpha {
-                            child.minimax(
-                                tt,
-                                next_depth.clone(),
-                                alpha,
-                                beta,
-                                !is_maximizing,
-                                getting_move_for,
-                                board_count,
-                                flags,
-                                ply + 1,
-                                killers,
-                            )
-                        } else {
-                            reduced_value
-                        }
-                    } else {
-                        child.minimax(
-                            tt,
-                            next_depth.clone(),
-                            alpha,
-                            beta,
-                            !is_maximizing,
-                            getting_move_for,
-                            board_count,
-                            flags,
-                            ply + 1,
-                            killers,
-                        )
-                    };
-
-                    if child_board_value > best_move_value {
-                        best_move_value = child_board_value;
-                    }
-
-                    if best_move_value > alpha {
-                        alpha = best_move_value
-                    }
-
-                    if beta <= alpha {
-                        if (flags & FLAG_KILLER_HEURISTIC) != 0
-                            && (ply as usize) < MAX_PLY
-                            && !self.is_capture(m)
-                        {
-                            let slot = &mut killers[ply as usize];
-                            if slot[0] != Some(m) {
-                                slot[1] = slot[0];
-                                slot[0] = Some(m);
-                            }
-                        }
-                        return best_move_value;
-                    }
+                if !is_maximizing && null_score <= alpha {
+                    return alpha;
                 }
-            };
-        } else {
-            best_move_value = POS_INFINITY;
-
-            if let Some(max_moves) = max_moves {
-                let Either::Right((_, rng)) = &mut next_depth else {
-                    panic!();
-                };
-                let sampled = self.sample_moves(&legal_moves, rng, max_moves as usize);
-                for (move_idx, m) in sampled.iter().enumerate() {
-                    let m = *m;
-                    let child = self.apply_eval_move(m);
-                    let child_board_value = if (flags & FLAG_LATE_MOVE_REDUCTION) != 0
-                        && move_idx >= 4
-                        && !self.is_capture(m)
-                        && !matches!(m, Move::Promotion(_, _, _))
-                        && !self.is_in_check(self.get_current_player_color())
-                        && !child.is_in_check(child.get_current_player_color())
-                        && Self::has_enough_depth_for_lmr(&next_depth)
-                    {
-                        let reduced = Self::reduce_depth(next_depth.clone());
-                        let reduced_value = child.minimax(
-                            tt,
-                            reduced,
-                            alpha,
-                            beta,
-                            !is_maximizing,
-                            getting_move_for,
-                            board_count,
-                            flags,
-                            ply + 1,
-                            killers,
-                        );
-                        if reduced_value < beta {
-                            child.minimax(
-                                tt,
-                                next_depth.clone(),
-                                alpha,
-                                beta,
-                                !is_maximizing,
-                                getting_move_for,
-                                board_count,
-                                flags,
-                                ply + 1,
-                                killers,
-                            )
-                        } else {
-                            reduced_value
-                        }
-                    } else {
-                        child.minimax(
-                            tt,
-                            next_depth.clone(),
-                            alpha,
-                            beta,
-                            !is_maximizing,
-                            getting_move_for,
-                            board_count,
-                            flags,
-                            ply + 1,
-                            killers,
-                        )
-                    };
+            }
+        }
 
-                    if child_board_value < best_move_value {
-                        best_move_value = child_board_value;
-                    }
+        // The randomized depth list searches only a sample of the moves.
+        let sampled = max_moves.is_some_and(|width| (width as usize) < legal_moves.len());
+        let moves = match (max_moves, &mut next_depth) {
+            (Some(max_moves), Either::Right((_, rng))) => {
+                self.sample_moves(&legal_moves, rng, max_moves as usize)
+            }
+            _ => legal_moves,
+        };
 
-                    if best_move_value < beta {
-                        beta = best_move_value
-                    }
+        let mut best_move_value = if is_maximizing {
+            NEG_INFINITY
+        } else {
+            POS_INFINITY
+        };
+        let mut best_move = None;
+
+        for (move_idx, &m) in moves.iter().enumerate() {
+            let is_capture = self.is_capture(m);
+            let reducible = (flags & FLAG_LATE_MOVE_REDUCTION) != 0
+                && move_idx >= 4
+                && !is_capture
+                && !matches!(m, Move::Promotion(_, _, _))
+                && !in_check;
+
+            let undo = self.make_move(m);
+            ordering.set_line(ply, Some(m));
+            let reduce = reducible
+                && !self.is_in_check(self.get_current_player_color())
+                && Board::has_enough_depth_for_lmr(&next_depth);
+            // Principal variation search: the first move is expected to be
+            // the best, so the later ones are searched with a zero window,
+            // which only proves that they are no better.
+            let zero_window = (flags & FLAG_PRINCIPAL_VARIATION_SEARCH) != 0 && move_idx > 0;
+            let (scout_alpha, scout_beta) = if !zero_window {
+                (alpha, beta)
+            } else if is_maximizing {
+                (alpha, alpha + 1)
+            } else {
+                (beta - 1, beta)
+            };
+            let mut child_board_value = self.minimax(
+                tt,
+                if reduce {
+                    Board::reduce_depth(next_depth.clone())
+                } else {
+                    next_depth.clone()
+                },
+                scout_alpha,
+                scout_beta,
+                !is_maximizing,
+                getting_move_for,
+                stats,
+                flags,
+                ply + 1,
+                ordering,
+            );
+            // re-search at full depth if the reduced search suggests
+            // the move improves on the window
+            let improves = |value: i32| {
+                if is_maximizing {
+                    value > alpha
+                } else {
+                    value < beta
+                }
+            };
+            if reduce && improves(child_board_value) {
+                child_board_value = self.minimax(
+                    tt,
+                    next_depth.clone(),
+                    scout_alpha,
+                    scout_beta,
+                    !is_maximizing,
+                    getting_move_for,
+                    stats,
+                    flags,
+                    ply + 1,
+                    ordering,
+                );
+            }
+            // and with the full window if the zero window search found a
+            // better move after all, to get its exact value
+            if zero_window && child_board_value > alpha && child_board_value < beta {
+                child_board_value = self.minimax(
+                    tt,
+                    next_depth.clone(),
+                    alpha,
+                    beta,
+                    !is_maximizing,
+                    getting_move_for,
+                    stats,
+                    flags,
+                    ply + 1,
+                    ordering,
+                );
+            }
+            self.unmake_move(undo);
 
-                    if beta <= alpha {
-                        if (flags & FLAG_KILLER_HEURISTIC) != 0
-                            && (ply as usize) < MAX_PLY
-                            && !self.is_capture(m)
-                        {
-                            let slot = &mut killers[ply as usize];
-                            if slot[0] != Some(m) {
-                                slot[1] = slot[0];
-                                slot[0] = Some(m);
-                            }
-                        }
-                        return best_move_value;
-                    }
+            if is_maximizing {
+                if child_board_value > best_move_value {
+                    best_move_value = child_board_value;
+                    best_move = Some(m);
+                }
+                if best_move_value > alpha {
+                    alpha = best_move_value
                 }
             } else {
-                for (move_idx, m) in legal_moves.iter().enumerate() {
-                    let m = *m;
-                    let child = self.apply_eval_move(m);
-                    let child_board_value = if (flags & FLAG_LATE_MOVE_REDUCTION) != 0
-                        && move_idx >= 4
-                        && !self.is_capture(m)
-                        && !matches!(m, Move::Promotion(_, _, _))
-                        && !self.is_in_check(self.get_current_player_color())
-                        && !child.is_in_check(child.get_current_player_color())
-                        && Self::has_enough_depth_for_lmr(&next_depth)
-                    {
-                        let reduced = Self::reduce_depth(next_depth.clone());
-                        let reduced_value = child.minimax(
-                            tt,
-                            reduced,
-                            alpha,
-                            beta,
-                            !is_maximizing,
-                            getting_move_for,
-                            board_count,
-                            flags,
-                            ply + 1,
-                            killers,
-                        );
-                        if reduced_value < beta {
-                            child.minimax(
-                                tt,
-                                next_depth.clone(),
-                                alpha,
-                                beta,
-                                !is_maximizing,
-                                getting_move_for,
-                                board_count,
-                                flags,
-                                ply + 1,
-                                killers,
-                            )
-                        } else {
-                            reduced_value
-                        }
-                    } else {
-                        child.minimax(
-                            tt,
-                            next_depth.clone(),
-                            alpha,
-                            beta,
-                            !is_maximizing,
-                            getting_move_for,
-                            board_count,
-                            flags,
-                            ply + 1,
-                            killers,
-                        )
-                    };
-
-                    if child_board_value < best_move_value {
-                        best_move_value = child_board_value;
-                    }
-
-                    if best_move_value < beta {
-                        beta = best_move_value
-                    }
+                if child_board_value < best_move_value {
+                    best_move_value = child_board_value;
+                    best_move = Some(m);
+                }
+                if best_move_value < beta {
+                    beta = best_move_value
+                }
+            }
 
-                    if beta <= alpha {
-                        if (flags & FLAG_KILLER_HEURISTIC) != 0
-                            && (ply as usize) < MAX_PLY
-                            && !self.is_capture(m)
-                        {
-                            let slot = &mut killers[ply as usize];
-                            if slot[0] != Some(m) {
-                                slot[1] = slot[0];
-                                slot[0] = Some(m);
-                            }
-                        }
-                        return best_move_value;
-                    }
+            if beta <= alpha {
+                stats.beta_cutoffs += 1;
+                if move_idx == 0 {
+                    stats.first_move_cutoffs += 1;
                 }
+                if !is_capture {
+                    ordering.record_cutoff(m, side_to_move, ply, tt_depth, flags);
+                }
+                return best_move_value;
             }
         }
 
@@ -1577,31 +2080,18 @@ impl Board {
         } else {
             TtFlag::Exact
         };
-        tt.store(tt_key, tt_depth, flag, best_move_value, None);
+        // The value of a sample can miss the best move, so it is stored as
+        // unsearched: the principal variation still follows its move, but it
+        // never answers a probe.
+        tt.store(
+            tt_key,
+            if sampled { 0 } else { tt_depth },
+            flag,
+            value_to_tt(best_move_value, ply),
+            best_move,
+        );
         best_move_value
     }
-
-    /// Reduce depth by one ply for LMR.
-    fn reduce_depth(depth: Either<u8, (&[u8], ChaCha20Rng)>) -> Either<u8, (&[u8], ChaCha20Rng)> {
-        match depth {
-            Either::Left(d) => Either::Left(d.saturating_sub(1).max(1)),
-            Either::Right((d, rng)) => {
-                if d.len() <= 1 {
-                    Either::Right((d, rng))
-                } else {
-                    Either::Right((&d[1..], rng))
-                }
-            }
-        }
-    }
-
-    /// Is there enough remaining depth to safely apply LMR?
-    fn has_enough_depth_for_lmr(depth: &Either<u8, (&[u8], ChaCha20Rng)>) -> bool {
-        match depth {
-            Either::Left(d) => *d >= 3,
-            Either::Right((d, _)) => !d.is_empty() && d[0] >= 3,
-        }
-    }
 }
 
 impl core::fmt::Display for Board {
@@ -1679,7 +2169,8 @@ impl core::fmt::Display for Board {
 }
 
 impl Board {
-    /// Create the default board for the Horde variant
+    /// Create the default board for the Horde variant, where White has no
+    /// king and so cannot castle.
     pub fn horde() -> Self {
         BoardBuilder::from(Board::default())
             .row(Piece::Pawn(WHITE, A1))
@@ -1690,9 +2181,81 @@ impl Board {
             .piece(Piece::Pawn(WHITE, G5))
             .piece(Piece::Pawn(WHITE, B5))
             .piece(Piece::Pawn(WHITE, C5))
+            .disable_kingside_castle(WHITE)
+            .disable_queenside_castle(WHITE)
             .build()
     }
 
+    /// Create the starting board of Chess960 (Fischer Random) position
+    /// number `index`, using Scharnagl's numbering of the 960 positions.
+    /// Position 518 is the standard starting position. The index is taken
+    /// modulo 960.
+    pub fn chess960(index: u16) -> Self {
+        let mut n = index as usize % 960;
+        let mut back_rank = [' '; 8];
+        // light-squared bishop on b, d, f or h, dark-squared one on a, c, e or g
+        back_rank[n % 4 * 2 + 1] = 'b';
+        n /= 4;
+        back_rank[n % 4 * 2] = 'b';
+        n /= 4;
+        // the queen and then both knights go on the remaining free files
+        let mut place = |nth: usize, piece: char| {
+            let col = (0..8)
+                .filter(|&col| back_rank[col] == ' ')
+                .nth(nth)
+                .unwrap();
+            back_rank[col] = piece;
+        };
+        place(n % 6, 'q');
+        n /= 6;
+        let (first, second) = [
+            (0, 1),
+            (0, 2),
+            (0, 3),
+            (0, 4),
+            (1, 2),
+            (1, 3),
+            (1, 4),
+            (2, 3),
+            (2, 4),
+            (3, 4),
+        ][n];
+        // placing the first knight shifts the later free files left by one
+        place(first, 'n');
+        place(second - 1, 'n');
+        // rook, king and rook fill the last three files in that order
+        for piece in ['r', 'k', 'r'] {
+            place(0, piece);
+        }
+
+        let mut builder = BoardBuilder::default()
+            .row(Piece::Pawn(BLACK, A7))
+            .row(Piece::Pawn(WHITE, A2));
+        let mut rook_cols = Vec::new();
+        for (col, piece) in back_rank.into_iter().enumerate() {
+            let col = col as i32;
+            let piece = match piece {
+                'b' => Piece::Bishop,
+                'q' => Piece::Queen,
+                'n' => Piece::Knight,
+                'k' => Piece::King,
+                _ => {
+                    rook_cols.push(col);
+                    Piece::Rook
+                }
+            };
+            builder = builder
+                .piece(piece(WHITE, Position::new(0, col)))
+                .piece(piece(BLACK, Position::new(7, col)));
+        }
+        for color in [WHITE, BLACK] {
+            builder = builder
+                .enable_queenside_castle_with(color, rook_cols[0])
+                .enable_kingside_castle_with(color, rook_cols[1]);
+        }
+        builder.build()
+    }
+
     pub fn empty() -> Self {
         Self {
             squares: [EMPTY_SQUARE; 64],
@@ -1702,6 +2265,8 @@ impl Board {
             black_castling_rights: CastlingRights::default(),
 
             turn: WHITE,
+
+            bitboards: Bitboards::default(),
         }
     }
 
@@ -1758,12 +2323,8 @@ impl Board {
     /// Remove all of the pieces for a given player
     pub fn remove_all(&self, color: Color) -> Self {
         let mut result = *self;
-        for square in &mut result.squares {
-            if let Some(piece) = square.get_piece() {
-                if piece.get_color() == color {
-                    *square = EMPTY_SQUARE
-                }
-            }
+        for sq in squares(self.bitboards.color(color)) {
+            result.set_square(square_position(sq), EMPTY_SQUARE);
         }
 
         result
@@ -1772,12 +2333,8 @@ impl Board {
     /// Convert all of a given players pieces to queens
     pub fn queen_all(&self, color: Color) -> Self {
         let mut result = *self;
-        for square in &mut result.squares {
-            if let Some(piece) = square.get_piece() {
-                if !piece.is_king() && piece.get_color() == color {
-                    *square = Square::from(Piece::Queen(color, piece.get_pos()))
-                }
-            }
+        for sq in squares(self.bitboards.color(color) & !self.bitboards.kind(KING)) {
+            result.add_piece(Piece::Queen(color, square_position(sq)));
         }
 
         result
@@ -1794,7 +2351,7 @@ impl Board {
     /// Count all pieces on the board
     #[inline]
     pub fn count_pieces(&self) -> u32 {
-        self.squares.iter().filter(|s| !s.is_empty()).count() as u32
+        self.bitboards.occupied().count_ones()
     }
 
     /// Count pieces remaining on starting rows (0, 1, 6, 7).
@@ -1830,15 +2387,36 @@ impl Board {
             .sum()
     }
 
+    /// Replace the contents of a square, keeping the bitboards in sync.
     #[inline]
-    fn get_square(&mut self, pos: Position) -> &mut Square {
-        &mut self.squares[((7 - pos.get_row()) * 8 + pos.get_col()) as usize]
+    fn set_square(&mut self, pos: Position, square: Square) {
+        self.squares[((7 - pos.get_row()) * 8 + pos.get_col()) as usize] = square;
+        self.bitboards.remove(pos);
+        if let Some(piece) = square.get_piece() {
+            self.bitboards.put(piece);
+        }
     }
 
     #[inline]
     fn add_piece(&mut self, piece: Piece) {
-        let pos = piece.get_pos();
-        *self.get_square(pos) = Square::from(piece);
+        self.set_square(piece.get_pos(), Square::from(piece));
+    }
+
+    /// Recompute the bitboards from `squares`. Called after deserializing,
+    /// since the bitboards are not stored.
+    fn rebuild_bitboards(&mut self) {
+        self.bitboards = Bitboards::default();
+        for square in &self.squares {
+            if let Some(piece) = square.get_piece() {
+                self.bitboards.put(piece);
+            }
+        }
+    }
+
+    /// The occupancy of the board as bitboards.
+    #[inline]
+    pub fn bitboards(&self) -> &Bitboards {
+        &self.bitboards
     }
 
     /// Does a square have any piece?
@@ -1890,43 +2468,24 @@ impl Board {
     }
 
     /// If there is a king on the board, return the position that it sits on.
+    #[inline]
     pub fn get_king_pos(&self, color: Color) -> Option<Position> {
-        let mut king_pos = None;
-        for square in &self.squares {
-            if let Some(Piece::King(c, pos)) = square.get_piece() {
-                if c == color {
-                    king_pos = Some(pos);
-                }
-            }
-        }
-        king_pos
+        squares(self.bitboards.pieces(KING, color))
+            .next()
+            .map(square_position)
     }
 
     /// Is a square threatened by an enemy piece?
+    ///
+    /// A square holding an enemy piece is never threatened by the enemy,
+    /// the same as in [`Piece::is_legal_attack`].
     pub fn is_threatened(&self, pos: Position, ally_color: Color) -> bool {
-        for (i, square) in self.squares.iter().enumerate() {
-            let row = 7 - i / 8;
-            let col = i % 8;
-            let square_pos = Position::new(row as i32, col as i32);
-            if !square_pos.is_orthogonal_to(pos)
-                && !square_pos.is_diagonal_to(pos)
-                && !square_pos.is_knight_move(pos)
-            {
-                continue;
-            }
-
-            if let Some(piece) = square.get_piece() {
-                if piece.get_color() == ally_color {
-                    continue;
-                }
-
-                if piece.is_legal_attack(pos, self) {
-                    return true;
-                }
-            }
+        if pos.is_off_board() || self.has_enemy_piece(pos, ally_color) {
+            return false;
         }
-
-        false
+        self.bitboards
+            .attackers_to(square_index(pos), !ally_color, self.bitboards.occupied())
+            != EMPTY
     }
 
     /// Get whether or not the king of a given color is in check.
@@ -1947,27 +2506,10 @@ impl Board {
             return result;
         }
 
-        let from_square = result.get_square(from);
-        if let Some(mut piece) = from_square.get_piece() {
-            *from_square = EMPTY_SQUARE;
+        if let Some(piece) = result.get_piece(from) {
+            result.set_square(from, EMPTY_SQUARE);
 
-            if piece.is_pawn() && (to.get_row() == 0 || to.get_row() == 7) {
-                piece = match promotion {
-                    // promotion only required to specify piece type
-                    Some(promotion) => {
-                        if promotion.is_king() || promotion.is_pawn() {
-                            // invalid promotion, use default
-                            Piece::Queen(piece.get_color(), piece.get_pos())
-                        } else {
-                            promotion
-                                .with_color(piece.get_color())
-                                .move_to(piece.get_pos())
-                        }
-                    }
-                    // queen by default
-                    None => Piece::Queen(piece.get_color(), piece.get_pos()),
-                }
-            }
+            let piece = promote(piece, to, promotion);
 
             if piece.is_starting_pawn() && (from.get_row() - to.get_row()).abs() == 2 {
                 result.en_passant = Some(to.pawn_back(piece.get_color()))
@@ -1975,74 +2517,100 @@ impl Board {
 
             result.add_piece(piece.move_to(to));
 
-            let castling_rights = match piece.get_color() {
-                WHITE => &mut result.white_castling_rights,
-                BLACK => &mut result.black_castling_rights,
-            };
-
             if piece.is_king() {
-                castling_rights.disable_all();
-            } else if piece.is_queenside_rook() {
-                castling_rights.disable_queenside();
-            } else if piece.is_kingside_rook() {
-                castling_rights.disable_kingside();
+                result.castling_rights_mut(piece.get_color()).disable_all();
+            }
+            // A castling rook that moves away or is captured takes the
+            // castling right on its side with it.
+            for color in [WHITE, BLACK] {
+                let castling_rights = result.castling_rights_mut(color);
+                castling_rights.disable_rook_at(from, color);
+                castling_rights.disable_rook_at(to, color);
             }
         }
 
         result
     }
 
-    /// Can a given player castle kingside?
-    pub fn can_kingside_castle(&self, color: Color) -> bool {
-        let right_of_king = Position::king_pos(color).next_right();
+    fn castling_rights_mut(&mut self, color: Color) -> &mut CastlingRights {
         match color {
-            WHITE => {
-                self.has_no_piece(Position::new(0, 5))
-                    && self.has_no_piece(Position::new(0, 6))
-                    && self.get_piece(Position::new(0, 7))
-                        == Some(Piece::Rook(color, Position::new(0, 7)))
-                    && self.white_castling_rights.can_kingside_castle()
-                    && !self.is_in_check(color)
-                    && !self.is_threatened(right_of_king, color)
-                    && !self.is_threatened(right_of_king.next_right(), color)
-            }
-            BLACK => {
-                self.has_no_piece(Position::new(7, 5))
-                    && self.has_no_piece(Position::new(7, 6))
-                    && self.get_piece(Position::new(7, 7))
-                        == Some(Piece::Rook(color, Position::new(7, 7)))
-                    && self.black_castling_rights.can_kingside_castle()
-                    && !self.is_in_check(color)
-                    && !self.is_threatened(right_of_king, color)
-                    && !self.is_threatened(right_of_king.next_right(), color)
-            }
+            WHITE => &mut self.white_castling_rights,
+            BLACK => &mut self.black_castling_rights,
+        }
+    }
+
+    /// The squares involved in castling to one side, as
+    /// `(king, rook, king destination, rook destination)`, or `None` if the
+    /// player has lost the right or the king and rook are not in place.
+    ///
+    /// The king always lands on the g- or c-file and the rook next to it on
+    /// the f- or d-file, wherever they started, so this covers Chess960 too.
+    fn castling_squares(
+        &self,
+        color: Color,
+        kingside: bool,
+    ) -> Option<(Position, Position, Position, Position)> {
+        let rights = self.get_castling_rights(color);
+        let (allowed, rook_col, king_to_col, rook_to_col) = if kingside {
+            (
+                rights.can_kingside_castle(),
+                rights.kingside_rook_col(),
+                6,
+                5,
+            )
+        } else {
+            (
+                rights.can_queenside_castle(),
+                rights.queenside_rook_col(),
+                2,
+                3,
+            )
+        };
+        let king = self.get_king_pos(color)?;
+        let row = Position::king_pos(color).get_row();
+        let rook = Position::new(row, rook_col);
+        if !allowed
+            || king.get_row() != row
+            || (rook_col > king.get_col()) != kingside
+            || self.get_piece(rook) != Some(Piece::Rook(color, rook))
+        {
+            return None;
+        }
+        Some((
+            king,
+            rook,
+            Position::new(row, king_to_col),
+            Position::new(row, rook_to_col),
+        ))
+    }
+
+    fn can_castle(&self, color: Color, kingside: bool) -> bool {
+        let Some((king, rook, king_to, rook_to)) = self.castling_squares(color, kingside) else {
+            return false;
+        };
+        let (king, rook) = (square_index(king), square_index(rook));
+        let (king_to, rook_to) = (square_index(king_to), square_index(rook_to));
+        // Apart from the king and rook themselves, everything between them
+        // and their destinations has to be empty.
+        let others = self.bitboards.occupied() & !(1 << king) & !(1 << rook);
+        let path = between(king, king_to) | between(rook, rook_to) | 1 << king_to | 1 << rook_to;
+        if path & others != EMPTY {
+            return false;
         }
+        // The king may not castle out of, through or into check. The rook is
+        // taken off the board, since it may be shielding the destination.
+        squares(between(king, king_to) | 1 << king | 1 << king_to)
+            .all(|sq| self.bitboards.attackers_to(sq, !color, others) == EMPTY)
+    }
+
+    /// Can a given player castle kingside?
+    pub fn can_kingside_castle(&self, color: Color) -> bool {
+        self.can_castle(color, true)
     }
 
     /// Can a given player castle queenside?
     pub fn can_queenside_castle(&self, color: Color) -> bool {
-        match color {
-            WHITE => {
-                self.has_no_piece(Position::new(0, 1))
-                    && self.has_no_piece(Position::new(0, 2))
-                    && self.has_no_piece(Position::new(0, 3))
-                    && self.get_piece(Position::new(0, 0))
-                        == Some(Piece::Rook(color, Position::new(0, 0)))
-                    && self.white_castling_rights.can_queenside_castle()
-                    && !self.is_in_check(color)
-                    && !self.is_threatened(Position::queen_pos(color), color)
-            }
-            BLACK => {
-                self.has_no_piece(Position::new(7, 1))
-                    && self.has_no_piece(Position::new(7, 2))
-                    && self.has_no_piece(Position::new(7, 3))
-                    && self.get_piece(Position::new(7, 0))
-                        == Some(Piece::Rook(color, Position::new(7, 0)))
-                    && self.black_castling_rights.can_queenside_castle()
-                    && !self.is_in_check(color)
-                    && !self.is_threatened(Position::queen_pos(color), color)
-            }
-        }
+        self.can_castle(color, false)
     }
 
     pub fn get_castling_rights(&self, color: Color) -> CastlingRights {
@@ -2097,33 +2665,19 @@ impl Board {
 
     /// Does the respective player have sufficient material?
     pub fn has_sufficient_material(&self, color: Color) -> bool {
-        let mut pieces = vec![];
-        for square in &self.squares {
-            if let Some(piece) = square.get_piece() {
-                if piece.get_color() == color {
-                    pieces.push(piece);
-                }
-            }
-        }
+        let own = self.bitboards.color(color);
+        let count = |kind| (own & self.bitboards.kind(kind)).count_ones();
+        let (kings, knights, bishops) = (count(KING), count(KNIGHT), count(BISHOP));
 
-        pieces.sort();
-
-        if pieces.is_empty()
-            || pieces.len() == 1 && pieces[0].is_king()
-            || pieces.len() == 2 && pieces[0].is_king() && pieces[1].is_knight()
-            || pieces.len() == 2 && pieces[0].is_king() && pieces[1].is_bishop()
-            || pieces.len() == 3
-                && pieces[0].is_king()
-                && pieces[1].is_knight()
-                && pieces[2].is_knight()
-        {
-            false
-        } else {
-            !(pieces.len() == 3
-                && pieces[0].is_king()
-                && pieces[1].is_bishop()
-                && pieces[2].is_bishop())
-        }
+        // A lone king, or a king with one minor piece or with two knights
+        // or two bishops, cannot force mate.
+        let insufficient = own == EMPTY
+            || kings == 1
+                && own.count_ones() == 1 + knights + bishops
+                && (knights + bishops <= 1
+                    || knights == 2 && bishops == 0
+                    || knights == 0 && bishops == 2);
+        !insufficient
     }
 
     /// Does the respective player have insufficient material?
@@ -2152,33 +2706,138 @@ impl Board {
         self
     }
 
-    fn apply_move(&self, m: Move) -> Self {
+    /// Play `m` in place, like [`apply_eval_move`](Self::apply_eval_move)
+    /// but without copying the board, which is what makes it cheap enough
+    /// for every node of the search. Passing the returned record to
+    /// [`unmake_move`](Self::unmake_move) takes the move back.
+    ///
+    /// [`Move::Resign`] does not move any pieces here and only passes the
+    /// turn; it is never one of the legal moves the search plays.
+    pub fn make_move(&mut self, m: Move) -> MoveUndo {
+        let mut undo = MoveUndo {
+            m,
+            moved: None,
+            captured: None,
+            castling: None,
+            en_passant: self.en_passant,
+            white_castling_rights: self.white_castling_rights,
+            black_castling_rights: self.black_castling_rights,
+            key: BLACK_TO_MOVE_ZOBRIST_KEY
+                ^ self.castling_zobrist_key()
+                ^ self.en_passant_zobrist_key(),
+        };
         match m {
-            Move::KingSideCastle => {
-                if let Some(king_pos) = self.get_king_pos(self.turn) {
-                    let rook_pos = match self.turn {
-                        WHITE => Position::new(0, 7),
-                        BLACK => Position::new(7, 7),
-                    };
-                    self.move_piece(king_pos, rook_pos.next_left(), None)
-                        .move_piece(rook_pos, king_pos.next_right(), None)
-                } else {
-                    *self
+            Move::KingSideCastle | Move::QueenSideCastle => {
+                let color = self.turn;
+                if let Some(castling) = self.castling_squares(color, m == Move::KingSideCastle) {
+                    let (king, rook, king_to, rook_to) = castling;
+                    self.en_passant = None;
+                    self.set_square(king, EMPTY_SQUARE);
+                    self.set_square(rook, EMPTY_SQUARE);
+                    self.add_piece(Piece::King(color, king_to));
+                    self.add_piece(Piece::Rook(color, rook_to));
+                    self.castling_rights_mut(color).disable_all();
+                    undo.castling = Some(castling);
+                    undo.key ^= piece_zobrist_key(Piece::King(color, king))
+                        ^ piece_zobrist_key(Piece::Rook(color, rook))
+                        ^ piece_zobrist_key(Piece::King(color, king_to))
+                        ^ piece_zobrist_key(Piece::Rook(color, rook_to));
                 }
             }
-            Move::QueenSideCastle => {
-                if let Some(king_pos) = self.get_king_pos(self.turn) {
-                    let rook_pos = match self.turn {
-                        WHITE => Position::new(0, 0),
-                        BLACK => Position::new(7, 0),
-                    };
-                    self.move_piece(king_pos, king_pos.next_left().next_left(), None)
-                        .move_piece(rook_pos, king_pos.next_left(), None)
+            Move::Piece(from, to) | Move::Promotion(from, to, _) => {
+                self.en_passant = None;
+                let piece = if from.is_off_board() || to.is_off_board() {
+                    None
                 } else {
-                    *self
+                    self.get_piece(from)
+                };
+                if let Some(piece) = piece {
+                    let promotion = match m {
+                        Move::Promotion(_, _, promotion) => Some(promotion),
+                        _ => None,
+                    };
+                    let moved = promote(piece, to, promotion).move_to(to);
+                    undo.moved = Some(piece);
+                    undo.captured = self.get_piece(to);
+                    self.set_square(from, EMPTY_SQUARE);
+                    self.add_piece(moved);
+
+                    if let (Some(en_passant), Piece::Pawn(color, _)) = (undo.en_passant, piece) {
+                        if (en_passant == from.pawn_up(color).next_left()
+                            || en_passant == from.pawn_up(color).next_right())
+                            && en_passant == to
+                        {
+                            let captured = en_passant.pawn_back(color);
+                            undo.captured = self.get_piece(captured);
+                            self.set_square(captured, EMPTY_SQUARE);
+                        }
+                    }
+                    if piece.is_starting_pawn() && (from.get_row() - to.get_row()).abs() == 2 {
+                        self.en_passant = Some(to.pawn_back(piece.get_color()))
+                    }
+
+                    if piece.is_king() {
+                        self.castling_rights_mut(piece.get_color()).disable_all();
+                    }
+                    for color in [WHITE, BLACK] {
+                        let castling_rights = self.castling_rights_mut(color);
+                        castling_rights.disable_rook_at(from, color);
+                        castling_rights.disable_rook_at(to, color);
+                    }
+
+                    undo.key ^= piece_zobrist_key(piece) ^ piece_zobrist_key(moved);
+                    if let Some(captured) = undo.captured {
+                        undo.key ^= piece_zobrist_key(captured);
+                    }
                 }
             }
+            Move::Resign => {}
+        }
+        self.turn = !self.turn;
+        undo.key ^= self.castling_zobrist_key() ^ self.en_passant_zobrist_key();
+        undo
+    }
+
+    /// Take back the move [`make_move`](Self::make_move) played, restoring
+    /// the board exactly as it was before.
+    pub fn unmake_move(&mut self, undo: MoveUndo) {
+        self.turn = !self.turn;
+        if let Some((king, rook, king_to, rook_to)) = undo.castling {
+            self.set_square(king_to, EMPTY_SQUARE);
+            self.set_square(rook_to, EMPTY_SQUARE);
+            self.add_piece(Piece::King(self.turn, king));
+            self.add_piece(Piece::Rook(self.turn, rook));
+        }
+        if let (Some(moved), Move::Piece(_, to) | Move::Promotion(_, to, _)) = (undo.moved, undo.m)
+        {
+            self.set_square(to, EMPTY_SQUARE);
+            self.add_piece(moved);
+            if let Some(captured) = undo.captured {
+                self.add_piece(captured);
+            }
+        }
+        self.en_passant = undo.en_passant;
+        self.white_castling_rights = undo.white_castling_rights;
+        self.black_castling_rights = undo.black_castling_rights;
+    }
 
+    fn apply_move(&self, m: Move) -> Self {
+        match m {
+            Move::KingSideCastle | Move::QueenSideCastle => {
+                match self.castling_squares(self.turn, m == Move::KingSideCastle) {
+                    Some((king, rook, king_to, rook_to)) => {
+                        let mut result = *self;
+                        result.en_passant = None;
+                        result.set_square(king, EMPTY_SQUARE);
+                        result.set_square(rook, EMPTY_SQUARE);
+                        result.add_piece(Piece::King(self.turn, king_to));
+                        result.add_piece(Piece::Rook(self.turn, rook_to));
+                        result.castling_rights_mut(self.turn).disable_all();
+                        result
+                    }
+                    None => *self,
+                }
+            }
             Move::Piece(from, to) => {
                 let mut result = self.move_piece(from, to, None);
 
@@ -2189,9 +2848,7 @@ impl Board {
                         || en_passant == from.pawn_up(player_color).next_right())
                         && en_passant == to
                     {
-                        result.squares[((7 - en_passant.pawn_back(player_color).get_row()) * 8
-                            + en_passant.get_col())
-                            as usize] = EMPTY_SQUARE;
+                        result.set_square(en_passant.pawn_back(player_color), EMPTY_SQUARE);
                     }
                 }
 
@@ -2202,6 +2859,36 @@ impl Board {
         }
     }
 
+    /// Does playing `m` make every earlier position unreachable? This is the
+    /// case for captures (including en-passant) and pawn moves, which are
+    /// also exactly the moves that reset the fifty-move rule's halfmove clock.
+    pub fn is_irreversible_move(&self, m: Move) -> bool {
+        match m {
+            Move::Piece(from, _) | Move::Promotion(from, _, _) => {
+                matches!(self.get_piece(from), Some(Piece::Pawn(_, _))) || self.is_capture(m)
+            }
+            _ => false,
+        }
+    }
+
+    /// Play a move like [`Board::play_move`], additionally recording it in
+    /// `history`. Because `history` knows the earlier positions of the game,
+    /// this also reports draws by threefold repetition and by the fifty-move
+    /// rule. Checkmate and stalemate take precedence over both.
+    pub fn play_move_with_history(&self, m: Move, history: &mut PositionHistory) -> GameResult {
+        let result = self.play_move(m);
+        if let GameResult::Continuing(next_turn) = result {
+            history.push(self, m, &next_turn);
+            if history.is_threefold_repetition() {
+                return GameResult::ThreefoldRepetition;
+            }
+            if history.is_fifty_move_rule() {
+                return GameResult::FiftyMoveRule;
+            }
+        }
+        result
+    }
+
     /// Play a move and confirm it is legal.
     pub fn play_move(&self, m: Move) -> GameResult {
         let current_color = self.get_turn_color();
@@ -2287,6 +2974,9 @@ mod ai_tests {
             match board.play_move(mv) {
                 GameResult::Victory(_) => return, // success: checkmated the lone king
                 GameResult::Stalemate => panic!("endgame picker stalemated a won game"),
+                GameResult::ThreefoldRepetition | GameResult::FiftyMoveRule => {
+                    panic!("endgame picker drew a won game")
+                }
                 GameResult::Continuing(b) => board = b,
                 GameResult::IllegalMove(_) => panic!("illegal move in playout"),
             }
@@ -2336,16 +3026,17 @@ mod ai_tests {
 
     /// Regression for the user's actual game: after 1.e4 Nf6 2.Nc3 the AI
     /// (Black) played Nfxe4?? losing a knight for a pawn to Nxe4 — a *knight*
-    /// recapture, which the old pawn-only guard did not catch. The generalised
-    /// `move_blunders_material` must flag it.
+    /// recapture, which the old pawn-only guard did not catch. Static exchange
+    /// evaluation must flag it.
     #[test]
-    fn move_blunders_material_flags_knight_grabbed_by_knight() {
+    fn see_flags_knight_grabbed_by_knight() {
         // Position after 1.e4 Nf6 2.Nc3, Black to move.
         let board =
             parse_fen("rnbqkb1r/pppppppp/5n2/8/4P3/2N5/PPPP1PPP/R1BQKBNR b KQkq - 2 2").unwrap();
         let grab = Move::Piece(Position::pgn("f6").unwrap(), Position::pgn("e4").unwrap());
-        assert!(
-            board.move_blunders_material(grab),
+        assert_eq!(
+            board.see(grab),
+            -2,
             "Nf6xe4 must be detected as a material blunder (knight recapture)"
         );
     }
@@ -2353,12 +3044,12 @@ mod ai_tests {
     /// An even trade (knight takes an undefended knight, recapturable) is NOT a
     /// blunder — the filter must not block legitimate exchanges.
     #[test]
-    fn move_blunders_material_allows_even_knight_trade() {
+    fn see_allows_even_knight_trade() {
         // White Nf4 vs Black Ne6, both kings far away. Nfxe6 trades evenly.
         let board = parse_fen("4k3/8/4n3/8/5N2/8/8/4K3 w - - 0 1").unwrap();
         let trade = Move::Piece(Position::pgn("f4").unwrap(), Position::pgn("e6").unwrap());
         assert!(
-            !board.move_blunders_material(trade),
+            board.see(trade) >= 0,
             "an even knight trade must not be classified as a blunder"
         );
     }
@@ -2366,16 +3057,39 @@ mod ai_tests {
     /// A winning capture (rook takes an undefended queen, even if the rook is
     /// then recapturable) must not be rejected — it nets material.
     #[test]
-    fn move_blunders_material_allows_winning_capture() {
+    fn see_allows_winning_capture() {
         // White Ra1 takes Black Qa8 (undefended). Rook(5) for Queen(9) is a win.
         let board = parse_fen("q3k3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
         let win = Move::Piece(Position::pgn("a1").unwrap(), Position::pgn("a8").unwrap());
         assert!(
-            !board.move_blunders_material(win),
+            board.see(win) >= 0,
             "Rxa8 winning the queen must not be classified as a blunder"
         );
     }
 
+    /// The whole exchange is resolved, including pieces lined up behind the
+    /// capturing ones: Nxe5 wins a pawn but loses the knight once the rooks,
+    /// bishops and queens behind it have joined in.
+    #[test]
+    fn see_resolves_exchange_with_x_rays() {
+        let board = parse_fen("1k1r4/1pp4p/p7/4p3/8/P5P1/1PP4P/2K1R3 w - - 0 1").unwrap();
+        let rxe5 = Move::Piece(Position::pgn("e1").unwrap(), Position::pgn("e5").unwrap());
+        assert_eq!(board.see(rxe5), 1);
+
+        let board = parse_fen("1k1r3q/1ppn3p/p4b2/4p3/8/P2N2P1/1PP1R1BP/2K1Q3 w - - 0 1").unwrap();
+        let nxe5 = Move::Piece(Position::pgn("d3").unwrap(), Position::pgn("e5").unwrap());
+        assert_eq!(board.see(nxe5), -2);
+
+        // Black may also stop recapturing: taking the defended pawn with the
+        // queen only loses the queen if White recaptures, which it will.
+        let board = parse_fen("4k3/8/3q4/8/3P4/4P3/8/4K3 b - - 0 1").unwrap();
+        let qxd4 = Move::Piece(Position::pgn("d6").unwrap(), Position::pgn("d4").unwrap());
+        assert_eq!(board.see(qxd4), 1 - 9);
+        // A quiet move onto an attacked square hangs the piece.
+        let qc5 = Move::Piece(Position::pgn("d6").unwrap(), Position::pgn("c5").unwrap());
+        assert_eq!(board.see(qc5), -9);
+    }
+
     /// End-to-end: on the exact game position, the AI must never return
     /// Nf6xe4 — neither from a completed search nor from the gas-abort safe
     /// fallback. Run twice: once with a normal gas budget (search path) and
@@ -2398,23 +3112,46 @@ mod ai_tests {
         let depths: &[u8] = &[8, 6, 4];
 
         // (1) Normal budget: the search completes and must reject Nxe4.
-        let (mv, _, _) = board.get_next_move(depths, seed, Gas::from_tgas(300), flags);
+        let mv = board
+            .get_next_move(depths, seed, &GasLimit(Gas::from_tgas(300)), flags)
+            .best_move;
         assert_ne!(mv, bad, "AI returned the Nxe4 blunder on a full search");
 
         // (2) Zero budget: search aborts immediately, so best_move would be the
         // first ordered move (Nxe4, the only capture) — the safe fallback must
         // replace it with a non-blundering move.
-        let (mv2, _, _) = board.get_next_move(depths, seed, Gas::from_tgas(0), flags);
+        let mv2 = board
+            .get_next_move(depths, seed, &GasLimit(Gas::from_tgas(0)), flags)
+            .best_move;
         assert_ne!(
             mv2, bad,
             "AI returned the Nxe4 blunder on gas-abort (fallback failed)"
         );
         assert!(
-            !board.move_blunders_material(mv2),
+            board.see(mv2) >= 0,
             "gas-abort fallback returned a move that still blunders material"
         );
     }
 
+    /// Iterative deepening with a single-entry depth list searches the root
+    /// moves only, like the plain search, instead of slicing past the end of
+    /// the list.
+    #[test]
+    fn get_next_move_iterative_deepening_single_depth() {
+        let board = parse_fen("6k1/5ppp/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+        let limits = NodeLimit(u64::MAX);
+        let result = board.get_next_move(
+            &[255],
+            [0u8; 32],
+            &limits,
+            FLAG_QUIESCENCE | FLAG_ITERATIVE_DEEPENING,
+        );
+        let plain = board.get_next_move(&[255], [0u8; 32], &limits, FLAG_QUIESCENCE);
+        assert_eq!(result.best_move, plain.best_move);
+        assert_eq!(result.value, plain.value);
+        assert_eq!(result.pv, vec![result.best_move]);
+    }
+
     /// Verify that get_legal_moves_fast produces exactly the same move set as
     /// get_legal_moves across positions with pins, en passant, check, etc.
     #[test]
@@ -2452,4 +3189,155 @@ mod ai_tests {
             );
         }
     }
+
+    /// The bitboard generator must agree with the reference generator on
+    /// every position two plies into the perft suite.
+    #[test]
+    fn fast_legal_moves_match_slow_on_perft_suite() {
+        fn sorted(mut moves: Vec<Move>) -> Vec<Move> {
+            moves.sort_by(|a, b| format!("{:?}", a).cmp(&format!("{:?}", b)));
+            moves
+        }
+        for position in crate::PERFT_SUITE {
+            let root = parse_fen(position.fen).unwrap();
+            let mut boards = vec![root];
+            for m in root.get_legal_moves() {
+                let child = root.apply_eval_move(m);
+                boards.extend(child.get_legal_moves().map(|m| child.apply_eval_move(m)));
+                boards.push(child);
+            }
+            for board in boards {
+                assert_eq!(
+                    sorted(board.get_legal_moves().collect()),
+                    sorted(board.get_legal_moves_fast()),
+                    "Move mismatch in {} for board:\n{:?}",
+                    position.name,
+                    board
+                );
+            }
+        }
+    }
+
+    /// The incrementally updated key must always match a full recompute,
+    /// through captures, promotions, castling, en-passant and null moves.
+    #[test]
+    fn search_board_key_matches_full_hash() {
+        fn check(board: SearchBoard, depth: u8) {
+            assert_eq!(board.zobrist_key(), board.get_board().zobrist_key());
+            let null = board.change_turn();
+            assert_eq!(null.zobrist_key(), null.get_board().zobrist_key());
+            if depth == 0 {
+                return;
+            }
+            for m in crate::get_perft_moves(board.get_board()) {
+                check(board.apply_eval_move(m), depth - 1);
+            }
+        }
+        for position in crate::PERFT_SUITE {
+            check(SearchBoard::new(parse_fen(position.fen).unwrap()), 2);
+        }
+    }
+
+    /// Making a move in place must give the same board and key as copying it
+    /// with `apply_eval_move`, and unmaking it must restore the board exactly.
+    #[test]
+    fn make_unmake_matches_apply_eval_move() {
+        fn check(board: &mut SearchBoard, depth: u8) {
+            let before = *board;
+            for m in crate::get_perft_moves(board.get_board()) {
+                let expected = board.apply_eval_move(m);
+                let undo = board.make_move(m);
+                assert_eq!(*board, expected, "{:?} from\n{}", m, before.get_board());
+                if depth > 1 {
+                    check(board, depth - 1);
+                }
+                board.unmake_move(undo);
+                assert_eq!(*board, before, "{:?} not taken back", m);
+            }
+        }
+        for position in crate::PERFT_SUITE {
+            check(&mut SearchBoard::new(parse_fen(position.fen).unwrap()), 2);
+        }
+    }
+
+    /// Stored games are Borsh-serialized boards, so the bitboards must not
+    /// change the layout, and must be rebuilt when a board is read back.
+    #[test]
+    fn borsh_layout_excludes_bitboards() {
+        let board =
+            parse_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+                .unwrap();
+        let bytes = near_sdk::borsh::to_vec(&board).unwrap();
+        let layout = near_sdk::borsh::to_vec(&(
+            board.squares,
+            board.en_passant,
+            board.white_castling_rights,
+            board.black_castling_rights,
+            board.turn,
+        ))
+        .unwrap();
+        assert_eq!(bytes, layout);
+
+        let restored: Board = near_sdk::borsh::from_slice(&bytes).unwrap();
+        assert_eq!(restored, board);
+        assert_eq!(
+            restored.bitboards().occupied().count_ones(),
+            board.count_pieces()
+        );
+    }
+
+    #[test]
+    fn chess960_start_positions() {
+        assert_eq!(Board::chess960(518), Board::default());
+        for index in 0..960 {
+            let board = Board::chess960(index);
+            let rights = board.get_castling_rights(WHITE);
+            let king = board.get_king_pos(WHITE).unwrap();
+            assert!(rights.queenside_rook_col() < king.get_col());
+            assert!(king.get_col() < rights.kingside_rook_col());
+            // one bishop on a dark square (a1, c1, e1 or g1)
+            let bishops = board.bitboards().pieces(crate::bitboard::BISHOP, WHITE);
+            assert_eq!((bishops & 0x55).count_ones(), 1, "position {}", index);
+            assert_eq!(board.count_pieces(), 32);
+
+            // rook files survive a store and load
+            let bytes = near_sdk::borsh::to_vec(&board).unwrap();
+            let restored: Board = near_sdk::borsh::from_slice(&bytes).unwrap();
+            assert_eq!(restored, board);
+        }
+        // the standard position keeps the original layout
+        assert_eq!(
+            near_sdk::borsh::to_vec(&Board::chess960(518)).unwrap(),
+            near_sdk::borsh::to_vec(&Board::default()).unwrap()
+        );
+    }
+
+    #[test]
+    fn chess960_castling() {
+        // The king on g1 castles with the b1 rook and lands on c1.
+        let board = parse_fen("4k3/8/8/8/8/8/r7/1R4K1 w B - 0 1").unwrap();
+        let castled = match board.play_move(Move::QueenSideCastle) {
+            GameResult::Continuing(board) => board,
+            _ => panic!("castling is legal"),
+        };
+        assert_eq!(castled.get_piece(C1), Some(Piece::King(WHITE, C1)));
+        assert_eq!(castled.get_piece(D1), Some(Piece::Rook(WHITE, D1)));
+        assert!(castled.has_no_piece(B1) && castled.has_no_piece(G1));
+        assert!(!castled.get_castling_rights(WHITE).can_queenside_castle());
+
+        // The b1 rook shields c1 from the a1 rook only until it moves to d1.
+        let board = parse_fen("4k3/8/8/8/8/8/8/rR4K1 w B - 0 1").unwrap();
+        assert!(!board.can_queenside_castle(WHITE));
+
+        // Capturing a castling rook on its square removes the right.
+        let board = parse_fen("1r2k1r1/8/8/8/8/8/8/1R2K1R1 w GBgb - 0 1").unwrap();
+        let board = match board.play_move(Move::Piece(G1, G8)) {
+            GameResult::Continuing(board) => board,
+            _ => panic!("capture is legal"),
+        };
+        let black = board.get_castling_rights(BLACK);
+        assert!(!black.can_kingside_castle() && black.can_queenside_castle());
+        let white = board.get_castling_rights(WHITE);
+        assert!(!white.can_kingside_castle() && white.can_queenside_castle());
+    }
 }
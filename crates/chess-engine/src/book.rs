@@ -0,0 +1,678 @@
+//! The opening book: looking moves up, and building it from games.
+//!
+//! [`OPENING_BOOK`] maps the engine's own Zobrist keys to up to three moves,
+//! best first, each packed into a `u16` by [`encode_move`]. The committed
+//! table is generated by `scripts/generate_static_data.py` from Stockfish
+//! analysis; [`BookBuilder`] builds one from PGN collections instead,
+//! keeping games by rating and ply and weighting every move by how often it
+//! was played and how it scored. Since the builder hashes positions with
+//! [`Board::zobrist_key`], its keys always agree with the contract's
+//! lookups. Run it with the `chess-book` tool.
+//!
+//! Books can also be imported from and exported to the Polyglot `.bin`
+//! format, which hashes positions with its own table of 781 random numbers,
+//! [`PolyglotKeys::standard`]. The table is only compiled in natively, where
+//! the `chess-book` tool runs, and not into the contract.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::Write;
+
+use crate::bitboard::{square_index, squares, BISHOP, KING, KNIGHT, PAWN, QUEEN, ROOK};
+use crate::static_book::OPENING_BOOK;
+use crate::{Board, Move, Pgn, PgnResult, Piece, Position, BLACK, WHITE};
+
+/// Look up a position in the opening book. Returns a weighted-random move:
+/// 50% best, 33% second, 17% third (falls back if fewer moves available).
+pub fn lookup_opening(zobrist: u64, seed: u8) -> Option<Move> {
+    let idx = OPENING_BOOK
+        .binary_search_by_key(&zobrist, |&(k, _, _, _)| k)
+        .ok()?;
+    let (_, m1, m2, m3) = OPENING_BOOK[idx];
+    if m1 == 0 {
+        return None;
+    }
+    let r = seed % 100;
+    let chosen = if r < 50 || m2 == 0 {
+        m1
+    } else if r < 83 || m3 == 0 {
+        m2
+    } else {
+        m3
+    };
+    Some(decode_move(chosen))
+}
+
+pub fn decode_move(code: u16) -> Move {
+    let special = (code >> 12) & 0xF;
+    match special {
+        5 => Move::KingSideCastle,
+        6 => Move::QueenSideCastle,
+        _ => {
+            let fi = (code & 0x3F) as usize;
+            let ti = ((code >> 6) & 0x3F) as usize;
+            let f = Position::new((fi / 8) as i32, (fi % 8) as i32);
+            let t = Position::new((ti / 8) as i32, (ti % 8) as i32);
+            match special {
+                1 => Move::Promotion(f, t, Piece::Knight(crate::Color::White, t)),
+                2 => Move::Promotion(f, t, Piece::Bishop(crate::Color::White, t)),
+                3 => Move::Promotion(f, t, Piece::Rook(crate::Color::White, t)),
+                4 => Move::Promotion(f, t, Piece::Queen(crate::Color::White, t)),
+                _ => Move::Piece(f, t),
+            }
+        }
+    }
+}
+
+/// The inverse of [`decode_move`]: the origin in bits 0-5, the target in
+/// bits 6-11 and promotions (1-4 for knight to queen) or castling (5
+/// kingside, 6 queenside) in bits 12-15. Resigning encodes as 0, which the
+/// book uses for "no move".
+pub fn encode_move(m: Move) -> u16 {
+    let squares =
+        |from: Position, to: Position| square_index(from) as u16 | (square_index(to) as u16) << 6;
+    match m {
+        Move::KingSideCastle => 5 << 12,
+        Move::QueenSideCastle => 6 << 12,
+        Move::Piece(from, to) => squares(from, to),
+        Move::Promotion(from, to, piece) => {
+            let promotion = match piece {
+                Piece::Knight(..) => 1,
+                Piece::Bishop(..) => 2,
+                Piece::Rook(..) => 3,
+                _ => 4,
+            };
+            squares(from, to) | promotion << 12
+        }
+        Move::Resign => 0,
+    }
+}
+
+/// A book move with how often it was played and how well.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BookMove {
+    pub mv: Move,
+    /// Games in which the move was played; 0 for imported moves.
+    pub games: u32,
+    /// Two points per win and one per draw (or unknown result) for the side
+    /// that played the move, as in Polyglot books.
+    pub weight: u32,
+}
+
+#[derive(Clone, Debug)]
+struct BookPosition {
+    board: Board,
+    moves: Vec<BookMove>,
+}
+
+/// An opening book keyed by [`Board::zobrist_key`].
+#[derive(Clone, Debug, Default)]
+pub struct Book {
+    positions: BTreeMap<u64, BookPosition>,
+}
+
+impl Book {
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// The moves of a position, highest weight first.
+    pub fn moves(&self, board: &Board) -> &[BookMove] {
+        self.positions
+            .get(&board.zobrist_key())
+            .map_or(&[][..], |position| &position.moves)
+    }
+
+    /// Add `weight` for `mv` in `board`.
+    fn add(&mut self, board: &Board, mv: Move, games: u32, weight: u32) {
+        let position = self
+            .positions
+            .entry(board.zobrist_key())
+            .or_insert_with(|| BookPosition {
+                board: *board,
+                moves: Vec::new(),
+            });
+        match position.moves.iter_mut().find(|m| m.mv == mv) {
+            Some(m) => {
+                m.games += games;
+                m.weight += weight;
+            }
+            None => position.moves.push(BookMove { mv, games, weight }),
+        }
+    }
+
+    /// Combine the moves of another book into this one.
+    pub fn merge(&mut self, other: &Book) {
+        for position in other.positions.values() {
+            for m in &position.moves {
+                self.add(&position.board, m.mv, m.games, m.weight);
+            }
+        }
+        self.sort();
+    }
+
+    fn sort(&mut self) {
+        for position in self.positions.values_mut() {
+            position
+                .moves
+                .sort_by(|a, b| b.weight.cmp(&a.weight).then(b.games.cmp(&a.games)));
+        }
+    }
+
+    /// The rows of [`OPENING_BOOK`]: every position with a move of nonzero
+    /// weight and its three heaviest moves, sorted by key.
+    pub fn opening_book_rows(&self) -> Vec<(u64, u16, u16, u16)> {
+        self.positions
+            .iter()
+            .filter_map(|(&key, position)| {
+                let mut codes = position
+                    .moves
+                    .iter()
+                    .filter(|m| m.weight > 0)
+                    .map(|m| encode_move(m.mv));
+                let first = codes.next()?;
+                Some((
+                    key,
+                    first,
+                    codes.next().unwrap_or(0),
+                    codes.next().unwrap_or(0),
+                ))
+            })
+            .collect()
+    }
+
+    /// The whole `static_book.rs` source file for this book.
+    pub fn to_static_book(&self) -> String {
+        let mut out = String::from(
+            "// Auto-generated by the `chess-book` tool from PGN games.\n\
+             // DO NOT EDIT MANUALLY — rebuild it with `chess-book`.\n\
+             pub use crate::book::{decode_move, lookup_opening};\n\
+             \n\
+             pub const OPENING_BOOK: &[(u64, u16, u16, u16)] = &[\n",
+        );
+        for (key, m1, m2, m3) in self.opening_book_rows() {
+            writeln!(out, "    ({}u64, {}u16, {}u16, {}u16),", key, m1, m2, m3).unwrap();
+        }
+        out.push_str("];\n");
+        out
+    }
+
+    /// The book as a Polyglot `.bin` file: 16-byte big-endian entries of
+    /// key, move, weight and learn value, sorted by key. Weights above
+    /// `u16::MAX` are scaled down per position.
+    pub fn to_polyglot(&self, keys: &PolyglotKeys) -> Vec<u8> {
+        let mut entries = Vec::new();
+        for position in self.positions.values() {
+            let key = keys.key(&position.board);
+            let max = position.moves.iter().map(|m| m.weight).max().unwrap_or(0);
+            for m in &position.moves {
+                let weight = if max > u16::MAX as u32 {
+                    (m.weight as u64 * u16::MAX as u64 / max as u64) as u16
+                } else {
+                    m.weight as u16
+                };
+                if weight > 0 {
+                    let mv = polyglot_move(&position.board, m.mv);
+                    entries.push((key, u16::MAX - weight, mv));
+                }
+            }
+        }
+        entries.sort();
+        let mut bytes = Vec::with_capacity(entries.len() * 16);
+        for (key, inverse_weight, mv) in entries {
+            bytes.extend_from_slice(&key.to_be_bytes());
+            bytes.extend_from_slice(&mv.to_be_bytes());
+            bytes.extend_from_slice(&(u16::MAX - inverse_weight).to_be_bytes());
+            bytes.extend_from_slice(&0u32.to_be_bytes());
+        }
+        bytes
+    }
+
+    /// Read a Polyglot `.bin` book. Polyglot keys cannot be turned back
+    /// into positions, so the book is walked from the starting position,
+    /// following its moves up to `max_ply` plies deep.
+    pub fn from_polyglot(
+        bytes: &[u8],
+        keys: &PolyglotKeys,
+        max_ply: usize,
+    ) -> Result<Self, String> {
+        if !bytes.len().is_multiple_of(16) {
+            return Err(format!(
+                "a Polyglot book is a multiple of 16 bytes long, got {}",
+                bytes.len()
+            ));
+        }
+        let entries: Vec<(u64, u16, u16)> = bytes
+            .chunks_exact(16)
+            .map(|entry| {
+                let key = u64::from_be_bytes(entry[0..8].try_into().unwrap());
+                let mv = u16::from_be_bytes(entry[8..10].try_into().unwrap());
+                let weight = u16::from_be_bytes(entry[10..12].try_into().unwrap());
+                (key, mv, weight)
+            })
+            .collect();
+        if entries.windows(2).any(|pair| pair[0].0 > pair[1].0) {
+            return Err("Polyglot book entries are not sorted by key".to_string());
+        }
+
+        let mut book = Book::default();
+        // breadth first, so every position is expanded at its lowest ply
+        let mut queue = VecDeque::from([(Board::default(), 0)]);
+        while let Some((board, ply)) = queue.pop_front() {
+            if ply >= max_ply || book.positions.contains_key(&board.zobrist_key()) {
+                continue;
+            }
+            let key = keys.key(&board);
+            let first = entries.partition_point(|&(k, _, _)| k < key);
+            for &(_, code, weight) in entries[first..].iter().take_while(|&&(k, _, _)| k == key) {
+                // entries for moves that are illegal here belong to another
+                // position with the same key
+                let Some(mv) = board
+                    .get_legal_moves()
+                    .find(|&m| polyglot_move(&board, m) == code)
+                else {
+                    continue;
+                };
+                book.add(&board, mv, 0, weight as u32);
+                queue.push_back((board.apply_eval_move(mv), ply + 1));
+            }
+        }
+        book.sort();
+        Ok(book)
+    }
+}
+
+/// A Polyglot move: target file and rank in bits 0-5, origin file and rank
+/// in bits 6-11, promotion (1-4 for knight to queen) in bits 12-14.
+/// Castling is written as the king taking its own rook.
+fn polyglot_move(board: &Board, m: Move) -> u16 {
+    let squares =
+        |from: Position, to: Position| (square_index(to) as u16) | (square_index(from) as u16) << 6;
+    let color = board.get_turn_color();
+    let castle = |col: i32| match board.get_king_pos(color) {
+        Some(king) => squares(king, Position::new(king.get_row(), col)),
+        None => 0,
+    };
+    match m {
+        Move::KingSideCastle => castle(board.get_castling_rights(color).kingside_rook_col()),
+        Move::QueenSideCastle => castle(board.get_castling_rights(color).queenside_rook_col()),
+        Move::Piece(from, to) => squares(from, to),
+        Move::Promotion(from, to, _) => squares(from, to) | (encode_move(m) >> 12) << 12,
+        Move::Resign => 0,
+    }
+}
+
+/// Polyglot's table of random numbers for hashing positions: 768 for the
+/// pieces, 4 for castling rights, 8 for the en passant file and 1 for White
+/// to move.
+pub struct PolyglotKeys(Box<[u64; 781]>);
+
+impl PolyglotKeys {
+    pub fn new(keys: [u64; 781]) -> Self {
+        Self(Box::new(keys))
+    }
+
+    /// The table every Polyglot book is hashed with.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn standard() -> Self {
+        Self::new(crate::polyglot_keys::POLYGLOT_RANDOM_64)
+    }
+
+    /// The Polyglot hash of a position.
+    pub fn key(&self, board: &Board) -> u64 {
+        let keys = &self.0;
+        let bitboards = board.bitboards();
+        let mut key = 0;
+        for (color, white) in [(WHITE, 1), (BLACK, 0)] {
+            // Polyglot counts pawn, knight, bishop, rook, queen, king
+            for (kind, polyglot_kind) in [
+                (PAWN, 0),
+                (KNIGHT, 1),
+                (BISHOP, 2),
+                (ROOK, 3),
+                (QUEEN, 4),
+                (KING, 5),
+            ] {
+                for sq in squares(bitboards.pieces(kind, color)) {
+                    key ^= keys[64 * (2 * polyglot_kind + white) + sq];
+                }
+            }
+        }
+        let castling = [
+            board.get_castling_rights(WHITE).can_kingside_castle(),
+            board.get_castling_rights(WHITE).can_queenside_castle(),
+            board.get_castling_rights(BLACK).can_kingside_castle(),
+            board.get_castling_rights(BLACK).can_queenside_castle(),
+        ];
+        for (i, _) in castling.iter().enumerate().filter(|(_, &right)| right) {
+            key ^= keys[768 + i];
+        }
+        // only hashed if a pawn can actually take en passant
+        let turn = board.get_turn_color();
+        if let Some(target) = board.get_en_passant() {
+            let from_row = target.get_row() + if turn == WHITE { -1 } else { 1 };
+            let capturer = [target.get_col() - 1, target.get_col() + 1]
+                .into_iter()
+                .filter(|col| (0..8).contains(col))
+                .any(|col| {
+                    let pos = Position::new(from_row, col);
+                    bitboards.pieces(PAWN, turn) & (1 << square_index(pos)) != 0
+                });
+            if capturer {
+                key ^= keys[772 + target.get_col() as usize];
+            }
+        }
+        if turn == WHITE {
+            key ^= keys[780];
+        }
+        key
+    }
+}
+
+/// Collects moves from PGN games into a [`Book`].
+#[derive(Clone, Debug)]
+pub struct BookBuilder {
+    min_rating: u32,
+    max_ply: usize,
+    min_games: u32,
+    games: usize,
+    book: Book,
+}
+
+impl Default for BookBuilder {
+    fn default() -> Self {
+        Self {
+            min_rating: 0,
+            max_ply: 20,
+            min_games: 1,
+            games: 0,
+            book: Book::default(),
+        }
+    }
+}
+
+impl BookBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only use games in which both players are rated at least `rating`
+    /// (`WhiteElo` and `BlackElo` tags). Unrated games are skipped.
+    pub fn min_rating(mut self, rating: u32) -> Self {
+        self.min_rating = rating;
+        self
+    }
+
+    /// Only use the first `plies` moves of every game. Defaults to 20.
+    pub fn max_ply(mut self, plies: usize) -> Self {
+        self.max_ply = plies;
+        self
+    }
+
+    /// Drop moves played in fewer than `games` games. Defaults to 1.
+    pub fn min_games(mut self, games: u32) -> Self {
+        self.min_games = games;
+        self
+    }
+
+    /// The number of games used so far.
+    pub fn games(&self) -> usize {
+        self.games
+    }
+
+    /// Add the moves of a game, unless it is filtered out or starts from a
+    /// custom position. Returns whether it was used.
+    pub fn add_game(&mut self, game: &Pgn) -> bool {
+        if game.get_tag("FEN").is_some() {
+            return false;
+        }
+        if self.min_rating > 0 {
+            let rating = |tag| {
+                game.get_tag(tag)
+                    .and_then(|elo| elo.trim().parse::<u32>().ok())
+            };
+            match (rating("WhiteElo"), rating("BlackElo")) {
+                (Some(white), Some(black)) if white.min(black) >= self.min_rating => {}
+                _ => return false,
+            }
+        }
+        let mut board = Board::default();
+        for m in game.get_moves().iter().take(self.max_ply) {
+            let weight = match (game.get_result(), board.get_turn_color()) {
+                (PgnResult::WhiteWins, WHITE) | (PgnResult::BlackWins, BLACK) => 2,
+                (PgnResult::WhiteWins, BLACK) | (PgnResult::BlackWins, WHITE) => 0,
+                (PgnResult::Draw, _) | (PgnResult::Unknown, _) => 1,
+            };
+            self.book.add(&board, m.mv, 1, weight);
+            board = board.apply_eval_move(m.mv);
+        }
+        self.games += 1;
+        true
+    }
+
+    /// Add every game of a PGN database. Returns how many were used.
+    pub fn add_pgn(&mut self, pgn: &str) -> Result<usize, String> {
+        let games = crate::parse_pgn_games(pgn)?;
+        Ok(games.iter().filter(|game| self.add_game(game)).count())
+    }
+
+    pub fn build(&self) -> Book {
+        let mut book = self.book.clone();
+        for position in book.positions.values_mut() {
+            position.moves.retain(|m| m.games >= self.min_games);
+        }
+        book.positions
+            .retain(|_, position| !position.moves.is_empty());
+        book.sort();
+        book
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_fen, parse_uci_move};
+
+    const GAMES: &str = r#"[White "A"]
+[Black "B"]
+[WhiteElo "2400"]
+[BlackElo "2300"]
+[Result "1-0"]
+
+1. e4 e5 2. Nf3 Nc6 3. Bb5 1-0
+
+[White "C"]
+[Black "D"]
+[WhiteElo "2500"]
+[BlackElo "2450"]
+[Result "1/2-1/2"]
+
+1. e4 c5 2. Nf3 d6 1/2-1/2
+
+[White "E"]
+[Black "F"]
+[WhiteElo "2500"]
+[BlackElo "2450"]
+[Result "0-1"]
+
+1. d4 d5 2. c4 e6 0-1
+
+[White "G"]
+[Black "H"]
+[WhiteElo "1500"]
+[BlackElo "2450"]
+[Result "1-0"]
+
+1. d4 Nf6 1-0
+"#;
+
+    fn mv(board: &Board, uci: &str) -> Move {
+        parse_uci_move(board, uci).unwrap()
+    }
+
+    #[test]
+    fn encodes_moves_like_the_static_book() {
+        let board = parse_fen("r3k3/1P6/8/8/8/8/8/R3K2R w KQq - 0 1").unwrap();
+        for m in board.get_legal_moves() {
+            let code = encode_move(m);
+            let decoded = decode_move(code);
+            match (m, decoded) {
+                (Move::Promotion(f, t, p), Move::Promotion(df, dt, dp)) => {
+                    assert_eq!((f, t, p.get_name()), (df, dt, dp.get_name()));
+                }
+                _ => assert_eq!(m, decoded),
+            }
+        }
+        // e2e4 and O-O as the Python generator writes them
+        assert_eq!(encode_move(mv(&Board::default(), "e2e4")), 12 | 28 << 6);
+        assert_eq!(encode_move(Move::KingSideCastle), 20480);
+    }
+
+    #[test]
+    fn builds_weighted_books_from_pgn() {
+        let mut builder = BookBuilder::new().min_rating(2200).max_ply(3);
+        assert_eq!(builder.add_pgn(GAMES), Ok(3));
+        let book = builder.build();
+
+        let start = Board::default();
+        let moves = book.moves(&start);
+        let e4 = mv(&start, "e2e4");
+        let d4 = mv(&start, "d2d4");
+        assert_eq!(
+            moves,
+            [
+                BookMove {
+                    mv: e4,
+                    games: 2,
+                    weight: 3
+                },
+                BookMove {
+                    mv: d4,
+                    games: 1,
+                    weight: 0
+                },
+            ]
+        );
+        // the third ply is the last one used
+        let after = |ucis: &[&str]| {
+            ucis.iter().fold(Board::default(), |board, uci| {
+                board.apply_eval_move(mv(&board, uci))
+            })
+        };
+        assert_eq!(book.moves(&after(&["e2e4", "e7e5"])).len(), 1);
+        assert!(book.moves(&after(&["e2e4", "e7e5", "g1f3"])).is_empty());
+        // the lost d4 has no weight and is left out of the table
+        let rows = book.opening_book_rows();
+        let start_row = rows
+            .iter()
+            .find(|row| row.0 == start.zobrist_key())
+            .unwrap();
+        assert_eq!(*start_row, (start.zobrist_key(), encode_move(e4), 0, 0));
+        assert!(rows.windows(2).all(|pair| pair[0].0 < pair[1].0));
+
+        let mut frequent = BookBuilder::new().min_games(2);
+        frequent.add_pgn(GAMES).unwrap();
+        assert_eq!(frequent.build().len(), 1);
+    }
+
+    /// The builder hashes positions like the generator of the committed
+    /// table, so its keys can be looked up in the contract.
+    #[test]
+    fn keys_agree_with_the_committed_book() {
+        let mut builder = BookBuilder::new().max_ply(2);
+        builder.add_pgn(GAMES).unwrap();
+        for (key, ..) in builder.build().opening_book_rows() {
+            assert!(
+                OPENING_BOOK.binary_search_by_key(&key, |row| row.0).is_ok(),
+                "{} is not in the opening book",
+                key
+            );
+        }
+    }
+
+    #[test]
+    fn writes_the_static_book() {
+        let mut builder = BookBuilder::new().max_ply(1);
+        builder.add_pgn(GAMES).unwrap();
+        let source = builder.build().to_static_book();
+        let key = Board::default().zobrist_key();
+        assert!(source.contains("pub use crate::book::{decode_move, lookup_opening};"));
+        let row = format!("    ({}u64, 1804u16, 1739u16, 0u16),\n];\n", key);
+        assert!(source.ends_with(&row), "{}", source);
+    }
+
+    #[test]
+    fn polyglot_round_trip() {
+        let keys = PolyglotKeys::standard();
+
+        let mut builder = BookBuilder::new();
+        builder
+            .add_pgn("1. e4 e5 2. Nf3 Nc6 3. Bc4 Nf6 4. O-O Bc5 5. d4 *")
+            .unwrap();
+        builder.add_pgn("1. e4 e5 2. Nf3 Nf6 1-0").unwrap();
+        let book = builder.build();
+        let bytes = book.to_polyglot(&keys);
+        // every move but the lost 2... Nf6
+        assert_eq!(bytes.len(), 16 * 9);
+
+        let imported = Book::from_polyglot(&bytes, &keys, 20).unwrap();
+        assert_eq!(imported.len(), book.len());
+        for position in book.positions.values() {
+            let weights: Vec<_> = position
+                .moves
+                .iter()
+                .filter(|m| m.weight > 0)
+                .map(|m| (m.mv, m.weight))
+                .collect();
+            let imported: Vec<_> = imported
+                .moves(&position.board)
+                .iter()
+                .map(|m| (m.mv, m.weight))
+                .collect();
+            assert_eq!(weights, imported);
+        }
+        assert!(Book::from_polyglot(&bytes[1..], &keys, 20).is_err());
+    }
+
+    /// The keys published with the Polyglot book format.
+    #[test]
+    fn polyglot_published_keys() {
+        let keys = PolyglotKeys::standard();
+        for (moves, expected) in [
+            ("", 0x463b96181691fc9c),
+            ("e2e4", 0x823c9b50fd114196),
+            ("e2e4 d7d5", 0x0756b94461c50fb0),
+            ("e2e4 d7d5 e4e5", 0x662fafb965db29d4),
+            ("e2e4 d7d5 e4e5 f7f5", 0x22a48b5a8e47ff78),
+            ("e2e4 d7d5 e4e5 f7f5 e1e2", 0x652a607ca3f242c1),
+            ("e2e4 d7d5 e4e5 f7f5 e1e2 e8f7", 0x00fdd303c946bdd9),
+            ("a2a4 b7b5 h2h4 b5b4 c2c4", 0x3c8123ea7b067637),
+            ("a2a4 b7b5 h2h4 b5b4 c2c4 b4c3 a1a3", 0x5c3f9b829b279560),
+        ] {
+            let mut board = Board::default();
+            for uci in moves.split_whitespace() {
+                board = board.apply_eval_move(mv(&board, uci));
+            }
+            assert_eq!(keys.key(&board), expected, "after {:?}", moves);
+        }
+    }
+
+    #[test]
+    fn polyglot_moves_and_en_passant() {
+        let board = parse_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+        // e1h1 and e1a1
+        assert_eq!(polyglot_move(&board, Move::KingSideCastle), 7 | 4 << 6);
+        assert_eq!(polyglot_move(&board, Move::QueenSideCastle), 4 << 6);
+
+        let keys = PolyglotKeys::new(core::array::from_fn(|i| 1 << (i % 64) ^ i as u64));
+        let with_ep = parse_fen("4k3/8/8/8/4P3/8/8/4K3 b - e3 0 1").unwrap();
+        let without_ep = parse_fen("4k3/8/8/8/4P3/8/8/4K3 b - - 0 1").unwrap();
+        assert_eq!(keys.key(&with_ep), keys.key(&without_ep));
+        let capturable = parse_fen("4k3/8/8/8/3pP3/8/8/4K3 b - e3 0 1").unwrap();
+        let not_capturable = parse_fen("4k3/8/8/8/3pP3/8/8/4K3 b - - 0 1").unwrap();
+        assert_ne!(keys.key(&capturable), keys.key(&not_capturable));
+    }
+}
@@ -0,0 +1,108 @@
+//! Builds the opening book from PGN game collections.
+//!
+//! Build with `cargo build --release -p chess-engine --features uci`, then
+//!
+//! ```text
+//! chess-book [--min-rating <elo>] [--max-ply <plies>] [--min-games <games>]
+//!            [--polyglot-in <book.bin>]... [--polyglot-out <book.bin>]
+//!            [--out <static_book.rs>] [<games.pgn>...]
+//! ```
+//!
+//! Games from all PGN files and moves from all imported Polyglot books are
+//! merged into one book, which is written as `static_book.rs` source (to
+//! stdout unless `--out` is given) and optionally as a Polyglot book.
+
+extern crate chess_engine;
+use chess_engine::book::{Book, BookBuilder, PolyglotKeys};
+use std::{fs, process::exit};
+
+const USAGE: &str = "usage: chess-book [--min-rating <elo>] [--max-ply <plies>] \
+                     [--min-games <games>] [--polyglot-in <book.bin>]... \
+                     [--polyglot-out <book.bin>] [--out <static_book.rs>] [<games.pgn>...]";
+
+#[derive(Default)]
+struct Args {
+    min_rating: u32,
+    max_ply: Option<usize>,
+    min_games: Option<u32>,
+    polyglot_in: Vec<String>,
+    polyglot_out: Option<String>,
+    out: Option<String>,
+    pgn_files: Vec<String>,
+}
+
+impl Args {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Self::default();
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or_else(|| format!("{} needs a value", arg));
+            match arg.as_str() {
+                "--min-rating" => parsed.min_rating = number(&value()?)?,
+                "--max-ply" => parsed.max_ply = Some(number(&value()?)?),
+                "--min-games" => parsed.min_games = Some(number(&value()?)?),
+                "--polyglot-in" => parsed.polyglot_in.push(value()?),
+                "--polyglot-out" => parsed.polyglot_out = Some(value()?),
+                "--out" => parsed.out = Some(value()?),
+                "-h" | "--help" => return Err(USAGE.to_string()),
+                flag if flag.starts_with("--") => return Err(format!("unknown option {}", flag)),
+                _ => parsed.pgn_files.push(arg),
+            }
+        }
+        if parsed.pgn_files.is_empty() && parsed.polyglot_in.is_empty() {
+            return Err(USAGE.to_string());
+        }
+        Ok(parsed)
+    }
+}
+
+fn number<T: std::str::FromStr>(value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid number {}", value))
+}
+
+fn run(args: Args) -> Result<(), String> {
+    let mut builder = BookBuilder::new().min_rating(args.min_rating);
+    if let Some(plies) = args.max_ply {
+        builder = builder.max_ply(plies);
+    }
+    if let Some(games) = args.min_games {
+        builder = builder.min_games(games);
+    }
+    for file in &args.pgn_files {
+        let pgn = fs::read_to_string(file).map_err(|e| format!("{}: {}", file, e))?;
+        let used = builder
+            .add_pgn(&pgn)
+            .map_err(|e| format!("{}: {}", file, e))?;
+        eprintln!("{}: {} games used", file, used);
+    }
+    let mut book = builder.build();
+
+    let keys = PolyglotKeys::standard();
+    for file in &args.polyglot_in {
+        let bytes = fs::read(file).map_err(|e| format!("{}: {}", file, e))?;
+        let imported = Book::from_polyglot(&bytes, &keys, args.max_ply.unwrap_or(20))
+            .map_err(|e| format!("{}: {}", file, e))?;
+        eprintln!("{}: {} positions imported", file, imported.len());
+        book.merge(&imported);
+    }
+    eprintln!("{} positions", book.len());
+
+    if let Some(file) = &args.polyglot_out {
+        fs::write(file, book.to_polyglot(&keys)).map_err(|e| format!("{}: {}", file, e))?;
+    }
+    let source = book.to_static_book();
+    match &args.out {
+        Some(file) => fs::write(file, source).map_err(|e| format!("{}: {}", file, e))?,
+        None => print!("{}", source),
+    }
+    Ok(())
+}
+
+fn main() {
+    let result = Args::parse(std::env::args().skip(1)).and_then(run);
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        exit(1);
+    }
+}
@@ -1,9 +1,7 @@
+use crate::bitbase::{self, Wdl};
 use crate::{Board, Color, Move, Position, BLACK, WHITE};
 
 enum EndgameType {
-    Krvk,
-    Kqvk,
-    Kpvk,
     Kbbvk,
     Kbnvk,
     /// Generic "attacker has extra material vs a lone defender king" case that
@@ -87,24 +85,6 @@ fn detect_endgame(board: &Board) -> Option<(EndgameType, Color)> {
     let white_only = wk && !wq && !wr && wb == 0 && wn == 0 && wp == 0;
     let black_only = bk && !bq && !br && bb == 0 && bn == 0 && bp == 0;
 
-    if wr && white_only && bk && !bq && !br && bb == 0 && bn == 0 && bp == 0 {
-        return Some((EndgameType::Krvk, WHITE));
-    }
-    if br && black_only && wk && !wq && !wr && wb == 0 && wn == 0 && wp == 0 {
-        return Some((EndgameType::Krvk, BLACK));
-    }
-    if wq && white_only && bk && !bq && !br && bb == 0 && bn == 0 && bp == 0 {
-        return Some((EndgameType::Kqvk, WHITE));
-    }
-    if bq && black_only && wk && !wq && !wr && wb == 0 && wn == 0 && wp == 0 {
-        return Some((EndgameType::Kqvk, BLACK));
-    }
-    if wp == 1 && white_only && bk && !bq && !br && bb == 0 && bn == 0 && bp == 0 {
-        return Some((EndgameType::Kpvk, WHITE));
-    }
-    if bp == 1 && black_only && wk && !wq && !wr && wb == 0 && wn == 0 && wp == 0 {
-        return Some((EndgameType::Kpvk, BLACK));
-    }
     if wb >= 2 && white_only && bk && !bq && !br && bb == 0 && bn == 0 && bp == 0 {
         return Some((EndgameType::Kbbvk, WHITE));
     }
@@ -183,32 +163,6 @@ fn has_opposition(ak: Position, dk: Position) -> bool {
     false
 }
 
-/// Returns true if `king` is on a key square for `pawn` (attacker perspective).
-/// Key squares are the three squares two ranks ahead of the pawn (or one rank
-/// ahead once the pawn reaches the 5th rank).
-fn on_key_square(king: Position, pawn: Position, attacker: Color) -> bool {
-    let pr = pawn.get_row();
-    let pc = pawn.get_col();
-    let kr = king.get_row();
-    let kc = king.get_col();
-
-    let target_row = if attacker == WHITE {
-        if pr <= 3 {
-            pr + 2
-        } else {
-            pr + 1
-        }
-    } else {
-        if pr >= 4 {
-            pr - 2
-        } else {
-            pr - 1
-        }
-    };
-
-    kr == target_row && kc >= (pc - 1).max(0) && kc <= (pc + 1).min(7)
-}
-
 /// Find the position of the attacker's non-king piece (rook, queen, etc.).
 fn find_attacker_piece(board: &Board, attacker: Color, name: &str) -> Option<Position> {
     for row in 0..8 {
@@ -231,264 +185,6 @@ fn piece_is_safe(nb: &Board, pos: Position, attacker: Color) -> bool {
 
 // ── Endgame scorers ──
 
-fn krvk_move(board: &Board, attacker: Color, defender: Color) -> Option<Move> {
-    let dk = board.get_king_pos(defender)?;
-    let ak = board.get_king_pos(attacker)?;
-    let rook_sq = find_attacker_piece(board, attacker, "rook")?;
-
-    let mut best: Option<Move> = None;
-    let mut best_score = f64::NEG_INFINITY;
-
-    for mv in board.get_legal_moves() {
-        let nb = board.apply_eval_move(mv);
-        let ndk = nb.get_king_pos(defender).unwrap_or(dk);
-        let nak = nb.get_king_pos(attacker).unwrap_or(ak);
-        let nrook = find_attacker_piece(&nb, attacker, "rook").unwrap_or(rook_sq);
-
-        if nb.is_checkmate() {
-            return Some(mv);
-        }
-
-        let mut score = 0.0;
-
-        if nb.is_stalemate() || !nb.has_sufficient_material(attacker) {
-            score -= 1000.0;
-        }
-
-        // REWARD driving defender toward edge (was inverted before)
-        score += (3 - edge_dist(ndk)) as f64 * 12.0;
-
-        // REWARD bringing kings together for opposition / restriction
-        score -= king_move_dist(nak, ndk) as f64 * 3.0;
-
-        // REWARD opposition (attacker holds it after this move)
-        if has_opposition(nak, ndk) {
-            score += 20.0;
-        }
-
-        // REWARD checks, especially when kings are close (rook can force king back)
-        if nb.is_in_check(defender) {
-            score += 15.0;
-            if king_move_dist(nak, ndk) <= 2 {
-                score += 25.0;
-            }
-        }
-
-        // Don't hang the rook to the enemy king
-        if !piece_is_safe(&nb, nrook, attacker) {
-            score -= 200.0;
-        }
-
-        // Slightly prefer rook moves that restrict the enemy king's box
-        if let Move::Piece(from, _) = mv {
-            if let Some(p) = board.get_piece(from) {
-                if p.is_rook() {
-                    // Count how many files/ranks the rook cuts off
-                    let rr = nrook.get_row();
-                    let rc = nrook.get_col();
-                    let dk_r = ndk.get_row();
-                    let dk_c = ndk.get_col();
-                    // Rook on same rank as defender king but farther = restricts to a smaller box
-                    if rr == dk_r || rc == dk_c {
-                        score += 5.0;
-                    }
-                    // Rook restricts defender to one side
-                    if rc != dk_c {
-                        score += 2.0;
-                    }
-                }
-            }
-        }
-
-        if score > best_score {
-            best_score = score;
-            best = Some(mv);
-        }
-    }
-    best
-}
-
-fn kqvk_move(board: &Board, attacker: Color, defender: Color) -> Option<Move> {
-    let dk = board.get_king_pos(defender)?;
-    let ak = board.get_king_pos(attacker)?;
-    let queen_sq = find_attacker_piece(board, attacker, "queen")?;
-
-    let mut best: Option<Move> = None;
-    let mut best_score = f64::NEG_INFINITY;
-
-    for mv in board.get_legal_moves() {
-        let nb = board.apply_eval_move(mv);
-        let ndk = nb.get_king_pos(defender).unwrap_or(dk);
-        let nak = nb.get_king_pos(attacker).unwrap_or(ak);
-        let nqueen = find_attacker_piece(&nb, attacker, "queen").unwrap_or(queen_sq);
-
-        if nb.is_checkmate() {
-            return Some(mv);
-        }
-
-        let mut score = 0.0;
-
-        if nb.is_stalemate() || !nb.has_sufficient_material(attacker) {
-            score -= 1000.0;
-        }
-
-        // REWARD driving defender toward edge
-        score += (3 - edge_dist(ndk)) as f64 * 12.0;
-
-        // REWARD bringing kings together
-        score -= king_move_dist(nak, ndk) as f64 * 3.0;
-
-        // REWARD opposition
-        if has_opposition(nak, ndk) {
-            score += 15.0;
-        }
-
-        // REWARD checks
-        if nb.is_in_check(defender) {
-            score += 10.0;
-            if king_move_dist(nak, ndk) <= 2 {
-                score += 20.0;
-            }
-        }
-
-        // Don't hang the queen (stalemate risk is high with queen)
-        if !piece_is_safe(&nb, nqueen, attacker) {
-            score -= 200.0;
-        }
-
-        // EXTRA stalemate avoidance: queen covers many squares, so penalize
-        // positions where the defender has very few legal moves AND is not in check
-        if !nb.is_in_check(defender) {
-            let defender_moves = nb.get_legal_moves().count();
-            if defender_moves <= 1 {
-                score -= 50.0; // likely stalemate next move
-            }
-        }
-
-        if score > best_score {
-            best_score = score;
-            best = Some(mv);
-        }
-    }
-    best
-}
-
-fn kpvk_move(board: &Board, attacker: Color, defender: Color) -> Option<Move> {
-    let ak = board.get_king_pos(attacker)?;
-    let dk = board.get_king_pos(defender)?;
-
-    // Find the pawn
-    let mut pawn_sq = None;
-    for row in 0..8 {
-        for col in 0..8 {
-            let pos = Position::new(row, col);
-            if let Some(p) = board.get_piece(pos) {
-                if p.get_color() == attacker && p.is_pawn() {
-                    pawn_sq = Some(pos);
-                    break;
-                }
-            }
-        }
-    }
-    let pawn_sq = pawn_sq?;
-
-    let mut best: Option<Move> = None;
-    let mut best_score = f64::NEG_INFINITY;
-
-    let is_pawn_move = |mv: Move| -> bool {
-        if let Move::Piece(from, _) = mv {
-            board.get_piece(from).map_or(false, |p| p.is_pawn())
-        } else {
-            false
-        }
-    };
-
-    for mv in board.get_legal_moves() {
-        let nb = board.apply_eval_move(mv);
-        let ndk = nb.get_king_pos(defender).unwrap_or(dk);
-        let nak = nb.get_king_pos(attacker).unwrap_or(ak);
-
-        if nb.is_checkmate() {
-            return Some(mv);
-        }
-
-        let mut score = 0.0;
-
-        if nb.is_stalemate() || !nb.has_sufficient_material(attacker) {
-            score -= 1000.0;
-        }
-
-        // Find the pawn in the new position (it may have moved)
-        let mut npawn_sq: Option<Position> = Some(pawn_sq);
-        if is_pawn_move(mv) {
-            if let Move::Piece(_, to) = mv {
-                npawn_sq = Some(to);
-            }
-        }
-
-        // Pawn advancement bonus (higher = closer to promotion)
-        if let Some(np) = npawn_sq {
-            let advance = if attacker == WHITE {
-                np.get_row()
-            } else {
-                7 - np.get_row()
-            };
-            score += advance as f64 * 8.0;
-
-            // If pawn is one square from promotion, strongly prefer pushing
-            if advance == 6 {
-                score += 100.0;
-            }
-
-            // REWARD king on a key square
-            if on_key_square(nak, np, attacker) {
-                score += 30.0;
-            }
-
-            // Keep king close to the pawn
-            let kd = king_move_dist(nak, np);
-            score -= kd as f64 * 2.0;
-        }
-
-        // REWARD opposition — critical for KPvK technique
-        if has_opposition(nak, ndk) {
-            score += 25.0;
-        }
-
-        // PENALIZE pushing the pawn when the king is NOT well-placed.
-        // Pushing the pawn without key squares or opposition often draws.
-        if is_pawn_move(mv) {
-            let was_on_key = on_key_square(ak, pawn_sq, attacker);
-            let had_opp = has_opposition(ak, dk);
-            if !was_on_key && !had_opp {
-                score -= 15.0;
-            }
-        }
-
-        // REWARD driving the defender king away from the pawn's path
-        if let Some(np) = npawn_sq {
-            let pawn_file = np.get_col();
-            // Defender king should not be blockading the pawn
-            if ndk.get_col() == pawn_file {
-                let block_row = if attacker == WHITE {
-                    ndk.get_row() > np.get_row()
-                } else {
-                    ndk.get_row() < np.get_row()
-                };
-                if block_row {
-                    score -= 10.0; // defender is blockading
-                }
-            }
-        }
-
-        if score > best_score {
-            best_score = score;
-            best = Some(mv);
-        }
-    }
-    best
-}
-
 fn kbbvk_move(board: &Board, attacker: Color, defender: Color) -> Option<Move> {
     let dk = board.get_king_pos(defender)?;
     let ak = board.get_king_pos(attacker)?;
@@ -683,7 +379,14 @@ fn lone_king_move(board: &Board, attacker: Color, defender: Color) -> Option<Mov
     best
 }
 
+/// A move for an ending with little material left: the perfect move from
+/// the [bitbases](crate::bitbase) when they cover the position, otherwise a
+/// heuristic move for the side trying to mate a lone king.
 pub fn get_endgame_move(board: &Board) -> Option<Move> {
+    if let Some(mv) = bitbase::best_move(board) {
+        return Some(mv);
+    }
+
     let (egtype, attacker) = detect_endgame(board)?;
     let defender = if attacker == WHITE { BLACK } else { WHITE };
 
@@ -691,12 +394,15 @@ pub fn get_endgame_move(board: &Board) -> Option<Move> {
         return None;
     }
 
-    match egtype {
-        EndgameType::Krvk => krvk_move(board, attacker, defender),
-        EndgameType::Kqvk => kqvk_move(board, attacker, defender),
-        EndgameType::Kpvk => kpvk_move(board, attacker, defender),
+    let mv = match egtype {
         EndgameType::Kbbvk => kbbvk_move(board, attacker, defender),
         EndgameType::Kbnvk => kbnvk_move(board, attacker, defender),
         EndgameType::LoneKing => lone_king_move(board, attacker, defender),
+    }?;
+    // the heuristics must not give away a win the bitbases know about
+    let keeps_win = |mv| bitbase::probe_wdl(&board.apply_eval_move(mv)) == Some(Wdl::Loss);
+    if bitbase::probe_wdl(board) == Some(Wdl::Win) && !keeps_win(mv) {
+        return board.get_legal_moves().find(|&mv| keeps_win(mv));
     }
+    Some(mv)
 }
@@ -0,0 +1,305 @@
+//! Static evaluation behind [`Board::value_for`].
+//!
+//! Every term is scored twice, once for the midgame and once for the
+//! endgame, and the two are blended by the material left on the board: with
+//! all pieces on it the midgame score counts fully, with only kings and
+//! pawns left the endgame score does. This is what keeps the king tucked
+//! away behind its pawns in the midgame and brings it to the center once
+//! the queens are gone.
+//!
+//! Scores are in centipawns, like [`Piece::get_weighted_value`], and are
+//! from White's point of view.
+
+use crate::{
+    bitboard::{
+        bishop_attacks, queen_attacks, rook_attacks, square_position, squares, Bitboard, Bitboards,
+        BISHOP, EMPTY, FILE_A, FILE_H, KING, KNIGHT, KNIGHT_ATTACKS, PAWN, QUEEN, RANK_1, ROOK,
+    },
+    Board, Color, BLACK, WHITE,
+};
+
+/// The phase of the starting position: 1 per knight and bishop, 2 per rook
+/// and 4 per queen.
+pub(crate) const MAX_PHASE: i32 = 24;
+
+const DOUBLED_PAWN: (i32, i32) = (-10, -20);
+const ISOLATED_PAWN: (i32, i32) = (-10, -15);
+/// Passed pawn bonus by the number of ranks the pawn has advanced.
+const PASSED_PAWN_MIDGAME: [i32; 8] = [0, 5, 10, 15, 25, 40, 60, 0];
+const PASSED_PAWN_ENDGAME: [i32; 8] = [0, 10, 20, 35, 60, 90, 130, 0];
+const BISHOP_PAIR: (i32, i32) = (30, 50);
+/// Bonus per square a piece can move to that no enemy pawn attacks.
+const KNIGHT_MOBILITY: (i32, i32) = (4, 4);
+const BISHOP_MOBILITY: (i32, i32) = (4, 5);
+const ROOK_MOBILITY: (i32, i32) = (2, 4);
+const QUEEN_MOBILITY: (i32, i32) = (1, 2);
+/// King shelter, midgame only: own pawns one or two ranks in front of the
+/// king on its own and the neighbouring files, and penalties for those files
+/// having no own pawn (half-open) or no pawn at all (open).
+const SHELTER_PAWN_CLOSE: i32 = 15;
+const SHELTER_PAWN_FAR: i32 = 8;
+const HALF_OPEN_FILE_NEAR_KING: i32 = -25;
+const OPEN_FILE_NEAR_KING: i32 = -15;
+
+/// The tapered evaluation of `board` from White's point of view.
+pub(crate) fn evaluate(board: &Board) -> i32 {
+    let bitboards = board.bitboards();
+    let (mut midgame, mut endgame) = (0, 0);
+
+    for sq in squares(bitboards.occupied()) {
+        if let Some(piece) = board.get_piece(square_position(sq)) {
+            let sign = sign(piece.get_color());
+            midgame += sign * piece.get_weighted_value();
+            endgame += sign * piece.get_endgame_weighted_value();
+        }
+    }
+
+    for color in [WHITE, BLACK] {
+        let sign = sign(color);
+        let terms = [
+            pawn_structure(bitboards, color),
+            mobility(bitboards, color),
+            (king_shelter(bitboards, color), 0),
+            if bitboards.pieces(BISHOP, color).count_ones() >= 2 {
+                BISHOP_PAIR
+            } else {
+                (0, 0)
+            },
+        ];
+        for (term_midgame, term_endgame) in terms {
+            midgame += sign * term_midgame;
+            endgame += sign * term_endgame;
+        }
+    }
+
+    let phase = game_phase(bitboards);
+    (midgame * phase + endgame * (MAX_PHASE - phase)) / MAX_PHASE
+}
+
+/// How much of the midgame is left, from [`MAX_PHASE`] in the starting
+/// position down to 0 with only kings and pawns. Promotions can push the
+/// material count past the start, so it is capped.
+pub(crate) fn game_phase(bitboards: &Bitboards) -> i32 {
+    let count = |kind| bitboards.kind(kind).count_ones() as i32;
+    let phase = count(KNIGHT) + count(BISHOP) + 2 * count(ROOK) + 4 * count(QUEEN);
+    phase.min(MAX_PHASE)
+}
+
+fn sign(color: Color) -> i32 {
+    match color {
+        WHITE => 1,
+        BLACK => -1,
+    }
+}
+
+fn file(col: usize) -> Bitboard {
+    FILE_A << col
+}
+
+/// The files next to `col`.
+fn adjacent_files(col: usize) -> Bitboard {
+    let bb = file(col);
+    ((bb << 1) & !FILE_A) | ((bb >> 1) & !FILE_H)
+}
+
+/// All squares on ranks strictly in front of `row` from `color`'s side.
+fn ranks_ahead(row: usize, color: Color) -> Bitboard {
+    match color {
+        WHITE if row < 7 => !0 << (8 * (row + 1)),
+        BLACK => (1 << (8 * row)) - 1,
+        _ => EMPTY,
+    }
+}
+
+/// Doubled, isolated and passed pawns.
+fn pawn_structure(bitboards: &Bitboards, color: Color) -> (i32, i32) {
+    let own = bitboards.pieces(PAWN, color);
+    let enemy = bitboards.pieces(PAWN, !color);
+    let (mut midgame, mut endgame) = (0, 0);
+
+    for col in 0..8 {
+        let on_file = (own & file(col)).count_ones() as i32;
+        if on_file == 0 {
+            continue;
+        }
+        if on_file > 1 {
+            midgame += DOUBLED_PAWN.0 * (on_file - 1);
+            endgame += DOUBLED_PAWN.1 * (on_file - 1);
+        }
+        if own & adjacent_files(col) == EMPTY {
+            midgame += ISOLATED_PAWN.0 * on_file;
+            endgame += ISOLATED_PAWN.1 * on_file;
+        }
+    }
+
+    for sq in squares(own) {
+        let (row, col) = (sq / 8, sq % 8);
+        let front_span = ranks_ahead(row, color) & (file(col) | adjacent_files(col));
+        if enemy & front_span == EMPTY {
+            let advanced = match color {
+                WHITE => row,
+                BLACK => 7 - row,
+            };
+            midgame += PASSED_PAWN_MIDGAME[advanced];
+            endgame += PASSED_PAWN_ENDGAME[advanced];
+        }
+    }
+
+    (midgame, endgame)
+}
+
+/// Squares the knights, bishops, rooks and queens can move to, not counting
+/// squares covered by enemy pawns.
+fn mobility(bitboards: &Bitboards, color: Color) -> (i32, i32) {
+    let occupied = bitboards.occupied();
+    let enemy_pawns = bitboards.pieces(PAWN, !color);
+    // the same shifts as `Board::value_for`, from the enemy's side
+    let enemy_pawn_attacks = match color {
+        WHITE => ((enemy_pawns >> 9) & !FILE_H) | ((enemy_pawns >> 7) & !FILE_A),
+        BLACK => ((enemy_pawns << 7) & !FILE_H) | ((enemy_pawns << 9) & !FILE_A),
+    };
+    let available = !bitboards.color(color) & !enemy_pawn_attacks;
+
+    let (mut midgame, mut endgame) = (0, 0);
+    let mut add = |pieces: Bitboard, weight: (i32, i32), attacks: &dyn Fn(usize) -> Bitboard| {
+        for sq in squares(pieces) {
+            let moves = (attacks(sq) & available).count_ones() as i32;
+            midgame += weight.0 * moves;
+            endgame += weight.1 * moves;
+        }
+    };
+    add(bitboards.pieces(KNIGHT, color), KNIGHT_MOBILITY, &|sq| {
+        KNIGHT_ATTACKS[sq]
+    });
+    add(bitboards.pieces(BISHOP, color), BISHOP_MOBILITY, &|sq| {
+        bishop_attacks(sq, occupied)
+    });
+    add(bitboards.pieces(ROOK, color), ROOK_MOBILITY, &|sq| {
+        rook_attacks(sq, occupied)
+    });
+    add(bitboards.pieces(QUEEN, color), QUEEN_MOBILITY, &|sq| {
+        queen_attacks(sq, occupied)
+    });
+    (midgame, endgame)
+}
+
+/// Pawn shelter and open files around the king. Only scored in the
+/// midgame, where an exposed king can be attacked.
+fn king_shelter(bitboards: &Bitboards, color: Color) -> i32 {
+    let king = bitboards.pieces(KING, color);
+    if king == EMPTY {
+        return 0;
+    }
+    let sq = king.trailing_zeros() as usize;
+    let (row, col) = (sq / 8, sq % 8);
+    let own = bitboards.pieces(PAWN, color);
+    let all = bitboards.kind(PAWN);
+    let rank = |ahead: usize| -> Bitboard {
+        let row = match color {
+            WHITE => row + ahead,
+            BLACK => row.wrapping_sub(ahead),
+        };
+        if row < 8 {
+            RANK_1 << (8 * row)
+        } else {
+            EMPTY
+        }
+    };
+
+    let mut score = 0;
+    for col in col.saturating_sub(1)..=(col + 1).min(7) {
+        let file = file(col);
+        if own & file & rank(1) != EMPTY {
+            score += SHELTER_PAWN_CLOSE;
+        } else if own & file & rank(2) != EMPTY {
+            score += SHELTER_PAWN_FAR;
+        }
+        if own & file == EMPTY {
+            score += HALF_OPEN_FILE_NEAR_KING;
+            if all & file == EMPTY {
+                score += OPEN_FILE_NEAR_KING;
+            }
+        }
+    }
+    score
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_fen;
+
+    fn eval(fen: &str) -> i32 {
+        evaluate(&parse_fen(fen).unwrap())
+    }
+
+    #[test]
+    fn symmetric_positions_are_even() {
+        assert_eq!(evaluate(&Board::default()), 0);
+        let italian =
+            eval("r1bqk2r/pppp1ppp/2n2n2/2b1p3/2B1P3/2N2N2/PPPP1PPP/R1BQK2R w KQkq - 0 1");
+        assert_eq!(italian, 0);
+        // swapping the colors and flipping the board negates the score
+        let white_short =
+            eval("r1bqkb1r/pppp1ppp/2n2n2/4p3/4P3/2N2N2/PPPP1PPP/R1BQK2R w KQkq - 0 1");
+        let black_short =
+            eval("r1bqk2r/pppp1ppp/2n2n2/4p3/4P3/2N2N2/PPPP1PPP/R1BQKB1R b KQkq - 0 1");
+        assert!(white_short < 0);
+        assert_eq!(white_short + black_short, 0);
+    }
+
+    #[test]
+    fn phase() {
+        assert_eq!(game_phase(Board::default().bitboards()), MAX_PHASE);
+        let pawn_ending = parse_fen("4k3/pppp4/8/8/8/8/PPPP4/4K3 w - - 0 1").unwrap();
+        assert_eq!(game_phase(pawn_ending.bitboards()), 0);
+    }
+
+    #[test]
+    fn king_stays_home_in_the_midgame_and_centralizes_in_the_endgame() {
+        // with queens and rooks on the board the castled king is better
+        let castled = eval("r2q1rk1/ppp2ppp/8/8/8/8/PPP2PPP/R2Q1RK1 w - - 0 1");
+        let marched = eval("r2q1rk1/ppp2ppp/8/8/4K3/8/PPP2PPP/R2Q1R2 w - - 0 1");
+        assert!(castled > marched);
+        // with only pawns left the king belongs in the center
+        let home = eval("6k1/ppp2ppp/8/8/8/8/PPP2PPP/6K1 w - - 0 1");
+        let central = eval("6k1/ppp2ppp/8/8/4K3/8/PPP2PPP/8 w - - 0 1");
+        assert!(central > home);
+    }
+
+    #[test]
+    fn pawn_structure_terms() {
+        // doubled, isolated pawns are worse than connected ones
+        let connected = eval("4k3/8/8/8/8/8/3PP3/4K3 w - - 0 1");
+        let doubled = eval("4k3/8/8/8/8/4P3/4P3/4K3 w - - 0 1");
+        assert!(connected > doubled);
+        // a passed pawn beats one blocked by an enemy pawn in front of it,
+        // and the further it has advanced the better
+        let passed = eval("4k3/p7/8/4P3/8/8/8/4K3 w - - 0 1");
+        let opposed = eval("4k3/4p3/8/4P3/8/8/8/4K3 w - - 0 1");
+        assert!(passed - opposed > PASSED_PAWN_ENDGAME[4] / 2);
+        let far = eval("4k3/p7/4P3/8/8/8/8/4K3 w - - 0 1");
+        assert!(far > passed);
+    }
+
+    #[test]
+    fn bishop_pair_and_mobility() {
+        let pair = eval("4k3/8/8/8/8/8/8/2B1KB2 w - - 0 1");
+        let knights = eval("4k3/8/8/8/8/8/8/2N1KN2 w - - 0 1");
+        assert!(pair > knights);
+        // a rook on an open file moves more than one behind its own pawn
+        let open = eval("4k3/8/8/8/8/8/P7/1R2K3 w - - 0 1");
+        let blocked = eval("4k3/8/8/8/8/8/1P6/1R2K3 w - - 0 1");
+        assert!(open > blocked);
+    }
+
+    #[test]
+    fn open_files_near_the_king() {
+        let sheltered = eval("r5k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 0 1");
+        let exposed = eval("r5k1/5ppp/8/8/8/8/5P1P/R5K1 w - - 0 1");
+        // one pawn less either way, but losing the g-pawn also opens the king
+        let elsewhere = eval("r5k1/5ppp/8/8/8/8/P4P1P/R5K1 w - - 0 1");
+        assert!(sheltered > exposed);
+        assert!(elsewhere > exposed);
+    }
+}
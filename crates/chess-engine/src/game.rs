@@ -1,6 +1,6 @@
 use crate::{
-    util::{format_fen, parse_fen, parse_san_move},
-    Board, Color, GameResult,
+    util::{format_fen, parse_fen_strict, parse_san_move},
+    Board, Color, GameResult, PositionHistory,
 };
 
 pub enum GameAction {
@@ -40,33 +40,59 @@ pub enum GameOver {
     BlackCheckmates,
     BlackResigns,
     Stalemate,
+    ThreefoldRepetition,
+    FiftyMoveRule,
     DrawAccepted,
 }
 
 // wrapper around chess_engine::Board
 //
 // abstractions for two player games, like offering/accepting a draw.
+// history detects draws by repetition and the fifty-move rule.
 // status is Some when the game is over.
-#[derive(Default)]
 pub struct Game {
     pub board: Board,
+    pub history: PositionHistory,
     pub draw_offered: Option<Color>,
     pub status: Option<GameOver>,
 }
 
+impl Default for Game {
+    fn default() -> Self {
+        let board = Board::default();
+        Game {
+            history: PositionHistory::new(&board),
+            board,
+            draw_offered: None,
+            status: None,
+        }
+    }
+}
+
 impl Game {
     pub fn from_fen(
         fen: &str,
         draw_offered: Option<Color>,
         status: Option<GameOver>,
     ) -> Result<Self, GameError> {
-        let board = match parse_fen(fen) {
+        let board = match parse_fen_strict(fen) {
             Ok(board) => board,
             Err(_) => {
                 return Err(GameError::InvalidPosition);
             }
         };
+        // parse_fen only validates the first four fields
+        let halfmove_clock = match fen.split_ascii_whitespace().nth(4) {
+            None => 0,
+            Some(clock) => match clock.parse() {
+                Ok(clock) => clock,
+                Err(_) => {
+                    return Err(GameError::InvalidPosition);
+                }
+            },
+        };
         Ok(Game {
+            history: PositionHistory::with_halfmove_clock(&board, halfmove_clock),
             board,
             draw_offered,
             status,
@@ -123,7 +149,10 @@ impl Game {
             true => Some(self.get_turn_color()),
             false => None,
         };
-        self.status = match self.board.play_move(chess_move) {
+        self.status = match self
+            .board
+            .play_move_with_history(chess_move, &mut self.history)
+        {
             GameResult::Continuing(board) => {
                 self.board = board;
                 None
@@ -132,6 +161,8 @@ impl Game {
                 return Err(GameError::InvalidMove {});
             }
             GameResult::Stalemate => Some(GameOver::Stalemate),
+            GameResult::ThreefoldRepetition => Some(GameOver::ThreefoldRepetition),
+            GameResult::FiftyMoveRule => Some(GameOver::FiftyMoveRule),
             GameResult::Victory(color) => match color {
                 Color::Black => Some(GameOver::BlackCheckmates),
                 Color::White => Some(GameOver::WhiteCheckmates),
@@ -178,6 +209,24 @@ mod tests {
         assert_eq!(game.status, Some(GameOver::BlackCheckmates));
     }
 
+    #[test]
+    fn test_threefold_repetition() {
+        let mut game = Game::default();
+        let game_moves = vec!["Nf3", "Nf6", "Ng1", "Ng8", "Nf3", "Nf6", "Ng1", "Ng8"];
+        for game_move in game_moves {
+            game.make_move(&GameAction::from(game_move))
+                .expect(game_move);
+        }
+        assert_eq!(game.status, Some(GameOver::ThreefoldRepetition));
+    }
+
+    #[test]
+    fn test_fifty_move_rule_from_fen() {
+        let mut game = Game::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 99 80", None, None).unwrap();
+        game.make_move(&GameAction::from("Ra2")).expect("Ra2");
+        assert_eq!(game.status, Some(GameOver::FiftyMoveRule));
+    }
+
     #[test]
     fn test_promotion() {
         let mut game = Game::default();
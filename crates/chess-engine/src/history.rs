@@ -0,0 +1,161 @@
+use crate::{Board, Move};
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+
+/// Number of halfmoves without a capture or pawn move after which the game is
+/// drawn by the fifty-move rule.
+pub const FIFTY_MOVE_RULE_HALFMOVES: u16 = 100;
+
+/// The part of a game's state that a single `Board` cannot know about: the
+/// halfmove clock for the fifty-move rule and the Zobrist keys of all
+/// positions since the last irreversible move for threefold repetition.
+///
+/// Captures and pawn moves can never be undone, so every key recorded before
+/// one of them is dropped. This keeps the history bounded to at most
+/// [`FIFTY_MOVE_RULE_HALFMOVES`] + 1 entries, which is cheap enough to store
+/// next to the board in contract state.
+#[derive(Clone, Debug, Default, PartialEq, Eq, BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct PositionHistory {
+    halfmove_clock: u16,
+    keys: Vec<u64>,
+}
+
+impl PositionHistory {
+    /// Start a new history at `board`.
+    pub fn new(board: &Board) -> Self {
+        Self::with_halfmove_clock(board, 0)
+    }
+
+    /// Start a new history at `board`, e.g. from a FEN whose halfmove clock
+    /// is already running.
+    pub fn with_halfmove_clock(board: &Board, halfmove_clock: u16) -> Self {
+        Self {
+            halfmove_clock,
+            keys: vec![board.zobrist_key()],
+        }
+    }
+
+    /// Number of halfmoves since the last capture or pawn move.
+    #[inline]
+    pub fn get_halfmove_clock(&self) -> u16 {
+        self.halfmove_clock
+    }
+
+    /// Record that `m` was played on `board`, resulting in `next`. `board`
+    /// must be the position last recorded, whose key is updated incrementally
+    /// with what the move changed.
+    pub fn push(&mut self, board: &Board, m: Move, next: &Board) {
+        let key = match self.keys.last() {
+            Some(&key) => {
+                let mut after = *board;
+                key ^ after.make_move(m).get_zobrist_delta()
+            }
+            None => next.zobrist_key(),
+        };
+        if board.is_irreversible_move(m) {
+            self.halfmove_clock = 0;
+            self.keys.clear();
+        } else {
+            self.halfmove_clock = self.halfmove_clock.saturating_add(1);
+        }
+        self.keys.push(key);
+    }
+
+    /// Forget the positions recorded so far, so that none of them counts as
+    /// a repetition of a later one. The fifty-move rule keeps counting.
+    pub fn clear_repetitions(&mut self) {
+        self.keys.clear();
+    }
+
+    /// How often the current position has occurred, including now.
+    pub fn repetitions(&self) -> usize {
+        match self.keys.last() {
+            Some(current) => self.keys.iter().filter(|key| *key == current).count(),
+            None => 0,
+        }
+    }
+
+    /// Has the current position occurred at least three times?
+    #[inline]
+    pub fn is_threefold_repetition(&self) -> bool {
+        self.repetitions() >= 3
+    }
+
+    /// Have fifty moves by each side passed without a capture or pawn move?
+    #[inline]
+    pub fn is_fifty_move_rule(&self) -> bool {
+        self.halfmove_clock >= FIFTY_MOVE_RULE_HALFMOVES
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_fen, GameResult, Position};
+
+    fn mv(from: &str, to: &str) -> Move {
+        Move::Piece(Position::pgn(from).unwrap(), Position::pgn(to).unwrap())
+    }
+
+    #[test]
+    fn knight_shuffle_is_threefold_repetition() {
+        let mut board = Board::default();
+        let mut history = PositionHistory::new(&board);
+        let shuffle = [
+            mv("g1", "f3"),
+            mv("g8", "f6"),
+            mv("f3", "g1"),
+            mv("f6", "g8"),
+        ];
+        // The start position occurs for the second time after one shuffle and
+        // for the third time after the second one.
+        for (i, m) in shuffle.iter().chain(shuffle.iter()).enumerate() {
+            match board.play_move_with_history(*m, &mut history) {
+                GameResult::Continuing(next) => {
+                    assert!(i < 7, "repetition not detected");
+                    board = next;
+                }
+                GameResult::ThreefoldRepetition => {
+                    assert_eq!(i, 7);
+                    return;
+                }
+                other => panic!("unexpected result {:?}", other),
+            }
+        }
+        panic!("repetition not detected");
+    }
+
+    #[test]
+    fn pawn_move_resets_history() {
+        let board = Board::default();
+        let mut history = PositionHistory::new(&board);
+        let next = board.apply_eval_move(mv("g1", "f3"));
+        history.push(&board, mv("g1", "f3"), &next);
+        assert_eq!(history.get_halfmove_clock(), 1);
+
+        let after_pawn = next.apply_eval_move(mv("e7", "e5"));
+        history.push(&next, mv("e7", "e5"), &after_pawn);
+        assert_eq!(history.get_halfmove_clock(), 0);
+        assert_eq!(history.repetitions(), 1);
+    }
+
+    #[test]
+    fn fifty_move_rule_draws() {
+        let board = parse_fen("4k3/8/8/8/8/8/8/R3K3 w - - 99 80").unwrap();
+        let mut history = PositionHistory::with_halfmove_clock(&board, 99);
+        assert!(matches!(
+            board.play_move_with_history(mv("a1", "a2"), &mut history),
+            GameResult::FiftyMoveRule
+        ));
+    }
+
+    #[test]
+    fn checkmate_beats_fifty_move_rule() {
+        let board = parse_fen("6k1/5ppp/8/8/8/8/8/R3K3 w - - 99 80").unwrap();
+        let mut history = PositionHistory::with_halfmove_clock(&board, 99);
+        assert!(matches!(
+            board.play_move_with_history(mv("a1", "a8"), &mut history),
+            GameResult::Victory(crate::WHITE)
+        ));
+    }
+}
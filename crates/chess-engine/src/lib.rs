@@ -14,14 +14,30 @@ use near_sdk::{
 };
 use std::convert::TryFrom;
 
+mod ai;
+pub use ai::{
+    get_ai_move, get_ai_move_in_game, get_ai_move_with_stats, scale_depths, AI_FLAGS_EASY,
+    AI_FLAGS_HARD, AI_FLAGS_MEDIUM, AI_FLAGS_VERY_HARD,
+};
+
+pub mod bitbase;
+pub mod bitboard;
+pub mod book;
+pub use bitboard::Bitboards;
+
 mod board;
 pub use board::{
-    Board, BoardBuilder, FLAG_CHECK_EXTENSIONS, FLAG_ENDGAME_HEURISTICS, FLAG_ITERATIVE_DEEPENING,
-    FLAG_KILLER_HEURISTIC, FLAG_LATE_MOVE_REDUCTION, FLAG_MOVE_ORDERING, FLAG_NULL_MOVE_PRUNING,
-    FLAG_OPENING_BOOK, FLAG_QUIESCENCE,
+    Board, BoardBuilder, MoveOrdering, MoveUndo, SearchBoard, FLAG_ASPIRATION_WINDOWS,
+    FLAG_CHECK_EXTENSIONS, FLAG_ENDGAME_HEURISTICS, FLAG_HISTORY_HEURISTIC,
+    FLAG_ITERATIVE_DEEPENING, FLAG_KILLER_HEURISTIC, FLAG_LATE_MOVE_REDUCTION, FLAG_MOVE_ORDERING,
+    FLAG_NULL_MOVE_PRUNING, FLAG_OPENING_BOOK, FLAG_PRINCIPAL_VARIATION_SEARCH, FLAG_QUIESCENCE,
+    MATE,
 };
 
 pub mod endgame;
+mod eval;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod polyglot_keys;
 pub mod static_book;
 pub mod transposition_table;
 pub mod zobrist_keys;
@@ -30,6 +46,32 @@ pub use endgame::get_endgame_move;
 mod game;
 pub use game::{Game, GameAction, GameError, GameOver};
 
+mod history;
+pub use history::{PositionHistory, FIFTY_MOVE_RULE_HALFMOVES};
+
+pub mod mate;
+pub use mate::{find_missed_mate, find_shortest_mate, is_mate_in, solve_mate, MateSolution};
+
+pub mod pgn;
+pub use pgn::{parse_pgn, parse_pgn_games, Pgn, PgnMove, PgnResult};
+
+pub mod perft;
+pub use perft::{get_perft_moves, perft, perft_divide, PerftPosition, PERFT_SUITE};
+
+pub mod review;
+pub use review::{review_game, review_pgn, GameReview, MoveClass, MoveReview};
+
+mod search;
+#[cfg(not(target_arch = "wasm32"))]
+pub use search::TimeLimit;
+pub use search::{
+    is_mate_value, value_from_float, value_to_float, DepthLimit, GasLimit, NodeLimit, PvLine,
+    Score, SearchLimits, SearchResult, SearchStats,
+};
+
+pub mod selfplay;
+pub use selfplay::{AiSettings, MatchResult, MatchSettings, Sprt, SprtStatus};
+
 mod square;
 pub use square::{Square, EMPTY_SQUARE};
 
@@ -42,6 +84,9 @@ pub use position::*;
 mod util;
 pub use util::*;
 
+mod variant;
+pub use variant::{CheckCounter, Variant, THREE_CHECK_LIMIT};
+
 pub const WHITE: Color = Color::White;
 pub const BLACK: Color = Color::Black;
 
@@ -65,10 +110,17 @@ pub enum GameResult {
     /// 4. The player only has a king and a bishop
     /// 5. The player only has a king and two bishops
     ///
-    /// In a regular game of chess, threefold repetition also triggers
-    /// a stalemate, but this engine does not have builtin support for
-    /// threefold repetition detection yet.
+    /// Draws by threefold repetition or the fifty-move rule need the game's
+    /// history and are reported separately by
+    /// [`Board::play_move_with_history`].
     Stalemate,
+    /// The game is drawn because the same position, with the same player to
+    /// move, the same castling rights and the same en-passant square,
+    /// occurred for the third time.
+    ThreefoldRepetition,
+    /// The game is drawn because fifty moves by each player passed without
+    /// any capture or pawn move.
+    FiftyMoveRule,
     /// An illegal move was made. This can include many things,
     /// such as moving a piece through another piece, attempting
     /// to capture an allied piece, moving non-orthogonally or
@@ -208,7 +260,9 @@ impl TryFrom<String> for Move {
                     Self::Piece(Position::pgn(words[0])?, Position::pgn(words[2])?)
                 } else if words.len() == 4 && words[1] == "to" {
                     let piece = Piece::try_from(words[3])?;
-                    if piece.is_king() || piece.is_pawn() {
+                    // a king is only legal in Antichess, which the variant
+                    // checks when the move is played
+                    if piece.is_pawn() {
                         return Err(String::from("invalid promotion"));
                     }
                     Self::Promotion(Position::pgn(words[0])?, Position::pgn(words[2])?, piece)
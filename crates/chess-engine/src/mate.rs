@@ -0,0 +1,275 @@
+//! Exhaustive mate search.
+//!
+//! Unlike [`Board::minimax`], which samples a few moves per ply and scores
+//! the rest heuristically, the solver here tries every legal move of both
+//! sides, including under-promotions. So "no mate in N" is proven rather than
+//! guessed. This makes it useful for validating puzzles, for checking the
+//! endgame pickers and for finding forced mates the AI missed.
+//!
+//! Mate in N means the side to move mates with its N-th move at the latest,
+//! whatever the defence. The search follows the standard rules of chess,
+//! where only checkmate wins.
+
+use std::collections::HashMap;
+
+use crate::{get_perft_moves, Board, Move};
+
+/// A first move that forces mate.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MateSolution {
+    /// The key move.
+    pub mv: Move,
+    /// The number of moves of the mating side until mate, the key move
+    /// included, against the best defence.
+    pub mate_in: u8,
+    /// The main line, starting with [`MateSolution::mv`] and ending in mate.
+    /// The defender delays mate as long as possible and the attacker mates
+    /// as quickly as possible.
+    pub line: Vec<Move>,
+}
+
+/// Remembers which positions were already solved, keyed by
+/// [`Board::zobrist_key`], which includes the side to move.
+#[derive(Default)]
+struct MateSolver {
+    /// The fewest moves in which the side to move is known to mate.
+    mates: HashMap<u64, u8>,
+    /// The most moves in which the side to move is known not to mate.
+    refuted: HashMap<u64, u8>,
+}
+
+impl MateSolver {
+    /// Can the side to move mate within `n` moves?
+    fn mates_within(&mut self, board: &Board, n: u8) -> bool {
+        if n == 0 {
+            return false;
+        }
+        let key = board.zobrist_key();
+        if self.mates.get(&key).is_some_and(|&mate| mate <= n) {
+            return true;
+        }
+        if self.refuted.get(&key).is_some_and(|&refuted| refuted >= n) {
+            return false;
+        }
+
+        let defender = !board.get_turn_color();
+        let mut children: Vec<Board> = get_perft_moves(board)
+            .into_iter()
+            .map(|m| board.apply_eval_move(m))
+            .collect();
+        // the last move has to give check, and checks are the likeliest
+        // mates further up as well
+        if n == 1 {
+            children.retain(|child| child.is_in_check(defender));
+        } else {
+            children.sort_by_key(|child| !child.is_in_check(defender));
+        }
+        let mates = children.iter().any(|child| self.loses_within(child, n - 1));
+
+        if mates {
+            let mate = self.mates.entry(key).or_insert(n);
+            *mate = (*mate).min(n);
+        } else {
+            let refuted = self.refuted.entry(key).or_insert(n);
+            *refuted = (*refuted).max(n);
+        }
+        mates
+    }
+
+    /// Is the side to move mated, now or by the opponent's `n`-th move?
+    fn loses_within(&mut self, board: &Board, n: u8) -> bool {
+        let moves = get_perft_moves(board);
+        if moves.is_empty() {
+            return board.is_in_check(board.get_turn_color());
+        }
+        n > 0
+            && moves
+                .into_iter()
+                .all(|m| self.mates_within(&board.apply_eval_move(m), n))
+    }
+
+    /// The fewest moves in which the side to move mates, up to `n`.
+    fn shortest_mate(&mut self, board: &Board, n: u8) -> Option<u8> {
+        (1..=n).find(|&k| self.mates_within(board, k))
+    }
+
+    /// The main line after the key move `mv`, with which the side to move
+    /// mates in `n`.
+    fn line(&mut self, board: &Board, mv: Move, n: u8) -> Vec<Move> {
+        let mut line = vec![mv];
+        let mut board = board.apply_eval_move(mv);
+        let mut n = n;
+        while n > 1 {
+            // the defence that delays mate the longest
+            let longest = get_perft_moves(&board)
+                .into_iter()
+                .map(|reply| {
+                    let mate = self.shortest_mate(&board.apply_eval_move(reply), n - 1);
+                    (reply, mate)
+                })
+                .max_by_key(|&(_, mate)| mate);
+            let Some((reply, Some(mate))) = longest else {
+                break;
+            };
+            board = board.apply_eval_move(reply);
+            // and the quickest mate against it
+            let Some(m) = get_perft_moves(&board)
+                .into_iter()
+                .find(|&m| self.loses_within(&board.apply_eval_move(m), mate - 1))
+            else {
+                break;
+            };
+            line.extend([reply, m]);
+            board = board.apply_eval_move(m);
+            n = mate;
+        }
+        line
+    }
+
+    fn solution(&mut self, board: &Board, mv: Move, n: u8) -> Option<MateSolution> {
+        let child = board.apply_eval_move(mv);
+        let mate_in = (1..=n).find(|&k| self.loses_within(&child, k - 1))?;
+        let line = self.line(board, mv, mate_in);
+        Some(MateSolution { mv, mate_in, line })
+    }
+}
+
+/// Can the side to move force mate in at most `n` moves? `false` is a proof
+/// that it cannot, whatever it plays.
+pub fn is_mate_in(board: &Board, n: u8) -> bool {
+    MateSolver::default().mates_within(board, n)
+}
+
+/// Every move that forces mate in at most `n` moves, quickest mate first.
+/// A puzzle is sound if there is exactly one.
+pub fn solve_mate(board: &Board, n: u8) -> Vec<MateSolution> {
+    let mut solver = MateSolver::default();
+    let mut solutions: Vec<MateSolution> = get_perft_moves(board)
+        .into_iter()
+        .filter_map(|m| solver.solution(board, m, n))
+        .collect();
+    solutions.sort_by_key(|solution| solution.mate_in);
+    solutions
+}
+
+/// The quickest forced mate within `max_n` moves, if there is one.
+pub fn find_shortest_mate(board: &Board, max_n: u8) -> Option<MateSolution> {
+    let mut solver = MateSolver::default();
+    let n = solver.shortest_mate(board, max_n)?;
+    get_perft_moves(board)
+        .into_iter()
+        .find_map(|m| solver.solution(board, m, n))
+}
+
+/// If the side to move had a forced mate within `max_n` moves but `played`
+/// lets it slip, the quickest mate that was missed. Playing on into a
+/// slower forced mate does not count as missing it.
+pub fn find_missed_mate(board: &Board, played: Move, max_n: u8) -> Option<MateSolution> {
+    let mut solver = MateSolver::default();
+    let n = solver.shortest_mate(board, max_n)?;
+    if solver.loses_within(&board.apply_eval_move(played), max_n - 1) {
+        return None;
+    }
+    get_perft_moves(board)
+        .into_iter()
+        .find_map(|m| solver.solution(board, m, n))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{format_uci_move, get_endgame_move, parse_fen, parse_uci_move, GameResult, Piece};
+
+    fn uci(board: &Board, line: &[Move]) -> Vec<String> {
+        let mut board = *board;
+        line.iter()
+            .map(|&m| {
+                let s = format_uci_move(&board, m).unwrap();
+                board = board.apply_eval_move(m);
+                s
+            })
+            .collect()
+    }
+
+    fn is_mated_after(board: &Board, line: &[Move]) -> bool {
+        let board = line
+            .iter()
+            .fold(*board, |board, &m| board.apply_eval_move(m));
+        board.is_checkmate()
+    }
+
+    #[test]
+    fn test_mate_in_one() {
+        // back rank mate
+        let board = parse_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+        assert!(is_mate_in(&board, 1));
+        let solutions = solve_mate(&board, 1);
+        assert_eq!(solutions.len(), 1);
+        assert_eq!(uci(&board, &solutions[0].line), ["a1a8"]);
+        assert_eq!(solutions[0].mate_in, 1);
+
+        // Black is to move and has no mate at all
+        assert!(!is_mate_in(&board.change_turn(), 3));
+    }
+
+    #[test]
+    fn test_mate_in_two() {
+        // 1. Kc7 Ka7 2. Ra1#, while 1. Rb7 only stalemates
+        let board = parse_fen("k7/8/2K5/8/8/8/8/1R6 w - - 0 1").unwrap();
+        assert!(!is_mate_in(&board, 1));
+        assert!(is_mate_in(&board, 2));
+        let best = find_shortest_mate(&board, 3).unwrap();
+        assert_eq!(best.mate_in, 2);
+        assert_eq!(best.line.len(), 3);
+
+        let solutions = solve_mate(&board, 2);
+        let key = parse_uci_move(&board, "c6c7").unwrap();
+        let stalemate = parse_uci_move(&board, "b1b7").unwrap();
+        assert!(matches!(board.play_move(stalemate), GameResult::Stalemate));
+        assert!(solutions.iter().any(|s| s.mv == key));
+        assert!(solutions.iter().all(|s| s.mv != stalemate));
+        for solution in &solutions {
+            assert_eq!(solution.mate_in, 2);
+            assert!(is_mated_after(&board, &solution.line));
+        }
+        let kc7 = solutions.iter().find(|s| s.mv == key).unwrap();
+        assert_eq!(uci(&board, &kc7.line), ["c6c7", "a8a7", "b1a1"]);
+    }
+
+    #[test]
+    fn test_under_promotion_mate() {
+        // f8=N is mate, every other promotion is not
+        let board = parse_fen("6nb/5Ppk/7p/8/8/8/8/K7 w - - 0 1").unwrap();
+        let solutions = solve_mate(&board, 1);
+        assert_eq!(solutions.len(), 1);
+        assert!(matches!(
+            solutions[0].mv,
+            Move::Promotion(_, _, Piece::Knight(..))
+        ));
+    }
+
+    #[test]
+    fn test_missed_mate() {
+        let board = parse_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+        let mate = parse_uci_move(&board, "a1a8").unwrap();
+        let quiet = parse_uci_move(&board, "g1f2").unwrap();
+        assert_eq!(find_missed_mate(&board, mate, 2), None);
+        let missed = find_missed_mate(&board, quiet, 2).unwrap();
+        assert_eq!(missed.mv, mate);
+    }
+
+    /// The endgame picker takes mates in one when they are on the board.
+    #[test]
+    fn test_endgame_picker_takes_mate_in_one() {
+        let board = parse_fen("k7/8/1K6/8/8/8/8/7Q w - - 0 1").unwrap();
+        let picked = get_endgame_move(&board).unwrap();
+        let mates: Vec<_> = solve_mate(&board, 1).into_iter().map(|s| s.mv).collect();
+        assert!(!mates.is_empty());
+        assert!(
+            mates.contains(&picked),
+            "{:?} is not one of {:?}",
+            picked,
+            mates
+        );
+    }
+}
@@ -0,0 +1,198 @@
+//! Perft ("performance test") move-generation verification.
+//!
+//! Perft walks the full legal move tree to a fixed depth and counts the leaf
+//! nodes. The counts for well-known positions are published, so any
+//! difference points at a move-generation bug. [`perft_divide`] splits the
+//! count by root move, which narrows a mismatch down to the offending branch
+//! when compared against another engine's output.
+
+use crate::{Board, Move, Piece};
+
+/// A perft reference position together with its known node counts.
+pub struct PerftPosition {
+    pub name: &'static str,
+    pub fen: &'static str,
+    /// Node counts for depth 1, 2, 3, ...
+    pub nodes: &'static [u64],
+}
+
+/// The standard perft suite from the Chess Programming Wiki: the starting
+/// position, Kiwipete and the positions exercising castling, promotion,
+/// en-passant and discovered checks, followed by Chess960 positions whose
+/// castling rooks start away from the corners.
+pub const PERFT_SUITE: &[PerftPosition] = &[
+    PerftPosition {
+        name: "startpos",
+        fen: "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        nodes: &[20, 400, 8_902, 197_281, 4_865_609],
+    },
+    PerftPosition {
+        name: "kiwipete",
+        fen: "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        nodes: &[48, 2_039, 97_862, 4_085_603],
+    },
+    PerftPosition {
+        name: "position 3",
+        fen: "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        nodes: &[14, 191, 2_812, 43_238, 674_624],
+    },
+    PerftPosition {
+        name: "position 4",
+        fen: "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        nodes: &[6, 264, 9_467, 422_333],
+    },
+    PerftPosition {
+        name: "position 4 mirrored",
+        fen: "r2q1rk1/pP1p2pp/Q4n2/bbp1p3/Np6/1B3NBn/pPPP1PPP/R3K2R b KQ - 0 1",
+        nodes: &[6, 264, 9_467, 422_333],
+    },
+    PerftPosition {
+        name: "position 5",
+        fen: "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        nodes: &[44, 1_486, 62_379, 2_103_487],
+    },
+    PerftPosition {
+        name: "position 6",
+        fen: "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+        nodes: &[46, 2_079, 89_890, 3_894_594],
+    },
+    PerftPosition {
+        name: "chess960 1",
+        fen: "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9",
+        nodes: &[21, 528, 12_189, 326_672],
+    },
+    PerftPosition {
+        name: "chess960 2",
+        fen: "2nnrbkr/p1qppppp/8/1ppb4/6PP/3PP3/PPP2P2/BQNNRBKR w HEhe - 1 9",
+        nodes: &[21, 807, 18_002, 667_366],
+    },
+    PerftPosition {
+        name: "chess960 3",
+        fen: "b1q1rrkb/pppppppp/3nn3/8/P7/1PPP4/4PPPP/BQNNRKRB w GE - 1 9",
+        nodes: &[20, 479, 10_471, 273_318],
+    },
+    PerftPosition {
+        name: "chess960 4",
+        fen: "qbbnnrkr/2pp2pp/p7/1p2pp2/8/P3PP2/1PPP1KPP/QBBNNR1R w hf - 0 9",
+        nodes: &[22, 593, 13_440, 382_958],
+    },
+    PerftPosition {
+        name: "chess960 5",
+        fen: "1nbbnrkr/p1p1ppp1/3p4/1p3P1p/3Pq2P/8/PPP1P1P1/QNBBNRKR w HFhf - 0 9",
+        nodes: &[28, 1_120, 31_058, 1_171_749],
+    },
+];
+
+/// All legal moves in the position, with every promotion expanded into one
+/// move per piece type. Move generation only produces the queen promotion
+/// (as a plain [`Move::Piece`]), which is all the engine's search needs, but
+/// perft counts under-promotions as separate moves.
+pub fn get_perft_moves(board: &Board) -> Vec<Move> {
+    let color = board.get_turn_color();
+    let mut moves = Vec::new();
+    for m in board.get_legal_moves() {
+        match m {
+            Move::Piece(from, to)
+                if (to.get_row() == 0 || to.get_row() == 7)
+                    && matches!(board.get_piece(from), Some(Piece::Pawn(_, _))) =>
+            {
+                for piece in [
+                    Piece::Queen(color, to),
+                    Piece::Rook(color, to),
+                    Piece::Bishop(color, to),
+                    Piece::Knight(color, to),
+                ] {
+                    moves.push(Move::Promotion(from, to, piece));
+                }
+            }
+            _ => moves.push(m),
+        }
+    }
+    moves
+}
+
+/// Count the leaf nodes of the legal move tree `depth` plies deep.
+pub fn perft(board: &Board, depth: u8) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    let moves = get_perft_moves(board);
+    if depth == 1 {
+        return moves.len() as u64;
+    }
+    moves
+        .into_iter()
+        .map(|m| perft(&board.apply_eval_move(m), depth - 1))
+        .sum()
+}
+
+/// Like [`perft`], but returns the node count below each root move.
+pub fn perft_divide(board: &Board, depth: u8) -> Vec<(Move, u64)> {
+    if depth == 0 {
+        return Vec::new();
+    }
+    get_perft_moves(board)
+        .into_iter()
+        .map(|m| (m, perft(&board.apply_eval_move(m), depth - 1)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{format_uci_move, parse_fen};
+
+    /// Run every suite position up to the deepest count below `max_nodes`,
+    /// so the test stays fast in debug builds.
+    #[test]
+    fn test_perft_suite() {
+        const MAX_NODES: u64 = 100_000;
+        for position in PERFT_SUITE {
+            let board = parse_fen(position.fen).unwrap();
+            for (depth, &expected) in position.nodes.iter().enumerate() {
+                if expected > MAX_NODES {
+                    break;
+                }
+                let depth = depth as u8 + 1;
+                let divide = perft_divide(&board, depth);
+                let nodes: u64 = divide.iter().map(|(_, n)| n).sum();
+                if nodes != expected {
+                    let breakdown: Vec<String> = divide
+                        .iter()
+                        .map(|(m, n)| format!("{}: {}", format_uci_move(&board, *m).unwrap(), n))
+                        .collect();
+                    panic!(
+                        "perft({}) of {} is {}, expected {}:\n{}",
+                        depth,
+                        position.name,
+                        nodes,
+                        expected,
+                        breakdown.join("\n")
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_perft_promotions() {
+        // Both the push and the capture to the last rank promote to any of
+        // the four pieces.
+        let board = parse_fen("1n2k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let promotions = get_perft_moves(&board)
+            .into_iter()
+            .filter(|m| matches!(m, Move::Promotion(_, _, _)))
+            .count();
+        assert_eq!(promotions, 8);
+        assert_eq!(perft(&board, 0), 1);
+        assert!(perft_divide(&board, 0).is_empty());
+    }
+
+    #[test]
+    fn test_no_queenside_castle_into_check() {
+        // The bishop on a6 covers c8, where the king would land.
+        let board = parse_fen("r3k3/8/B7/8/8/8/8/4K3 b q - 0 1").unwrap();
+        assert!(!board.can_queenside_castle(crate::BLACK));
+        assert!(!get_perft_moves(&board).contains(&Move::QueenSideCastle));
+    }
+}
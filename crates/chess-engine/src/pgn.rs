@@ -0,0 +1,710 @@
+//! Reading and writing games in Portable Game Notation (PGN).
+//!
+//! [`parse_pgn`] reads tag pairs, movetext, comments, NAGs and the game
+//! result and replays every move on a [`Board`], so an imported game is
+//! always legal. Recursive annotation variations are skipped. A [`Pgn`]
+//! can also be built move by move and written back with its `Display`
+//! implementation.
+
+use crate::{
+    format_played_san_move, parse_fen, parse_san_move, Board, Color, GameResult, Move, Variant,
+};
+use core::fmt;
+
+/// Tags every PGN game should have, in the order they are exported.
+pub const SEVEN_TAG_ROSTER: [&str; 7] =
+    ["Event", "Site", "Date", "Round", "White", "Black", "Result"];
+
+/// Maximum line length of exported movetext.
+const LINE_LENGTH: usize = 79;
+
+/// The result of a game as written at the end of its movetext.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PgnResult {
+    WhiteWins,
+    BlackWins,
+    Draw,
+    /// The game is still in progress or the result is unknown.
+    #[default]
+    Unknown,
+}
+
+impl PgnResult {
+    fn from_token(token: &str) -> Option<Self> {
+        match token {
+            "1-0" => Some(Self::WhiteWins),
+            "0-1" => Some(Self::BlackWins),
+            "1/2-1/2" => Some(Self::Draw),
+            "*" => Some(Self::Unknown),
+            _ => None,
+        }
+    }
+
+    fn from_game_result(result: GameResult) -> Option<Self> {
+        match result {
+            GameResult::Continuing(_) | GameResult::IllegalMove(_) => None,
+            GameResult::Victory(Color::White) => Some(Self::WhiteWins),
+            GameResult::Victory(Color::Black) => Some(Self::BlackWins),
+            GameResult::Stalemate | GameResult::ThreefoldRepetition | GameResult::FiftyMoveRule => {
+                Some(Self::Draw)
+            }
+        }
+    }
+}
+
+impl fmt::Display for PgnResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::WhiteWins => "1-0",
+                Self::BlackWins => "0-1",
+                Self::Draw => "1/2-1/2",
+                Self::Unknown => "*",
+            }
+        )
+    }
+}
+
+/// A move of a PGN game together with its annotations.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PgnMove {
+    pub mv: Move,
+    /// The move in Standard Algebraic Notation.
+    pub san: String,
+    /// Numeric Annotation Glyphs, e.g. `1` for "!" or `4` for "??".
+    pub nags: Vec<u8>,
+    pub comment: Option<String>,
+}
+
+/// A single game in Portable Game Notation.
+#[derive(Clone, Debug)]
+pub struct Pgn {
+    tags: Vec<(String, String)>,
+    comment: Option<String>,
+    start: Board,
+    start_fullmove_number: u16,
+    moves: Vec<PgnMove>,
+    board: Board,
+    result: PgnResult,
+}
+
+impl Default for Pgn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Pgn {
+    /// Create a game from the standard starting position with the seven tag
+    /// roster set to unknown values.
+    pub fn new() -> Self {
+        let board = Board::default();
+        let tags = SEVEN_TAG_ROSTER
+            .iter()
+            .map(|name| {
+                let value = match *name {
+                    "Date" => "????.??.??",
+                    "Result" => "*",
+                    _ => "?",
+                };
+                (name.to_string(), value.to_string())
+            })
+            .collect();
+        Self {
+            tags,
+            comment: None,
+            start: board,
+            start_fullmove_number: 1,
+            moves: vec![],
+            board,
+            result: PgnResult::Unknown,
+        }
+    }
+
+    /// Create a game starting from a FEN position. This sets the `SetUp` and
+    /// `FEN` tags.
+    pub fn from_fen(fen: &str) -> Result<Self, String> {
+        let board = parse_fen(fen)?;
+        let fullmove_number = match fen.split_ascii_whitespace().nth(5) {
+            Some(number) => number
+                .parse()
+                .map_err(|_| "invalid fullmove number".to_string())?,
+            None => 1,
+        };
+
+        let mut pgn = Self::new();
+        pgn.start = board;
+        pgn.start_fullmove_number = fullmove_number;
+        pgn.board = board;
+        pgn.set_tag("SetUp", "1");
+        pgn.set_tag("FEN", fen);
+        Ok(pgn)
+    }
+
+    /// Get the value of a tag pair.
+    pub fn get_tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Set the value of a tag pair, adding it if it doesn't exist yet.
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|(tag, _)| tag == name) {
+            Some((_, old)) => *old = value.to_string(),
+            None => self.tags.push((name.to_string(), value.to_string())),
+        }
+    }
+
+    /// All tag pairs in export order.
+    #[inline]
+    pub fn get_tags(&self) -> &[(String, String)] {
+        &self.tags
+    }
+
+    /// The comment before the first move.
+    #[inline]
+    pub fn get_comment(&self) -> Option<&str> {
+        self.comment.as_deref()
+    }
+
+    #[inline]
+    pub fn set_comment(&mut self, comment: Option<String>) {
+        self.comment = comment;
+    }
+
+    /// The position the game started from.
+    #[inline]
+    pub fn get_start_board(&self) -> &Board {
+        &self.start
+    }
+
+    /// The current position, i.e. after the last move.
+    #[inline]
+    pub fn get_board(&self) -> &Board {
+        &self.board
+    }
+
+    #[inline]
+    pub fn get_moves(&self) -> &[PgnMove] {
+        &self.moves
+    }
+
+    /// The last move, e.g. to annotate it.
+    #[inline]
+    pub fn last_move_mut(&mut self) -> Option<&mut PgnMove> {
+        self.moves.last_mut()
+    }
+
+    #[inline]
+    pub fn get_result(&self) -> PgnResult {
+        self.result
+    }
+
+    /// Set the result of the game, e.g. after a resignation or an agreed
+    /// draw. This also updates the `Result` tag.
+    pub fn set_result(&mut self, result: PgnResult) {
+        self.result = result;
+        self.set_tag("Result", &result.to_string());
+    }
+
+    /// Play a legal move and append it to the game. If the move ends the
+    /// game by checkmate or stalemate, the result is set accordingly.
+    ///
+    /// Draws by repetition or the fifty-move rule have to be claimed in a
+    /// real game, so they don't end a PGN game.
+    pub fn play_move(&mut self, m: Move) -> Result<GameResult, String> {
+        if self.result != PgnResult::Unknown {
+            return Err("game is already over".to_string());
+        }
+        let result = self.board.play_move(m);
+        let san = format_played_san_move(&self.board, m, Variant::Standard, &result)?;
+        match result {
+            GameResult::Continuing(next_turn) => self.board = next_turn,
+            GameResult::IllegalMove(_) => return Err("illegal move".to_string()),
+            _ => self.board = self.board.apply_eval_move(m),
+        }
+        if let Some(pgn_result) = PgnResult::from_game_result(result) {
+            self.set_result(pgn_result);
+        }
+        self.moves.push(PgnMove {
+            mv: m,
+            san,
+            nags: vec![],
+            comment: None,
+        });
+        Ok(result)
+    }
+
+    /// Parse a move in Standard Algebraic Notation and play it.
+    pub fn play_san_move(&mut self, san: &str) -> Result<GameResult, String> {
+        let m = parse_san_move(&self.board, san)?;
+        self.play_move(m)
+    }
+}
+
+impl fmt::Display for Pgn {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (name, value) in &self.tags {
+            writeln!(
+                f,
+                "[{} \"{}\"]",
+                name,
+                value.replace('\\', "\\\\").replace('"', "\\\"")
+            )?;
+        }
+        writeln!(f)?;
+
+        let mut tokens: Vec<String> = vec![];
+        if let Some(comment) = &self.comment {
+            push_comment(&mut tokens, comment);
+        }
+
+        let mut color = self.start.get_turn_color();
+        let mut fullmove_number = self.start_fullmove_number;
+        // black's move needs its own number at the start and after comments
+        let mut needs_number = true;
+        for pgn_move in &self.moves {
+            // keep move numbers on the same line as their move
+            tokens.push(match color {
+                Color::White => format!("{}. {}", fullmove_number, pgn_move.san),
+                Color::Black if needs_number => {
+                    format!("{}... {}", fullmove_number, pgn_move.san)
+                }
+                Color::Black => pgn_move.san.clone(),
+            });
+            for nag in &pgn_move.nags {
+                tokens.push(format!("${}", nag));
+            }
+            needs_number = false;
+            if let Some(comment) = &pgn_move.comment {
+                push_comment(&mut tokens, comment);
+                needs_number = true;
+            }
+            if color == Color::Black {
+                fullmove_number += 1;
+            }
+            color = !color;
+        }
+        tokens.push(self.result.to_string());
+
+        let mut line_length = 0;
+        for token in tokens {
+            if line_length > 0 && line_length + 1 + token.len() > LINE_LENGTH {
+                writeln!(f)?;
+                line_length = 0;
+            }
+            if line_length > 0 {
+                write!(f, " ")?;
+                line_length += 1;
+            }
+            write!(f, "{}", token)?;
+            line_length += token.len();
+        }
+        writeln!(f)
+    }
+}
+
+/// Parse a single PGN game. Moves are validated by replaying them.
+pub fn parse_pgn(pgn: &str) -> Result<Pgn, String> {
+    let mut games = parse_pgn_games(pgn)?;
+    match games.len() {
+        0 => Err("no game found".to_string()),
+        1 => Ok(games.remove(0)),
+        _ => Err("more than one game found".to_string()),
+    }
+}
+
+/// Parse all games of a PGN database.
+pub fn parse_pgn_games(pgn: &str) -> Result<Vec<Pgn>, String> {
+    let mut games = vec![];
+    let mut parser = Parser::new(pgn);
+    while let Some(game) = parser.parse_game()? {
+        games.push(game);
+    }
+    Ok(games)
+}
+
+enum Token {
+    Tag(String, String),
+    Comment(String),
+    Nag(u8),
+    Result(PgnResult),
+    Symbol(String),
+}
+
+struct Parser<'a> {
+    chars: core::iter::Peekable<core::str::Chars<'a>>,
+    line_start: bool,
+    peeked: Option<Token>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(pgn: &'a str) -> Self {
+        Self {
+            chars: pgn.chars().peekable(),
+            line_start: true,
+            peeked: None,
+        }
+    }
+
+    fn parse_game(&mut self) -> Result<Option<Pgn>, String> {
+        let mut tags = vec![];
+        while let Some(Token::Tag(_, _)) = self.peek_token()? {
+            if let Some(Token::Tag(name, value)) = self.next_token()? {
+                tags.push((name, value));
+            }
+        }
+        if tags.is_empty() && self.peek_token()?.is_none() {
+            return Ok(None);
+        }
+
+        let mut game = match tags.iter().find(|(name, _)| name == "FEN") {
+            Some((_, fen)) => Pgn::from_fen(fen)?,
+            None => Pgn::new(),
+        };
+        for (name, value) in &tags {
+            game.set_tag(name, value);
+        }
+        let tag_result = game
+            .get_tag("Result")
+            .and_then(PgnResult::from_token)
+            .unwrap_or_default();
+
+        loop {
+            match self.peek_token()? {
+                // tags without a result in between start the next game
+                None | Some(Token::Tag(_, _)) => break,
+                _ => {}
+            }
+            match self.next_token()? {
+                Some(Token::Comment(comment)) => {
+                    let target = match game.last_move_mut() {
+                        Some(pgn_move) => &mut pgn_move.comment,
+                        None => &mut game.comment,
+                    };
+                    *target = Some(match target.take() {
+                        Some(previous) => format!("{} {}", previous, comment),
+                        None => comment,
+                    });
+                }
+                Some(Token::Nag(nag)) => match game.last_move_mut() {
+                    Some(pgn_move) => pgn_move.nags.push(nag),
+                    None => return Err("annotation before first move".to_string()),
+                },
+                Some(Token::Result(result)) => {
+                    game.set_result(result);
+                    return Ok(Some(game));
+                }
+                Some(Token::Symbol(symbol)) => {
+                    // strip move numbers like "12." or "12..."
+                    let unnumbered = symbol.trim_start_matches(|c: char| c.is_ascii_digit());
+                    let san = if unnumbered.len() < symbol.len() && unnumbered.starts_with('.') {
+                        unnumbered.trim_start_matches('.')
+                    } else {
+                        symbol.as_str()
+                    };
+                    if san.is_empty() {
+                        continue;
+                    }
+                    let (san, nag) = split_suffix_annotation(san);
+                    game.play_san_move(san)
+                        .map_err(|err| format!("{}: {}", symbol, err))?;
+                    if let Some(nag) = nag {
+                        if let Some(pgn_move) = game.last_move_mut() {
+                            pgn_move.nags.push(nag);
+                        }
+                    }
+                }
+                Some(Token::Tag(_, _)) | None => unreachable!(),
+            }
+        }
+        if game.get_result() == PgnResult::Unknown && tag_result != PgnResult::Unknown {
+            game.set_result(tag_result);
+        }
+        Ok(Some(game))
+    }
+
+    fn peek_token(&mut self) -> Result<Option<&Token>, String> {
+        if self.peeked.is_none() {
+            self.peeked = self.read_token()?;
+        }
+        Ok(self.peeked.as_ref())
+    }
+
+    fn next_token(&mut self) -> Result<Option<Token>, String> {
+        match self.peeked.take() {
+            Some(token) => Ok(Some(token)),
+            None => self.read_token(),
+        }
+    }
+
+    fn next_char(&mut self) -> Option<char> {
+        let c = self.chars.next();
+        self.line_start = c == Some('\n');
+        c
+    }
+
+    fn read_token(&mut self) -> Result<Option<Token>, String> {
+        loop {
+            let line_start = self.line_start;
+            let c = match self.next_char() {
+                Some(c) => c,
+                None => return Ok(None),
+            };
+            match c {
+                c if c.is_whitespace() => {}
+                // escape mechanism, the rest of the line is ignored
+                '%' if line_start => self.skip_line(),
+                ';' => {
+                    let mut comment = String::new();
+                    while let Some(c) = self.next_char() {
+                        if c == '\n' {
+                            break;
+                        }
+                        comment.push(c);
+                    }
+                    return Ok(Some(Token::Comment(comment.trim().to_string())));
+                }
+                '{' => {
+                    let mut comment = String::new();
+                    loop {
+                        match self.next_char() {
+                            Some('}') => break,
+                            Some(c) => comment.push(c),
+                            None => return Err("unterminated comment".to_string()),
+                        }
+                    }
+                    let comment = comment.split_whitespace().collect::<Vec<_>>().join(" ");
+                    return Ok(Some(Token::Comment(comment)));
+                }
+                '(' => self.skip_variation()?,
+                ')' => return Err("unexpected end of variation".to_string()),
+                '[' => return self.read_tag().map(Some),
+                '$' => {
+                    let nag = self.read_symbol();
+                    return match nag.parse() {
+                        Ok(nag) => Ok(Some(Token::Nag(nag))),
+                        Err(_) => Err(format!("invalid NAG ${}", nag)),
+                    };
+                }
+                c => {
+                    let mut symbol = self.read_symbol();
+                    symbol.insert(0, c);
+                    return Ok(Some(match PgnResult::from_token(&symbol) {
+                        Some(result) => Token::Result(result),
+                        None => Token::Symbol(symbol),
+                    }));
+                }
+            }
+        }
+    }
+
+    fn read_symbol(&mut self) -> String {
+        let mut symbol = String::new();
+        while let Some(&c) = self.chars.peek() {
+            if c.is_whitespace() || "{}()[];$".contains(c) {
+                break;
+            }
+            symbol.push(c);
+            self.next_char();
+        }
+        symbol
+    }
+
+    fn read_tag(&mut self) -> Result<Token, String> {
+        let mut name = String::new();
+        while let Some(&c) = self.chars.peek() {
+            if c.is_whitespace() || c == '"' || c == ']' {
+                break;
+            }
+            name.push(c);
+            self.next_char();
+        }
+        while self.chars.peek().is_some_and(|c| c.is_whitespace()) {
+            self.next_char();
+        }
+        if self.next_char() != Some('"') || name.is_empty() {
+            return Err("invalid tag pair".to_string());
+        }
+        let mut value = String::new();
+        loop {
+            match self.next_char() {
+                Some('\\') => match self.next_char() {
+                    Some(c) => value.push(c),
+                    None => return Err("unterminated tag value".to_string()),
+                },
+                Some('"') => break,
+                Some(c) => value.push(c),
+                None => return Err("unterminated tag value".to_string()),
+            }
+        }
+        while self.chars.peek().is_some_and(|c| c.is_whitespace()) {
+            self.next_char();
+        }
+        if self.next_char() != Some(']') {
+            return Err("invalid tag pair".to_string());
+        }
+        Ok(Token::Tag(name, value))
+    }
+
+    fn skip_line(&mut self) {
+        while let Some(c) = self.next_char() {
+            if c == '\n' {
+                break;
+            }
+        }
+    }
+
+    fn skip_variation(&mut self) -> Result<(), String> {
+        let mut depth = 1;
+        while depth > 0 {
+            match self.next_char() {
+                Some('(') => depth += 1,
+                Some(')') => depth -= 1,
+                Some('{') => loop {
+                    match self.next_char() {
+                        Some('}') => break,
+                        Some(_) => {}
+                        None => return Err("unterminated comment".to_string()),
+                    }
+                },
+                Some(';') => self.skip_line(),
+                Some(_) => {}
+                None => return Err("unterminated variation".to_string()),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Push a comment as one token per word, so long comments can be wrapped.
+fn push_comment(tokens: &mut Vec<String>, comment: &str) {
+    let words: Vec<&str> = comment.split_whitespace().collect();
+    if words.is_empty() {
+        tokens.push("{}".to_string());
+    }
+    for (i, word) in words.iter().enumerate() {
+        let mut token = word.to_string();
+        if i == 0 {
+            token.insert(0, '{');
+        }
+        if i == words.len() - 1 {
+            token.push('}');
+        }
+        tokens.push(token);
+    }
+}
+
+/// Split traditional suffix annotations like "!?" from a move and convert
+/// them to their NAG.
+fn split_suffix_annotation(san: &str) -> (&str, Option<u8>) {
+    let stripped = san.trim_end_matches(['!', '?']);
+    let nag = match &san[stripped.len()..] {
+        "!" => Some(1),
+        "?" => Some(2),
+        "!!" => Some(3),
+        "??" => Some(4),
+        "!?" => Some(5),
+        "?!" => Some(6),
+        _ => None,
+    };
+    (stripped, nag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{E2, E4};
+
+    const OPERA_GAME: &str = r#"[Event "Paris"]
+[Site "Paris FRA"]
+[Date "1858.??.??"]
+[Round "?"]
+[White "Paul Morphy"]
+[Black "Duke Karl / Count Isouard"]
+[Result "1-0"]
+
+1. e4 e5 2. Nf3 d6 3. d4 Bg4 {This is a weak move already.} 4. dxe5 Bxf3
+5. Qxf3 dxe5 6. Bc4 Nf6 7. Qb3 Qe7 8. Nc3 c6 9. Bg5 b5 $2 10. Nxb5 cxb5
+11. Bxb5+ Nbd7 12. O-O-O Rd8 13. Rxd7 Rxd7 14. Rd1 Qe6 15. Bxd7+ Nxd7 16. Qb8+
+Nxb8 17. Rd8# 1-0
+"#;
+
+    #[test]
+    fn round_trips_annotated_game() {
+        let game = parse_pgn(OPERA_GAME).unwrap();
+        assert_eq!(game.get_tag("White"), Some("Paul Morphy"));
+        assert_eq!(game.get_moves().len(), 33);
+        assert_eq!(game.get_result(), PgnResult::WhiteWins);
+        assert_eq!(
+            game.get_moves()[5].comment.as_deref(),
+            Some("This is a weak move already.")
+        );
+        assert_eq!(game.get_moves()[17].nags, vec![2]);
+        assert!(game.get_board().is_checkmate());
+
+        assert_eq!(game.to_string(), OPERA_GAME);
+    }
+
+    #[test]
+    fn parses_variations_suffixes_and_multiple_games() {
+        let pgn = r#"[Event "First"]
+
+1. e4 (1. d4 d5 (1... Nf6)) e5!? ; line comment
+2. Nf3 $1 *
+
+[Event "Second"]
+[SetUp "1"]
+[FEN "6k1/5ppp/8/8/8/8/8/R3K3 w - - 0 40"]
+
+40. Ra8# 1-0
+"#;
+        let games = parse_pgn_games(pgn).unwrap();
+        assert_eq!(games.len(), 2);
+
+        let first = &games[0];
+        assert_eq!(first.get_moves().len(), 3);
+        assert_eq!(first.get_moves()[0].mv, Move::Piece(E2, E4));
+        assert_eq!(first.get_moves()[1].nags, vec![5]);
+        assert_eq!(
+            first.get_moves()[1].comment.as_deref(),
+            Some("line comment")
+        );
+        assert_eq!(first.get_moves()[2].nags, vec![1]);
+        assert_eq!(first.get_result(), PgnResult::Unknown);
+
+        let second = &games[1];
+        assert_eq!(second.get_moves()[0].san, "Ra8#");
+        assert_eq!(second.get_result(), PgnResult::WhiteWins);
+        assert!(second.to_string().ends_with("\n\n40. Ra8# 1-0\n"));
+    }
+
+    #[test]
+    fn rejects_illegal_moves() {
+        assert!(parse_pgn("1. e4 e5 2. Ke3 *").is_err());
+        assert!(parse_pgn("1. e4 e5 2. Qh5 Nc6 3. Bc4 Nf6 4. Qxf7# Ke7 1-0").is_err());
+    }
+
+    #[test]
+    fn builds_game_move_by_move() {
+        let mut game = Pgn::new();
+        game.set_tag("White", "alice.near");
+        game.play_san_move("e4").unwrap();
+        game.play_san_move("e5").unwrap();
+        game.last_move_mut().unwrap().comment = Some("symmetrical".to_string());
+        game.play_san_move("Nf3").unwrap();
+        assert_eq!(
+            game.to_string(),
+            "[Event \"?\"]\n[Site \"?\"]\n[Date \"????.??.??\"]\n[Round \"?\"]\n\
+             [White \"alice.near\"]\n[Black \"?\"]\n[Result \"*\"]\n\n\
+             1. e4 e5 {symmetrical} 2. Nf3 *\n"
+        );
+        game.set_result(PgnResult::Draw);
+        assert_eq!(game.get_tag("Result"), Some("1/2-1/2"));
+        assert!(game.play_san_move("Nc6").is_err());
+    }
+}
@@ -1,3 +1,4 @@
+use super::bitboard::*;
 use super::{Board, Color, Move, Position};
 use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
 use std::convert::TryFrom;
@@ -22,135 +23,162 @@ pub enum Piece {
     Pawn(Color, Position),
 }
 
-const WHITE_KING_POSITION_WEIGHTS: [[f64; 8]; 8] = [
-    [-3.0, -4.0, -4.0, -5.0, -5.0, -4.0, -4.0, -3.0],
-    [-3.0, -4.0, -4.0, -5.0, -5.0, -4.0, -4.0, -3.0],
-    [-3.0, -4.0, -4.0, -5.0, -5.0, -4.0, -4.0, -3.0],
-    [-3.0, -4.0, -4.0, -5.0, -5.0, -4.0, -4.0, -3.0],
-    [-2.0, -3.0, -3.0, -4.0, -4.0, -3.0, -3.0, -2.0],
-    [-1.0, -2.0, -2.0, -2.0, -2.0, -2.0, -2.0, -1.0],
-    [2.0, 2.0, 0.0, 0.0, 0.0, 0.0, 2.0, 2.0],
-    [2.0, 3.0, 1.0, 0.0, 0.0, 1.0, 3.0, 2.0],
+const WHITE_KING_POSITION_WEIGHTS: [[i32; 8]; 8] = [
+    [-30, -40, -40, -50, -50, -40, -40, -30],
+    [-30, -40, -40, -50, -50, -40, -40, -30],
+    [-30, -40, -40, -50, -50, -40, -40, -30],
+    [-30, -40, -40, -50, -50, -40, -40, -30],
+    [-20, -30, -30, -40, -40, -30, -30, -20],
+    [-10, -20, -20, -20, -20, -20, -20, -10],
+    [20, 20, 0, 0, 0, 0, 20, 20],
+    [20, 30, 10, 0, 0, 10, 30, 20],
 ];
 
-const BLACK_KING_POSITION_WEIGHTS: [[f64; 8]; 8] = [
-    [2.0, 3.0, 1.0, 0.0, 0.0, 1.0, 3.0, 2.0],
-    [2.0, 2.0, 0.0, 0.0, 0.0, 0.0, 2.0, 2.0],
-    [-1.0, -2.0, -2.0, -2.0, -2.0, -2.0, -2.0, -1.0],
-    [-2.0, -3.0, -3.0, -4.0, -4.0, -3.0, -3.0, -2.0],
-    [-3.0, -4.0, -4.0, -5.0, -5.0, -4.0, -4.0, -3.0],
-    [-3.0, -4.0, -4.0, -5.0, -5.0, -4.0, -4.0, -3.0],
-    [-3.0, -4.0, -4.0, -5.0, -5.0, -4.0, -4.0, -3.0],
-    [-3.0, -4.0, -4.0, -5.0, -5.0, -4.0, -4.0, -3.0],
+const BLACK_KING_POSITION_WEIGHTS: [[i32; 8]; 8] = [
+    [20, 30, 10, 0, 0, 10, 30, 20],
+    [20, 20, 0, 0, 0, 0, 20, 20],
+    [-10, -20, -20, -20, -20, -20, -20, -10],
+    [-20, -30, -30, -40, -40, -30, -30, -20],
+    [-30, -40, -40, -50, -50, -40, -40, -30],
+    [-30, -40, -40, -50, -50, -40, -40, -30],
+    [-30, -40, -40, -50, -50, -40, -40, -30],
+    [-30, -40, -40, -50, -50, -40, -40, -30],
 ];
 
-const WHITE_QUEEN_POSITION_WEIGHTS: [[f64; 8]; 8] = [
-    [-2.0, -1.0, -1.0, -0.5, -0.5, -1.0, -1.0, -2.0],
-    [-1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, -1.0],
-    [-1.0, 0.0, 0.5, 0.5, 0.5, 0.5, 0.0, -1.0],
-    [-0.5, 0.0, 0.5, 0.5, 0.5, 0.5, 0.0, -0.5],
-    [0.0, 0.0, 0.5, 0.5, 0.5, 0.5, 0.0, -0.5],
-    [-1.0, 0.5, 0.5, 0.5, 0.5, 0.5, 0.0, -1.0],
-    [-1.0, 0.0, 0.5, 0.0, 0.0, 0.0, 0.0, -1.0],
-    [-1.0, -0.0, -1.0, -0.5, -0.5, -0.5, -1.0, -2.0],
+const WHITE_QUEEN_POSITION_WEIGHTS: [[i32; 8]; 8] = [
+    [-20, -10, -10, -5, -5, -10, -10, -20],
+    [-10, 0, 0, 0, 0, 0, 0, -10],
+    [-10, 0, 5, 5, 5, 5, 0, -10],
+    [-5, 0, 5, 5, 5, 5, 0, -5],
+    [0, 0, 5, 5, 5, 5, 0, -5],
+    [-10, 5, 5, 5, 5, 5, 0, -10],
+    [-10, 0, 5, 0, 0, 0, 0, -10],
+    [-10, 0, -10, -5, -5, -5, -10, -20],
 ];
-const BLACK_QUEEN_POSITION_WEIGHTS: [[f64; 8]; 8] = [
-    [-1.0, -0.0, -1.0, -0.5, -0.5, -0.5, -1.0, -2.0],
-    [-1.0, 0.0, 0.5, 0.0, 0.0, 0.0, 0.0, -1.0],
-    [-1.0, 0.5, 0.5, 0.5, 0.5, 0.5, 0.0, -1.0],
-    [0.0, 0.0, 0.5, 0.5, 0.5, 0.5, 0.0, -0.5],
-    [-0.5, 0.0, 0.5, 0.5, 0.5, 0.5, 0.0, -0.5],
-    [-1.0, 0.0, 0.5, 0.5, 0.5, 0.5, 0.0, -1.0],
-    [-1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, -1.0],
-    [-2.0, -1.0, -1.0, -0.5, -0.5, -1.0, -1.0, -2.0],
+const BLACK_QUEEN_POSITION_WEIGHTS: [[i32; 8]; 8] = [
+    [-10, 0, -10, -5, -5, -5, -10, -20],
+    [-10, 0, 5, 0, 0, 0, 0, -10],
+    [-10, 5, 5, 5, 5, 5, 0, -10],
+    [0, 0, 5, 5, 5, 5, 0, -5],
+    [-5, 0, 5, 5, 5, 5, 0, -5],
+    [-10, 0, 5, 5, 5, 5, 0, -10],
+    [-10, 0, 0, 0, 0, 0, 0, -10],
+    [-20, -10, -10, -5, -5, -10, -10, -20],
 ];
 
-const WHITE_ROOK_POSITION_WEIGHTS: [[f64; 8]; 8] = [
-    [0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
-    [0.5, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 0.5],
-    [-0.5, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, -0.5],
-    [-0.5, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, -0.5],
-    [-0.5, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, -0.5],
-    [-0.5, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, -0.5],
-    [-0.5, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, -0.5],
-    [0.0, 0.0, 0.0, 0.5, 0.5, 0.0, 0.0, 0.0],
+const WHITE_ROOK_POSITION_WEIGHTS: [[i32; 8]; 8] = [
+    [0, 0, 0, 0, 0, 0, 0, 0],
+    [5, 10, 10, 10, 10, 10, 10, 5],
+    [-5, 0, 0, 0, 0, 0, 0, -5],
+    [-5, 0, 0, 0, 0, 0, 0, -5],
+    [-5, 0, 0, 0, 0, 0, 0, -5],
+    [-5, 0, 0, 0, 0, 0, 0, -5],
+    [-5, 0, 0, 0, 0, 0, 0, -5],
+    [0, 0, 0, 5, 5, 0, 0, 0],
 ];
 
-const BLACK_ROOK_POSITION_WEIGHTS: [[f64; 8]; 8] = [
-    [0.0, 0.0, 0.0, 0.5, 0.5, 0.0, 0.0, 0.0],
-    [-0.5, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, -0.5],
-    [-0.5, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, -0.5],
-    [-0.5, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, -0.5],
-    [-0.5, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, -0.5],
-    [-0.5, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, -0.5],
-    [0.5, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 0.5],
-    [0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+const BLACK_ROOK_POSITION_WEIGHTS: [[i32; 8]; 8] = [
+    [0, 0, 0, 5, 5, 0, 0, 0],
+    [-5, 0, 0, 0, 0, 0, 0, -5],
+    [-5, 0, 0, 0, 0, 0, 0, -5],
+    [-5, 0, 0, 0, 0, 0, 0, -5],
+    [-5, 0, 0, 0, 0, 0, 0, -5],
+    [-5, 0, 0, 0, 0, 0, 0, -5],
+    [5, 10, 10, 10, 10, 10, 10, 5],
+    [0, 0, 0, 0, 0, 0, 0, 0],
 ];
 
-const WHITE_BISHOP_POSITION_WEIGHTS: [[f64; 8]; 8] = [
-    [-2.0, -1.0, -1.0, -1.0, -1.0, -1.0, -1.0, -2.0],
-    [-1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, -1.0],
-    [-1.0, 0.0, 0.5, 1.0, 1.0, 0.5, 0.0, -1.0],
-    [-1.0, 0.5, 0.5, 1.0, 1.0, 0.5, 0.5, -1.0],
-    [-1.0, 0.0, 1.0, 1.0, 1.0, 1.0, 0.0, -1.0],
-    [-1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, -1.0],
-    [-1.0, 0.5, 0.0, 0.0, 0.0, 0.0, 0.5, -1.0],
-    [-2.0, -1.0, -1.0, -1.0, -1.0, -1.0, -1.0, -2.0],
+const WHITE_BISHOP_POSITION_WEIGHTS: [[i32; 8]; 8] = [
+    [-20, -10, -10, -10, -10, -10, -10, -20],
+    [-10, 0, 0, 0, 0, 0, 0, -10],
+    [-10, 0, 5, 10, 10, 5, 0, -10],
+    [-10, 5, 5, 10, 10, 5, 5, -10],
+    [-10, 0, 10, 10, 10, 10, 0, -10],
+    [-10, 10, 10, 10, 10, 10, 10, -10],
+    [-10, 5, 0, 0, 0, 0, 5, -10],
+    [-20, -10, -10, -10, -10, -10, -10, -20],
 ];
 
-const BLACK_BISHOP_POSITION_WEIGHTS: [[f64; 8]; 8] = [
-    [-2.0, -1.0, -1.0, -1.0, -1.0, -1.0, -1.0, -2.0],
-    [-1.0, 0.5, 0.0, 0.0, 0.0, 0.0, 0.5, -1.0],
-    [-1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, -1.0],
-    [-1.0, 0.0, 1.0, 1.0, 1.0, 1.0, 0.0, -1.0],
-    [-1.0, 0.5, 0.5, 1.0, 1.0, 0.5, 0.5, -1.0],
-    [-1.0, 0.0, 0.5, 1.0, 1.0, 0.5, 0.0, -1.0],
-    [-1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, -1.0],
-    [-2.0, -1.0, -1.0, -1.0, -1.0, -1.0, -1.0, -2.0],
+const BLACK_BISHOP_POSITION_WEIGHTS: [[i32; 8]; 8] = [
+    [-20, -10, -10, -10, -10, -10, -10, -20],
+    [-10, 5, 0, 0, 0, 0, 5, -10],
+    [-10, 10, 10, 10, 10, 10, 10, -10],
+    [-10, 0, 10, 10, 10, 10, 0, -10],
+    [-10, 5, 5, 10, 10, 5, 5, -10],
+    [-10, 0, 5, 10, 10, 5, 0, -10],
+    [-10, 0, 0, 0, 0, 0, 0, -10],
+    [-20, -10, -10, -10, -10, -10, -10, -20],
 ];
 
-const WHITE_KNIGHT_POSITION_WEIGHTS: [[f64; 8]; 8] = [
-    [-5.0, -4.0, -3.0, -3.0, -3.0, -3.0, -4.0, -5.0],
-    [-4.0, -2.0, 0.0, 0.0, 0.0, 0.0, -2.0, -4.0],
-    [-3.0, 0.0, 1.0, 1.5, 1.5, 1.0, 0.0, -3.0],
-    [-3.0, 0.5, 1.5, 2.0, 2.0, 1.5, 0.5, -3.0],
-    [-3.0, 0.0, 1.5, 2.0, 2.0, 1.5, 0.0, -3.0],
-    [-3.0, 0.5, 1.0, 1.5, 1.5, 1.0, 0.5, -3.0],
-    [-4.0, -2.0, 0.0, 0.5, 0.5, 0.0, -2.0, -4.0],
-    [-5.0, -4.0, -3.0, -3.0, -3.0, -3.0, -4.0, -5.0],
+const WHITE_KNIGHT_POSITION_WEIGHTS: [[i32; 8]; 8] = [
+    [-50, -40, -30, -30, -30, -30, -40, -50],
+    [-40, -20, 0, 0, 0, 0, -20, -40],
+    [-30, 0, 10, 15, 15, 10, 0, -30],
+    [-30, 5, 15, 20, 20, 15, 5, -30],
+    [-30, 0, 15, 20, 20, 15, 0, -30],
+    [-30, 5, 10, 15, 15, 10, 5, -30],
+    [-40, -20, 0, 5, 5, 0, -20, -40],
+    [-50, -40, -30, -30, -30, -30, -40, -50],
 ];
 
-const BLACK_KNIGHT_POSITION_WEIGHTS: [[f64; 8]; 8] = [
-    [-5.0, -4.0, -3.0, -3.0, -3.0, -3.0, -4.0, -5.0],
-    [-4.0, -2.0, 0.0, 0.5, 0.5, 0.0, -2.0, -4.0],
-    [-3.0, 0.5, 1.0, 1.5, 1.5, 1.0, 0.5, -3.0],
-    [-3.0, 0.0, 1.5, 2.0, 2.0, 1.5, 0.0, -3.0],
-    [-3.0, 0.5, 1.5, 2.0, 2.0, 1.5, 0.5, -3.0],
-    [-3.0, 0.0, 1.0, 1.5, 1.5, 1.0, 0.0, -3.0],
-    [-4.0, -2.0, 0.0, 0.0, 0.0, 0.0, -2.0, -4.0],
-    [-5.0, -4.0, -3.0, -3.0, -3.0, -3.0, -4.0, -5.0],
+const BLACK_KNIGHT_POSITION_WEIGHTS: [[i32; 8]; 8] = [
+    [-50, -40, -30, -30, -30, -30, -40, -50],
+    [-40, -20, 0, 5, 5, 0, -20, -40],
+    [-30, 5, 10, 15, 15, 10, 5, -30],
+    [-30, 0, 15, 20, 20, 15, 0, -30],
+    [-30, 5, 15, 20, 20, 15, 5, -30],
+    [-30, 0, 10, 15, 15, 10, 0, -30],
+    [-40, -20, 0, 0, 0, 0, -20, -40],
+    [-50, -40, -30, -30, -30, -30, -40, -50],
 ];
 
-const WHITE_PAWN_POSITION_WEIGHTS: [[f64; 8]; 8] = [
-    [0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
-    [5.0, 5.0, 5.0, 5.0, 5.0, 5.0, 5.0, 5.0],
-    [1.0, 1.0, 2.0, 3.0, 3.0, 2.0, 1.0, 1.0],
-    [0.5, 0.5, 1.0, 2.5, 2.5, 1.0, 0.5, 0.5],
-    [0.0, 0.0, 0.0, 2.0, 2.0, 0.0, 0.0, 0.0],
-    [0.5, -0.5, -1.0, 0.0, 0.0, -1.0, -0.5, 0.5],
-    [0.5, 1.5, -1.0, -2.0, -2.0, 1.0, 1.5, 0.5],
-    [0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+const WHITE_PAWN_POSITION_WEIGHTS: [[i32; 8]; 8] = [
+    [0, 0, 0, 0, 0, 0, 0, 0],
+    [50, 50, 50, 50, 50, 50, 50, 50],
+    [10, 10, 20, 30, 30, 20, 10, 10],
+    [5, 5, 10, 25, 25, 10, 5, 5],
+    [0, 0, 0, 20, 20, 0, 0, 0],
+    [5, -5, -10, 0, 0, -10, -5, 5],
+    [5, 15, -10, -20, -20, 10, 15, 5],
+    [0, 0, 0, 0, 0, 0, 0, 0],
 ];
 
-const BLACK_PAWN_POSITION_WEIGHTS: [[f64; 8]; 8] = [
-    [0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
-    [0.5, 1.5, -1.0, -2.0, -2.0, 1.0, 1.5, 0.5],
-    [0.5, -0.5, -1.0, 0.0, 0.0, -1.0, -0.5, 0.5],
-    [0.0, 0.0, 0.0, 2.0, 2.0, 0.0, 0.0, 0.0],
-    [0.5, 0.5, 1.0, 2.5, 2.5, 1.0, 0.5, 0.5],
-    [1.0, 1.0, 2.0, 3.0, 3.0, 2.0, 1.0, 1.0],
-    [5.0, 5.0, 5.0, 5.0, 5.0, 5.0, 5.0, 5.0],
-    [0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+const BLACK_PAWN_POSITION_WEIGHTS: [[i32; 8]; 8] = [
+    [0, 0, 0, 0, 0, 0, 0, 0],
+    [5, 15, -10, -20, -20, 10, 15, 5],
+    [5, -5, -10, 0, 0, -10, -5, 5],
+    [0, 0, 0, 20, 20, 0, 0, 0],
+    [5, 5, 10, 25, 25, 10, 5, 5],
+    [10, 10, 20, 30, 30, 20, 10, 10],
+    [50, 50, 50, 50, 50, 50, 50, 50],
+    [0, 0, 0, 0, 0, 0, 0, 0],
+];
+
+/// Endgame king weights from White's side, rank 8 first. With few pieces
+/// left the king is safe in the center and needed there, so unlike the
+/// midgame table this one pulls it forward.
+const KING_ENDGAME_POSITION_WEIGHTS: [[i32; 8]; 8] = [
+    [-50, -30, -20, -20, -20, -20, -30, -50],
+    [-30, -10, 0, 5, 5, 0, -10, -30],
+    [-20, 0, 20, 30, 30, 20, 0, -20],
+    [-20, 5, 30, 40, 40, 30, 5, -20],
+    [-20, 5, 30, 40, 40, 30, 5, -20],
+    [-20, 0, 20, 30, 30, 20, 0, -20],
+    [-30, -10, 0, 5, 5, 0, -10, -30],
+    [-50, -30, -20, -20, -20, -20, -30, -50],
+];
+
+/// Endgame pawn weights from White's side, rank 8 first: every step towards
+/// promotion counts, on any file.
+const PAWN_ENDGAME_POSITION_WEIGHTS: [[i32; 8]; 8] = [
+    [0, 0, 0, 0, 0, 0, 0, 0],
+    [80, 80, 80, 80, 80, 80, 80, 80],
+    [50, 50, 50, 50, 50, 50, 50, 50],
+    [30, 30, 30, 30, 30, 30, 30, 30],
+    [15, 15, 15, 15, 15, 15, 15, 15],
+    [5, 5, 5, 5, 5, 5, 5, 5],
+    [0, 0, 0, 0, 0, 0, 0, 0],
+    [0, 0, 0, 0, 0, 0, 0, 0],
 ];
 
 impl core::fmt::Display for Piece {
@@ -255,10 +283,10 @@ impl Piece {
     /// a king in the center of the board is highly unfavorable compared to
     /// a king its respective side.
     ///
-    /// Additionally, the weighted value of the piece is 10 times greater than
-    /// its material value, plus or minus a weight ranging between 5.0 and -5.0.
+    /// The weighted value is in centipawns: 100 times the material value,
+    /// plus or minus a weight ranging between 50 and -50.
     #[inline]
-    pub fn get_weighted_value(&self) -> f64 {
+    pub fn get_weighted_value(&self) -> i32 {
         let weights = match self {
             Self::King(c, _) => match c {
                 Color::White => WHITE_KING_POSITION_WEIGHTS,
@@ -286,7 +314,28 @@ impl Piece {
             },
         };
         weights[(7 - self.get_pos().get_row()) as usize][self.get_pos().get_col() as usize]
-            + (self.get_material_value() * 10) as f64
+            + self.get_material_value() * 100
+    }
+
+    /// Get the weighted value of a piece in the endgame, on the same scale
+    /// as [`Piece::get_weighted_value`], which is the midgame value. Kings
+    /// and pawns have their own endgame tables; the other pieces are valued
+    /// the same in both phases.
+    #[inline]
+    pub fn get_endgame_weighted_value(&self) -> i32 {
+        let pos = self.get_pos();
+        // the tables are from White's side, so Black reads them upside down
+        let row = match self.get_color() {
+            Color::White => 7 - pos.get_row(),
+            Color::Black => pos.get_row(),
+        } as usize;
+        let col = pos.get_col() as usize;
+        let weight = match self {
+            Self::King(_, _) => KING_ENDGAME_POSITION_WEIGHTS[row][col],
+            Self::Pawn(_, _) => PAWN_ENDGAME_POSITION_WEIGHTS[row][col],
+            _ => return self.get_weighted_value(),
+        };
+        weight + self.get_material_value() * 100
     }
 
     /// Get the color of a given piece.
@@ -590,6 +639,7 @@ impl Piece {
         if board.has_ally_piece(new_pos, self.get_color()) || new_pos.is_off_board() {
             return false;
         }
+        let target = square_bit(new_pos);
 
         match *self {
             Self::Pawn(ally_color, pos) => {
@@ -610,67 +660,21 @@ impl Piece {
                     || (board.has_no_piece(new_pos) && new_pos == up)
             }
 
-            Self::King(_, pos) => pos.is_adjacent_to(new_pos),
+            Self::King(_, pos) => KING_ATTACKS[square_index(pos)] & target != EMPTY,
 
             Self::Queen(_, pos) => {
-                if pos.is_orthogonal_to(new_pos) {
-                    let mut traveling = pos.orthogonals_to(new_pos);
-                    traveling.pop();
-
-                    for pos in traveling {
-                        if board.has_piece(pos) {
-                            return false;
-                        }
-                    }
-                    true
-                } else if pos.is_diagonal_to(new_pos) {
-                    let mut traveling = pos.diagonals_to(new_pos);
-                    traveling.pop();
-
-                    for pos in traveling {
-                        if board.has_piece(pos) {
-                            return false;
-                        }
-                    }
-                    true
-                } else {
-                    false
-                }
+                queen_attacks(square_index(pos), board.bitboards().occupied()) & target != EMPTY
             }
 
             Self::Rook(_, pos) => {
-                if pos.is_orthogonal_to(new_pos) {
-                    let mut traveling = pos.orthogonals_to(new_pos);
-                    traveling.pop();
-
-                    for pos in traveling {
-                        if board.has_piece(pos) {
-                            return false;
-                        }
-                    }
-                    true
-                } else {
-                    false
-                }
+                rook_attacks(square_index(pos), board.bitboards().occupied()) & target != EMPTY
             }
 
             Self::Bishop(_, pos) => {
-                if pos.is_diagonal_to(new_pos) {
-                    let mut traveling = pos.diagonals_to(new_pos);
-                    traveling.pop();
-
-                    for pos in traveling {
-                        if board.has_piece(pos) {
-                            return false;
-                        }
-                    }
-                    true
-                } else {
-                    false
-                }
+                bishop_attacks(square_index(pos), board.bitboards().occupied()) & target != EMPTY
             }
 
-            Self::Knight(_, pos) => pos.is_knight_move(new_pos),
+            Self::Knight(_, pos) => KNIGHT_ATTACKS[square_index(pos)] & target != EMPTY,
         }
     }
 
@@ -680,6 +684,7 @@ impl Piece {
         if board.has_ally_piece(new_pos, self.get_color()) || new_pos.is_off_board() {
             return false;
         }
+        let target = square_bit(new_pos);
 
         match *self {
             Self::Pawn(ally_color, pos) => {
@@ -693,67 +698,21 @@ impl Piece {
                     || new_pos == up.next_right()
             }
 
-            Self::King(_, pos) => pos.is_adjacent_to(new_pos),
+            Self::King(_, pos) => KING_ATTACKS[square_index(pos)] & target != EMPTY,
 
             Self::Queen(_, pos) => {
-                if pos.is_orthogonal_to(new_pos) {
-                    let mut traveling = pos.orthogonals_to(new_pos);
-                    traveling.pop();
-
-                    for pos in traveling {
-                        if board.has_piece(pos) {
-                            return false;
-                        }
-                    }
-                    true
-                } else if pos.is_diagonal_to(new_pos) {
-                    let mut traveling = pos.diagonals_to(new_pos);
-                    traveling.pop();
-
-                    for pos in traveling {
-                        if board.has_piece(pos) {
-                            return false;
-                        }
-                    }
-                    true
-                } else {
-                    false
-                }
+                queen_attacks(square_index(pos), board.bitboards().occupied()) & target != EMPTY
             }
 
             Self::Rook(_, pos) => {
-                if pos.is_orthogonal_to(new_pos) {
-                    let mut traveling = pos.orthogonals_to(new_pos);
-                    traveling.pop();
-
-                    for pos in traveling {
-                        if board.has_piece(pos) {
-                            return false;
-                        }
-                    }
-                    true
-                } else {
-                    false
-                }
+                rook_attacks(square_index(pos), board.bitboards().occupied()) & target != EMPTY
             }
 
             Self::Bishop(_, pos) => {
-                if pos.is_diagonal_to(new_pos) {
-                    let mut traveling = pos.diagonals_to(new_pos);
-                    traveling.pop();
-
-                    for pos in traveling {
-                        if board.has_piece(pos) {
-                            return false;
-                        }
-                    }
-                    true
-                } else {
-                    false
-                }
+                bishop_attacks(square_index(pos), board.bitboards().occupied()) & target != EMPTY
             }
 
-            Self::Knight(_, pos) => pos.is_knight_move(new_pos),
+            Self::Knight(_, pos) => KNIGHT_ATTACKS[square_index(pos)] & target != EMPTY,
         }
     }
 }
@@ -0,0 +1,344 @@
+//! Post-game review.
+//!
+//! Every position of a game is searched with [`Board::get_next_move`], so
+//! each move can be compared with the best one the engine finds: how many
+//! centipawns it gave away for the side that played it decides whether it
+//! was the best move, a good one, an inaccuracy, a mistake or a blunder.
+//! The evaluation after a move is the one of the next position, so a game
+//! of N moves takes N + 1 searches.
+//!
+//! Accuracy follows the formula popularised by Lichess: evaluations are
+//! turned into winning chances, and every move scores between 0 and 100 by
+//! how much of them it lost.
+
+use core::fmt;
+
+use crate::{Board, Color, Move, Pgn, Score, SearchLimits};
+
+/// Moves losing at least this many centipawns are inaccuracies.
+pub const INACCURACY_CENTIPAWNS: i32 = 50;
+/// Moves losing at least this many centipawns are mistakes.
+pub const MISTAKE_CENTIPAWNS: i32 = 100;
+/// Moves losing at least this many centipawns are blunders.
+pub const BLUNDER_CENTIPAWNS: i32 = 300;
+/// Evaluations are capped at this many centipawns, and forced mates count
+/// as this much. Beyond it a game is decided, so losing more is no worse.
+pub const MAX_REVIEW_CENTIPAWNS: i32 = 1_000;
+
+/// How a move compares to the best move in the position.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MoveClass {
+    /// The engine's choice, or one just as good.
+    Best,
+    /// Lost less than [`INACCURACY_CENTIPAWNS`].
+    Good,
+    Inaccuracy,
+    Mistake,
+    Blunder,
+}
+
+impl MoveClass {
+    /// Classify a move by the centipawns it lost.
+    pub fn from_loss(centipawns: i32) -> Self {
+        match centipawns {
+            i32::MIN..=0 => MoveClass::Best,
+            loss if loss >= BLUNDER_CENTIPAWNS => MoveClass::Blunder,
+            loss if loss >= MISTAKE_CENTIPAWNS => MoveClass::Mistake,
+            loss if loss >= INACCURACY_CENTIPAWNS => MoveClass::Inaccuracy,
+            _ => MoveClass::Good,
+        }
+    }
+}
+
+impl fmt::Display for MoveClass {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            MoveClass::Best => "best",
+            MoveClass::Good => "good",
+            MoveClass::Inaccuracy => "inaccuracy",
+            MoveClass::Mistake => "mistake",
+            MoveClass::Blunder => "blunder",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// The review of a single move.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MoveReview {
+    pub mv: Move,
+    /// The side that played the move.
+    pub color: Color,
+    /// The engine's choice in the position.
+    pub best_move: Move,
+    /// The evaluation before the move, for the side that played it.
+    pub before: Score,
+    /// The evaluation after the move, for the side that played it. Mates are
+    /// counted from before the move, like in `before`, so the best move
+    /// keeps the distance to mate.
+    pub after: Score,
+    /// The centipawns the move gave away, never negative.
+    pub loss: i32,
+    pub class: MoveClass,
+    /// Between 0 and 100, see [`move_accuracy`].
+    pub accuracy: f64,
+}
+
+/// The review of a whole game.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameReview {
+    pub moves: Vec<MoveReview>,
+    /// The average accuracy of White's moves, 100 if White made none.
+    pub white_accuracy: f64,
+    /// The average accuracy of Black's moves, 100 if Black made none.
+    pub black_accuracy: f64,
+}
+
+impl GameReview {
+    pub fn accuracy(&self, color: Color) -> f64 {
+        match color {
+            Color::White => self.white_accuracy,
+            Color::Black => self.black_accuracy,
+        }
+    }
+
+    /// How many of `color`'s moves fall into `class`.
+    pub fn count(&self, color: Color, class: MoveClass) -> usize {
+        self.moves
+            .iter()
+            .filter(|review| review.color == color && review.class == class)
+            .count()
+    }
+}
+
+/// A score in centipawns, capped at [`MAX_REVIEW_CENTIPAWNS`].
+fn centipawns(score: Score) -> i32 {
+    match score {
+        Score::Centipawns(cp) => cp.clamp(-MAX_REVIEW_CENTIPAWNS, MAX_REVIEW_CENTIPAWNS),
+        Score::Mate(moves) if moves > 0 => MAX_REVIEW_CENTIPAWNS,
+        Score::Mate(_) => -MAX_REVIEW_CENTIPAWNS,
+    }
+}
+
+/// The chance to win, between 0 and 100, of the side with an advantage of
+/// `centipawns`.
+pub fn winning_chances(centipawns: i32) -> f64 {
+    50.0 + 50.0 * (2.0 / (1.0 + (-0.00368208 * centipawns as f64).exp()) - 1.0)
+}
+
+/// The accuracy of a move, between 0 and 100, from the evaluations before
+/// and after it for the side that played it.
+pub fn move_accuracy(before: Score, after: Score) -> f64 {
+    let lost = winning_chances(centipawns(before)) - winning_chances(centipawns(after));
+    if lost <= 0.0 {
+        return 100.0;
+    }
+    (103.1668 * (-0.04354 * lost).exp() - 3.1669).clamp(0.0, 100.0)
+}
+
+/// The evaluation of `board` for the side to move, and the engine's move.
+fn evaluate(
+    board: &Board,
+    depths: &[u8],
+    seed: [u8; 32],
+    limits: &impl SearchLimits,
+    flags: u16,
+) -> (Score, Move) {
+    if board.is_checkmate() {
+        return (Score::Mate(0), Move::Resign);
+    }
+    if board.is_stalemate() {
+        return (Score::Centipawns(0), Move::Resign);
+    }
+    let result = board.get_next_move(depths, seed, limits, flags);
+    (result.score, result.best_move)
+}
+
+/// The mover's view of the score of the position after its move, with
+/// mates counted from before the move. [`evaluate`] scores being checkmated
+/// as `Mate(0)`.
+fn negate(score: Score) -> Score {
+    match score {
+        Score::Centipawns(cp) => Score::Centipawns(-cp),
+        // being mated now is the mover having mated
+        Score::Mate(0) => Score::Mate(1),
+        Score::Mate(moves) if moves > 0 => Score::Mate(-moves),
+        Score::Mate(moves) => Score::Mate(1 - moves),
+    }
+}
+
+/// Review `moves` played from `board`. Every position is searched like
+/// [`Board::get_next_move`] with the same `depths`, `seed` and `flags`;
+/// `limits` apply to each search separately. Reviewing stops at the first
+/// illegal move.
+pub fn review_game(
+    board: &Board,
+    moves: &[Move],
+    depths: &[u8],
+    seed: [u8; 32],
+    limits: &impl SearchLimits,
+    flags: u16,
+) -> GameReview {
+    let mut reviews = Vec::with_capacity(moves.len());
+    let mut board = *board;
+    let mut eval = evaluate(&board, depths, seed, limits, flags);
+    for &mv in moves {
+        if !board.is_legal_move(mv, board.get_turn_color()) {
+            break;
+        }
+        let (before, best_move) = eval;
+        let next = board.apply_eval_move(mv);
+        eval = evaluate(&next, depths, seed, limits, flags);
+        let after = negate(eval.0);
+
+        let loss = if mv == best_move {
+            0
+        } else {
+            (centipawns(before) - centipawns(after)).max(0)
+        };
+        reviews.push(MoveReview {
+            mv,
+            color: board.get_turn_color(),
+            best_move,
+            before,
+            after,
+            loss,
+            class: MoveClass::from_loss(loss),
+            // the best move counts as perfect even if the deeper search
+            // after it scores a little lower
+            accuracy: if loss == 0 {
+                100.0
+            } else {
+                move_accuracy(before, after)
+            },
+        });
+        board = next;
+    }
+
+    let average = |color: Color| {
+        let accuracies: Vec<f64> = reviews
+            .iter()
+            .filter(|review| review.color == color)
+            .map(|review| review.accuracy)
+            .collect();
+        if accuracies.is_empty() {
+            100.0
+        } else {
+            accuracies.iter().sum::<f64>() / accuracies.len() as f64
+        }
+    };
+    GameReview {
+        white_accuracy: average(Color::White),
+        black_accuracy: average(Color::Black),
+        moves: reviews,
+    }
+}
+
+/// Review a PGN game from its starting position, see [`review_game`].
+pub fn review_pgn(
+    game: &Pgn,
+    depths: &[u8],
+    seed: [u8; 32],
+    limits: &impl SearchLimits,
+    flags: u16,
+) -> GameReview {
+    let moves: Vec<Move> = game.get_moves().iter().map(|m| m.mv).collect();
+    review_game(game.get_start_board(), &moves, depths, seed, limits, flags)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        parse_fen, parse_pgn, parse_uci_move, NodeLimit, FLAG_MOVE_ORDERING, FLAG_QUIESCENCE,
+    };
+
+    const DEPTHS: [u8; 3] = [30, 30, 30];
+    const FLAGS: u16 = FLAG_MOVE_ORDERING | FLAG_QUIESCENCE;
+
+    fn review(board: &Board, ucis: &[&str]) -> GameReview {
+        let mut moves = Vec::new();
+        let mut position = *board;
+        for uci in ucis {
+            let m = parse_uci_move(&position, uci).unwrap();
+            moves.push(m);
+            position = position.apply_eval_move(m);
+        }
+        review_game(board, &moves, &DEPTHS, [0; 32], &NodeLimit(u64::MAX), FLAGS)
+    }
+
+    #[test]
+    fn classifies_by_centipawn_loss() {
+        assert_eq!(MoveClass::from_loss(-20), MoveClass::Best);
+        assert_eq!(MoveClass::from_loss(0), MoveClass::Best);
+        assert_eq!(MoveClass::from_loss(49), MoveClass::Good);
+        assert_eq!(MoveClass::from_loss(50), MoveClass::Inaccuracy);
+        assert_eq!(MoveClass::from_loss(100), MoveClass::Mistake);
+        assert_eq!(MoveClass::from_loss(900), MoveClass::Blunder);
+        assert_eq!(MoveClass::Inaccuracy.to_string(), "inaccuracy");
+
+        assert_eq!(winning_chances(0), 50.0);
+        assert!(winning_chances(300) > 70.0);
+        let even = Score::Centipawns(0);
+        assert_eq!(move_accuracy(even, Score::Centipawns(50)), 100.0);
+        assert!(move_accuracy(even, Score::Centipawns(-300)) < 50.0);
+        assert_eq!(move_accuracy(Score::Mate(2), Score::Mate(-3)), 0.0);
+    }
+
+    #[test]
+    fn negates_scores() {
+        assert_eq!(negate(Score::Centipawns(35)), Score::Centipawns(-35));
+        assert_eq!(negate(Score::Mate(0)), Score::Mate(1));
+        // mated in one after the move is having mated in two before it
+        assert_eq!(negate(Score::Mate(-1)), Score::Mate(2));
+        assert_eq!(negate(Score::Mate(2)), Score::Mate(-2));
+    }
+
+    #[test]
+    fn reviews_a_hanging_queen() {
+        // 1. Qd4?? hangs the queen to the knight, 1... Nxd4 takes it
+        let board = parse_fen("4k3/8/2n5/8/8/8/3Q4/4K3 w - - 0 1").unwrap();
+        let review = review(&board, &["d2d4", "c6d4"]);
+        assert_eq!(review.moves.len(), 2);
+        let blunder = &review.moves[0];
+        assert_eq!(blunder.color, Color::White);
+        assert_eq!(blunder.class, MoveClass::Blunder);
+        assert_ne!(blunder.best_move, blunder.mv);
+        assert!(blunder.loss >= BLUNDER_CENTIPAWNS);
+        assert_eq!(review.moves[1].class, MoveClass::Best);
+        assert_eq!(review.moves[1].accuracy, 100.0);
+        assert!(review.white_accuracy < 50.0);
+        assert_eq!(review.count(Color::White, MoveClass::Blunder), 1);
+        assert_eq!(review.accuracy(Color::Black), review.black_accuracy);
+    }
+
+    #[test]
+    fn reviews_a_mate() {
+        // back rank mate
+        let board = parse_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+        let review = review(&board, &["a1a8"]);
+        let mate = &review.moves[0];
+        assert_eq!(mate.class, MoveClass::Best);
+        assert_eq!((mate.before, mate.after), (Score::Mate(1), Score::Mate(1)));
+        assert_eq!(review.white_accuracy, 100.0);
+        // Black made no move
+        assert_eq!(review.black_accuracy, 100.0);
+    }
+
+    #[test]
+    fn reviews_pgn_games() {
+        let game = parse_pgn("1. e4 e5 2. Qh5 Nc6 3. Bc4 Nf6 4. Qxf7# 1-0").unwrap();
+        let review = review_pgn(&game, &DEPTHS, [0; 32], &NodeLimit(u64::MAX), FLAGS);
+        assert_eq!(review.moves.len(), 7);
+        // 3... Nf6?? allows mate in one
+        assert_eq!(review.moves[5].class, MoveClass::Blunder);
+        assert_eq!(review.moves[6].after, Score::Mate(1));
+        assert!(review.white_accuracy > review.black_accuracy);
+
+        // stops at the first illegal move
+        let board = Board::default();
+        let e4 = parse_uci_move(&board, "e2e4").unwrap();
+        let partial = review_game(&board, &[e4, e4], &DEPTHS, [0; 32], &NodeLimit(0), FLAGS);
+        assert_eq!(partial.moves.len(), 1);
+    }
+}
@@ -0,0 +1,692 @@
+use crate::{board::MATE, Move};
+use core::fmt;
+use near_sdk::{
+    env,
+    serde::{Deserialize, Serialize},
+    Gas, NearSchema,
+};
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{Duration, Instant};
+
+/// When a search has to stop.
+///
+/// The search polls [`SearchLimits::is_exhausted`] before each root move and
+/// only keeps the result of an iteration that finished in time, so a limit
+/// may be overshot by the search of one root move. Limits can be combined as
+/// a tuple, which is exhausted as soon as either limit is.
+pub trait SearchLimits {
+    /// Whether the search must stop after evaluating `board_count` boards.
+    fn is_exhausted(&self, board_count: u64) -> bool;
+
+    /// The most plies to search, or `None` to search the whole depth list.
+    fn max_depth(&self) -> Option<usize> {
+        None
+    }
+
+    /// The gas used so far, or `None` if the limits do not count gas.
+    fn used_gas(&self) -> Option<Gas> {
+        None
+    }
+}
+
+/// Stop once the contract call has used this much gas. Only meaningful
+/// inside the NEAR runtime.
+#[derive(Clone, Copy, Debug)]
+pub struct GasLimit(pub Gas);
+
+impl SearchLimits for GasLimit {
+    fn is_exhausted(&self, _board_count: u64) -> bool {
+        env::used_gas() >= self.0
+    }
+
+    fn used_gas(&self) -> Option<Gas> {
+        Some(env::used_gas())
+    }
+}
+
+/// Stop after evaluating this many boards. Deterministic, so the same search
+/// gives the same result everywhere.
+#[derive(Clone, Copy, Debug)]
+pub struct NodeLimit(pub u64);
+
+impl SearchLimits for NodeLimit {
+    fn is_exhausted(&self, board_count: u64) -> bool {
+        board_count >= self.0
+    }
+}
+
+/// Search at most this many plies of the depth list.
+#[derive(Clone, Copy, Debug)]
+pub struct DepthLimit(pub usize);
+
+impl SearchLimits for DepthLimit {
+    fn is_exhausted(&self, _board_count: u64) -> bool {
+        false
+    }
+
+    fn max_depth(&self) -> Option<usize> {
+        Some(self.0)
+    }
+}
+
+/// Stop at a wall-clock deadline. Not available on-chain, where there is no
+/// clock.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Clone, Copy, Debug)]
+pub struct TimeLimit(pub Instant);
+
+#[cfg(not(target_arch = "wasm32"))]
+impl TimeLimit {
+    /// A deadline `duration` from now.
+    pub fn new(duration: Duration) -> Self {
+        Self(Instant::now() + duration)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl SearchLimits for TimeLimit {
+    fn is_exhausted(&self, _board_count: u64) -> bool {
+        Instant::now() >= self.0
+    }
+}
+
+/// Stop once the flag is set, e.g. by another thread.
+impl SearchLimits for AtomicBool {
+    fn is_exhausted(&self, _board_count: u64) -> bool {
+        self.load(Ordering::Relaxed)
+    }
+}
+
+/// No limit if `None`.
+impl<L: SearchLimits> SearchLimits for Option<L> {
+    fn is_exhausted(&self, board_count: u64) -> bool {
+        self.as_ref()
+            .is_some_and(|limits| limits.is_exhausted(board_count))
+    }
+
+    fn max_depth(&self) -> Option<usize> {
+        self.as_ref().and_then(L::max_depth)
+    }
+
+    fn used_gas(&self) -> Option<Gas> {
+        self.as_ref().and_then(L::used_gas)
+    }
+}
+
+impl<L: SearchLimits + ?Sized> SearchLimits for &L {
+    fn is_exhausted(&self, board_count: u64) -> bool {
+        (**self).is_exhausted(board_count)
+    }
+
+    fn max_depth(&self) -> Option<usize> {
+        (**self).max_depth()
+    }
+
+    fn used_gas(&self) -> Option<Gas> {
+        (**self).used_gas()
+    }
+}
+
+impl<A: SearchLimits, B: SearchLimits> SearchLimits for (A, B) {
+    fn is_exhausted(&self, board_count: u64) -> bool {
+        self.0.is_exhausted(board_count) || self.1.is_exhausted(board_count)
+    }
+
+    fn max_depth(&self) -> Option<usize> {
+        match (self.0.max_depth(), self.1.max_depth()) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+
+    fn used_gas(&self) -> Option<Gas> {
+        self.0.used_gas().or_else(|| self.1.used_gas())
+    }
+}
+
+/// The prefix of `depths` that `limits` allow, always at least the root.
+pub(crate) fn limit_depths<'a>(depths: &'a [u8], limits: &impl SearchLimits) -> &'a [u8] {
+    match limits.max_depth() {
+        Some(max) => &depths[..depths.len().min(max.max(1))],
+        None => depths,
+    }
+}
+
+/// The outcome of [`Board::get_next_move`](crate::Board::get_next_move).
+#[derive(Clone, Debug, PartialEq)]
+pub struct SearchResult {
+    /// The move to play. `Move::Resign` if there is no legal move.
+    pub best_move: Move,
+    /// The number of boards evaluated to come to a conclusion.
+    pub board_count: u64,
+    /// The raw search value of `best_move` for the side to move, converted
+    /// with [`value_to_float`].
+    pub value: f64,
+    /// The line the search expects, starting with `best_move`. It is read
+    /// back from the transposition table, so it can be shorter than the
+    /// search depth if entries were evicted.
+    pub pv: Vec<Move>,
+    /// `value` as centipawns or a forced mate.
+    pub score: Score,
+    /// What the search did to get there.
+    pub stats: SearchStats,
+}
+
+/// Counters collected by [`Board::get_next_move`](crate::Board::get_next_move),
+/// for tuning the difficulties.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SearchStats {
+    /// Boards evaluated, quiescence nodes included. The same as
+    /// [`SearchResult::board_count`].
+    pub nodes: u64,
+    /// Boards evaluated by the quiescence search.
+    pub qnodes: u64,
+    /// Transposition table lookups, one per internal node.
+    pub tt_probes: u64,
+    /// Lookups that found an entry, whether or not it was deep enough to use.
+    pub tt_hits: u64,
+    /// Nodes of the main search where a move failed high.
+    pub beta_cutoffs: u64,
+    /// Beta cutoffs by the first move searched, a measure of move ordering.
+    pub first_move_cutoffs: u64,
+    /// Plies of the deepest search of all root moves that completed within
+    /// the limits, 0 if none did. Without iterative deepening this is
+    /// either the whole depth list or nothing.
+    pub completed_depth: u8,
+    /// The gas each iteration used, in order, the last one included if the
+    /// limits cut it short. Only recorded if the limits count gas, see
+    /// [`SearchLimits::used_gas`].
+    pub iteration_gas: Vec<u64>,
+    /// Whether the limits stopped the search before the whole depth list
+    /// was searched.
+    pub aborted: bool,
+}
+
+impl SearchStats {
+    /// The share of beta cutoffs that came from the first move, from 0 to 1.
+    pub fn first_move_cutoff_rate(&self) -> f64 {
+        if self.beta_cutoffs == 0 {
+            return 0.0;
+        }
+        self.first_move_cutoffs as f64 / self.beta_cutoffs as f64
+    }
+}
+
+/// A one-line summary for logs, e.g.
+/// `depth 3 nodes 5210 qnodes 1877 tt 402/1190 cutoffs 733 first 91% gas 1.2,8.4 aborted`,
+/// with the gas per iteration in Tgas.
+impl fmt::Display for SearchStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "depth {} nodes {} qnodes {} tt {}/{} cutoffs {} first {:.0}%",
+            self.completed_depth,
+            self.nodes,
+            self.qnodes,
+            self.tt_hits,
+            self.tt_probes,
+            self.beta_cutoffs,
+            self.first_move_cutoff_rate() * 100.0
+        )?;
+        if !self.iteration_gas.is_empty() {
+            let gas: Vec<String> = self
+                .iteration_gas
+                .iter()
+                .map(|&gas| format!("{:.1}", gas as f64 / 1e12))
+                .collect();
+            write!(f, " gas {}", gas.join(","))?;
+        }
+        if self.aborted {
+            write!(f, " aborted")?;
+        }
+        Ok(())
+    }
+}
+
+/// One of the ranked moves of [`Board::get_multi_pv`](crate::Board::get_multi_pv).
+#[derive(Clone, Debug, PartialEq)]
+pub struct PvLine {
+    pub mv: Move,
+    /// The raw search value of `mv`, like [`SearchResult::value`].
+    pub value: f64,
+    pub score: Score,
+    /// The line the search expects after `mv`, starting with `mv`.
+    pub pv: Vec<Move>,
+}
+
+/// A search value in the units chess tools report, from the point of view of
+/// the side to move.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize, NearSchema)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum Score {
+    /// Advantage in hundredths of a pawn.
+    Centipawns(i32),
+    /// Mate in this many moves, negative if the side to move gets mated.
+    Mate(i32),
+}
+
+impl Score {
+    /// Convert a search value in centipawns, where mates are encoded as
+    /// `MATE - ply`.
+    pub fn from_value(value: i32) -> Self {
+        if is_mate_value(value) {
+            let ply = MATE - value.abs();
+            if value > 0 {
+                Score::Mate((ply + 1) / 2)
+            } else {
+                Score::Mate(-ply / 2)
+            }
+        } else {
+            Score::Centipawns(value)
+        }
+    }
+}
+
+/// Is this search value a forced mate for either side?
+pub fn is_mate_value(value: i32) -> bool {
+    value.abs() >= MATE / 2
+}
+
+/// Mate values count plies from the root, but the same position can be
+/// reached at a different ply through a transposition. The transposition
+/// table stores them counted from the node at `ply` instead.
+pub(crate) fn value_to_tt(value: i32, ply: u8) -> i32 {
+    if value >= MATE / 2 {
+        value + ply as i32
+    } else if value <= -MATE / 2 {
+        value - ply as i32
+    } else {
+        value
+    }
+}
+
+/// The inverse of [`value_to_tt`] for a node at `ply`.
+pub(crate) fn value_from_tt(value: i32, ply: u8) -> i32 {
+    if value >= MATE / 2 {
+        value - ply as i32
+    } else if value <= -MATE / 2 {
+        value + ply as i32
+    } else {
+        value
+    }
+}
+
+/// Convert a search value to the float scale of [`SearchResult::value`],
+/// on which a pawn is worth 10, as the search used before it switched to
+/// integer centipawns. Mates keep their `MATE - ply` encoding.
+pub fn value_to_float(value: i32) -> f64 {
+    if is_mate_value(value) {
+        value as f64
+    } else {
+        value as f64 / 10.0
+    }
+}
+
+/// The search value in centipawns of a float from [`value_to_float`].
+pub fn value_from_float(value: f64) -> i32 {
+    if value.abs() >= (MATE / 2) as f64 {
+        value.round() as i32
+    } else {
+        (value * 10.0).round() as i32
+    }
+}
+
+/// Formats the score like the UCI `info score` field, e.g. `cp 35` or
+/// `mate -2`.
+impl fmt::Display for Score {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Score::Centipawns(cp) => write!(f, "cp {}", cp),
+            Score::Mate(moves) => write!(f, "mate {}", moves),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        parse_fen, Board, Position, FLAG_ASPIRATION_WINDOWS, FLAG_HISTORY_HEURISTIC,
+        FLAG_ITERATIVE_DEEPENING, FLAG_KILLER_HEURISTIC, FLAG_MOVE_ORDERING,
+        FLAG_PRINCIPAL_VARIATION_SEARCH, FLAG_QUIESCENCE,
+    };
+
+    fn search(fen: &str, depths: &[u8], flags: u16) -> (Board, SearchResult) {
+        let board = parse_fen(fen).unwrap();
+        let result = board.get_next_move(depths, [0; 32], &NodeLimit(u64::MAX), flags);
+        (board, result)
+    }
+
+    #[test]
+    fn score_from_value() {
+        assert_eq!(Score::from_value(0), Score::Centipawns(0));
+        assert_eq!(Score::from_value(-123), Score::Centipawns(-123));
+        // mate on the first ply is mate in one, mated after two plies is
+        // getting mated in one
+        assert_eq!(Score::from_value(MATE - 1), Score::Mate(1));
+        assert_eq!(Score::from_value(MATE - 3), Score::Mate(2));
+        assert_eq!(Score::from_value(-MATE + 2), Score::Mate(-1));
+        // the float scale counts a pawn as 10 and leaves mates alone
+        assert_eq!(value_to_float(-123), -12.3);
+        assert_eq!(value_to_float(MATE - 3), 999_997.0);
+        for value in [0, -123, 4567, MATE - 3, -MATE + 2] {
+            assert_eq!(value_from_float(value_to_float(value)), value);
+        }
+        assert_eq!(Score::Mate(-1).to_string(), "mate -1");
+        assert_eq!(Score::Centipawns(35).to_string(), "cp 35");
+    }
+
+    #[test]
+    fn reports_mate_and_the_mating_line() {
+        // back rank mate: Ra8#
+        let (board, result) = search("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", &[30, 30, 30], 0);
+        let mate = Move::Piece(Position::new(0, 0), Position::new(7, 0));
+        assert_eq!(result.best_move, mate);
+        assert_eq!(result.score, Score::Mate(1));
+        assert_eq!(result.pv, vec![mate]);
+        assert!(board.apply_eval_move(mate).is_checkmate());
+
+        // the side getting mated sees it coming: Kb8 is forced, then Rh8#
+        let (_, result) = search("k7/8/1K6/8/8/8/8/7R b - - 0 1", &[30, 30, 30], 0);
+        assert_eq!(result.score, Score::Mate(-1));
+        assert_eq!(result.pv.len(), 2);
+    }
+
+    #[test]
+    fn mate_distance_through_a_transposition() {
+        use crate::transposition_table::TranspositionTable;
+        use crate::{MoveOrdering, BLACK, WHITE};
+        use either::Either;
+
+        // the same position searched at the root and again as if reached
+        // through a transposition three plies deeper, reusing the table
+        let search_at = |fen: &str, depth: u8, color, ply: u8| {
+            let board = parse_fen(fen).unwrap();
+            let mut tt = TranspositionTable::new(1 << 10);
+            let mut ordering = MoveOrdering::new();
+            let mut board_count = 0;
+            let mut search = |ply| {
+                board.minimax(
+                    &mut tt,
+                    Either::Left(depth),
+                    -2 * MATE,
+                    2 * MATE,
+                    true,
+                    color,
+                    &mut board_count,
+                    0,
+                    ply,
+                    &mut ordering,
+                )
+            };
+            (search(0), search(ply))
+        };
+
+        // Ra8# in one
+        let (root, deeper) = search_at("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", 2, WHITE, 3);
+        assert_eq!(root, MATE - 1);
+        assert_eq!(deeper, MATE - 4);
+
+        // Kb8 is forced, then Rh8#
+        let (root, deeper) = search_at("k7/8/1K6/8/8/8/8/7R b - - 0 1", 3, BLACK, 3);
+        assert_eq!(root, -MATE + 2);
+        assert_eq!(deeper, -MATE + 5);
+    }
+
+    #[test]
+    fn sampled_values_do_not_cut_off_wider_searches() {
+        use crate::transposition_table::TranspositionTable;
+        use crate::{MoveOrdering, WHITE};
+        use either::Either;
+        use rand::SeedableRng;
+        use rand_chacha::ChaCha20Rng;
+
+        // only Ra8 mates, one of 15 moves
+        let board = parse_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+        let mut tt = TranspositionTable::new(1 << 10);
+        let mut ordering = MoveOrdering::new();
+        let mut board_count = 0;
+        let mut search = |widths: &[u8]| {
+            board.minimax(
+                &mut tt,
+                Either::Right((widths, ChaCha20Rng::seed_from_u64(1))),
+                -2 * MATE,
+                2 * MATE,
+                true,
+                WHITE,
+                &mut board_count,
+                0,
+                0,
+                &mut ordering,
+            )
+        };
+        let narrow = search(&[1, 1]);
+        assert_ne!(narrow, MATE - 1);
+        // the narrow sample is stored, but must not answer the full search
+        assert_eq!(search(&[30, 30]), MATE - 1);
+        assert_eq!(search(&[1, 1]), MATE - 1);
+    }
+
+    #[test]
+    fn a_kept_table_carries_over_to_the_next_search() {
+        use crate::transposition_table::TranspositionTable;
+        use crate::PositionHistory;
+
+        let board =
+            parse_fen("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3").unwrap();
+        let history = PositionHistory::new(&board);
+        let depths = [60, 60, 60];
+        let flags = FLAG_MOVE_ORDERING;
+        let mut tt = TranspositionTable::new(1 << 16);
+        let first = board.get_next_move_in_game(
+            &history,
+            &mut tt,
+            &depths,
+            [0; 32],
+            &NodeLimit(u64::MAX),
+            flags,
+        );
+        let again = board.get_next_move_in_game(
+            &history,
+            &mut tt,
+            &depths,
+            [0; 32],
+            &NodeLimit(u64::MAX),
+            flags,
+        );
+        assert_eq!(again.best_move, first.best_move);
+        assert_eq!(again.score, first.score);
+        assert!(again.stats.nodes < first.stats.nodes / 2);
+    }
+
+    #[test]
+    fn root_moves_that_repeat_a_third_time_are_draws() {
+        use crate::transposition_table::TranspositionTable;
+        use crate::{GameResult, PositionHistory};
+
+        let mut board = parse_fen("k7/8/8/8/8/7Q/8/7K w - - 0 1").unwrap();
+        let mut history = PositionHistory::new(&board);
+        let mv = |from: &str, to: &str| {
+            Move::Piece(Position::pgn(from).unwrap(), Position::pgn(to).unwrap())
+        };
+        let shuffle = [
+            mv("h1", "g1"),
+            mv("a8", "b8"),
+            mv("g1", "h1"),
+            mv("b8", "a8"),
+        ];
+        // Kb8a8 would bring back the start position for the third time
+        for m in shuffle.iter().chain(&shuffle[..3]) {
+            match board.play_move_with_history(*m, &mut history) {
+                GameResult::Continuing(next) => board = next,
+                result => panic!("{:?}", result),
+            }
+        }
+
+        let depths = [30, 30];
+        let flags = FLAG_MOVE_ORDERING;
+        let lost = board.get_next_move(&depths, [0; 32], &NodeLimit(u64::MAX), flags);
+        assert!(matches!(lost.score, Score::Centipawns(cp) if cp < 0));
+        let mut tt = TranspositionTable::new(1 << 10);
+        let result = board.get_next_move_in_game(
+            &history,
+            &mut tt,
+            &depths,
+            [0; 32],
+            &NodeLimit(u64::MAX),
+            flags,
+        );
+        assert_eq!(result.best_move, mv("b8", "a8"));
+        assert_eq!(result.score, Score::Centipawns(0));
+    }
+
+    #[test]
+    fn pv_is_a_legal_line_starting_with_the_best_move() {
+        let fen = "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3";
+        let (board, result) = search(fen, &[30, 30, 30, 30], FLAG_MOVE_ORDERING | FLAG_QUIESCENCE);
+        assert_eq!(result.pv[0], result.best_move);
+        assert!(result.pv.len() > 1 && result.pv.len() <= 4);
+        let mut board = board;
+        for &m in &result.pv {
+            assert!(board.get_legal_moves().any(|legal| legal == m));
+            board = board.apply_eval_move(m);
+        }
+        assert!(matches!(result.score, Score::Centipawns(_)));
+    }
+
+    #[test]
+    fn multi_pv_ranks_root_moves() {
+        // the queen on d5 hangs to the knight on c3
+        let board = parse_fen("4k3/8/8/r2q4/8/2N5/8/4K3 w - - 0 1").unwrap();
+        let depths = [30, 30, 30];
+        let flags = FLAG_MOVE_ORDERING | FLAG_QUIESCENCE;
+        let lines = board.get_multi_pv(3, &depths, [0; 32], &NodeLimit(u64::MAX), flags);
+        assert_eq!(lines.len(), 3);
+        let takes_queen = Move::Piece(Position::new(2, 2), Position::new(4, 3));
+        assert_eq!(lines[0].mv, takes_queen);
+        assert!(lines.windows(2).all(|w| w[0].value >= w[1].value));
+        for line in &lines {
+            assert_eq!(line.pv[0], line.mv);
+            assert_eq!(line.score, Score::from_value(value_from_float(line.value)));
+        }
+
+        // the best line agrees with the single best move search
+        let best = board.get_next_move(&depths, [0; 32], &NodeLimit(u64::MAX), flags);
+        assert_eq!(best.best_move, lines[0].mv);
+        assert_eq!(best.value, lines[0].value);
+        assert_eq!(best.pv, lines[0].pv);
+
+        // every legal move is ranked if asked for more lines than there are
+        let all = board.get_multi_pv(100, &depths, [0; 32], &NodeLimit(u64::MAX), flags);
+        assert_eq!(all.len(), board.get_legal_moves().count());
+
+        // a node limit still ranks at least one move
+        let limited = board.get_multi_pv(3, &depths, [0; 32], &NodeLimit(0), flags);
+        assert_eq!(limited.len(), 1);
+    }
+
+    #[test]
+    fn limits() {
+        let board =
+            parse_fen("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3").unwrap();
+        let depths = [30, 30, 30, 30];
+        let flags = FLAG_MOVE_ORDERING | FLAG_QUIESCENCE | FLAG_ITERATIVE_DEEPENING;
+        let full = board.get_next_move(&depths, [0; 32], &NodeLimit(u64::MAX), flags);
+
+        // node limits are deterministic
+        let limited = board.get_next_move(&depths, [0; 32], &NodeLimit(2_000), flags);
+        let again = board.get_next_move(&depths, [0; 32], &NodeLimit(2_000), flags);
+        assert_eq!(limited, again);
+        assert!(limited.board_count < full.board_count);
+
+        // a depth limit searches the same as a shorter depth list
+        let shallow = board.get_next_move(&depths[..2], [0; 32], &NodeLimit(u64::MAX), flags);
+        let capped = board.get_next_move(&depths, [0; 32], &DepthLimit(2), flags);
+        assert_eq!(shallow, capped);
+        let combined = (NodeLimit(u64::MAX), DepthLimit(2));
+        assert_eq!(combined.max_depth(), Some(2));
+        assert_eq!(
+            board.get_next_move(&depths, [0; 32], &combined, flags),
+            capped
+        );
+
+        // a set stop flag or a past deadline stops before searching anything,
+        // but there is still a move to play
+        let stopped = board.get_next_move(&depths, [0; 32], &AtomicBool::new(true), flags);
+        assert_eq!(stopped.board_count, 0);
+        assert!(board.get_legal_moves().any(|m| m == stopped.best_move));
+        let late = board.get_next_move(&depths, [0; 32], &TimeLimit::new(Duration::ZERO), flags);
+        assert_eq!(late.board_count, 0);
+        assert!((&None::<NodeLimit>, GasLimit(Gas::from_tgas(300)))
+            .max_depth()
+            .is_none());
+    }
+
+    #[test]
+    fn search_stats() {
+        let board =
+            parse_fen("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3").unwrap();
+        let depths = [30, 30, 30, 30];
+        let flags = FLAG_MOVE_ORDERING | FLAG_QUIESCENCE | FLAG_ITERATIVE_DEEPENING;
+        let full = board.get_next_move(&depths, [0; 32], &NodeLimit(u64::MAX), flags);
+        let stats = &full.stats;
+        assert_eq!(stats.nodes, full.board_count);
+        assert!(stats.qnodes > 0 && stats.qnodes < stats.nodes);
+        assert!(stats.tt_hits > 0 && stats.tt_hits <= stats.tt_probes);
+        assert!(stats.beta_cutoffs > 0 && stats.first_move_cutoffs <= stats.beta_cutoffs);
+        assert!((0.0..=1.0).contains(&stats.first_move_cutoff_rate()));
+        assert_eq!(stats.completed_depth, 4);
+        // node limits do not count gas
+        assert!(stats.iteration_gas.is_empty());
+        assert!(!stats.aborted);
+
+        // the iterations to depth 2, 3 and 4
+        let limits = (NodeLimit(u64::MAX), GasLimit(Gas::from_tgas(300)));
+        let gas = board.get_next_move(&depths, [0; 32], &limits, flags);
+        assert_eq!(gas.stats.iteration_gas.len(), 3);
+
+        let limited = board.get_next_move(&depths, [0; 32], &NodeLimit(2_000), flags);
+        assert!(limited.stats.aborted);
+        assert!(limited.stats.completed_depth < 4);
+        let summary = limited.stats.to_string();
+        assert!(summary.starts_with(&format!(
+            "depth {} nodes {} ",
+            limited.stats.completed_depth, limited.board_count
+        )));
+        assert!(summary.ends_with(" aborted"));
+    }
+
+    #[test]
+    fn search_enhancements_agree_with_plain_alpha_beta() {
+        // the queen on d5 hangs to the knight on c3
+        let board = parse_fen("4k3/8/8/r2q4/8/2N5/8/4K3 w - - 0 1").unwrap();
+        let depths = [30, 30, 30, 30];
+        let base =
+            FLAG_MOVE_ORDERING | FLAG_QUIESCENCE | FLAG_ITERATIVE_DEEPENING | FLAG_KILLER_HEURISTIC;
+        let plain = board.get_next_move(&depths, [0; 32], &NodeLimit(u64::MAX), base);
+        let takes_queen = Move::Piece(Position::new(2, 2), Position::new(4, 3));
+        assert_eq!(plain.best_move, takes_queen);
+        for flag in [
+            FLAG_PRINCIPAL_VARIATION_SEARCH,
+            FLAG_ASPIRATION_WINDOWS,
+            FLAG_HISTORY_HEURISTIC,
+            FLAG_PRINCIPAL_VARIATION_SEARCH | FLAG_ASPIRATION_WINDOWS | FLAG_HISTORY_HEURISTIC,
+        ] {
+            let result = board.get_next_move(&depths, [0; 32], &NodeLimit(u64::MAX), base | flag);
+            assert_eq!(result.best_move, plain.best_move);
+            assert_eq!(result.score, plain.score);
+        }
+
+        // the narrow window around the last iteration's score prunes far
+        // more than the full one
+        let aspiration = board.get_next_move(
+            &depths,
+            [0; 32],
+            &NodeLimit(u64::MAX),
+            base | FLAG_ASPIRATION_WINDOWS,
+        );
+        assert!(aspiration.board_count < plain.board_count);
+    }
+}
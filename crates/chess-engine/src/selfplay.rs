@@ -0,0 +1,441 @@
+//! Self-play matches between AI settings.
+//!
+//! Two [`AiSettings`] play each other with [`get_ai_move`], the way the
+//! contract's AI plays, from varied openings: every opening is played twice
+//! with colors swapped, so neither side profits from a lopsided one. Gas
+//! only exists on-chain, so moves are limited by nodes instead.
+//!
+//! [`MatchResult`] turns the results into an Elo difference with a 95%
+//! confidence interval, and an [`Sprt`] can stop a match as soon as it is
+//! clear whether a change gained strength.
+
+use rand::{seq::IndexedRandom, RngExt, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+use crate::{
+    get_ai_move, static_book::lookup_opening, Board, Color, GameResult, Move, NodeLimit, Pgn,
+    PgnResult, PositionHistory,
+};
+
+/// The z-score of a two-sided 95% confidence interval.
+const Z_95: f64 = 1.959964;
+
+/// How one side of a match picks its moves.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AiSettings {
+    pub name: String,
+    pub flags: u16,
+    /// The sampling widths per ply, like a difficulty's depth list.
+    pub max_depths: Vec<u8>,
+}
+
+impl AiSettings {
+    pub fn new(name: &str, flags: u16, max_depths: &[u8]) -> Self {
+        Self {
+            name: name.to_string(),
+            flags,
+            max_depths: max_depths.to_vec(),
+        }
+    }
+}
+
+/// Play a game from `opening`, which must be legal from the starting
+/// position. The settings get `nodes` boards to evaluate per move. Games
+/// still going after `max_plies` plies are adjudicated as draws.
+pub fn play_game(
+    white: &AiSettings,
+    black: &AiSettings,
+    opening: &[Move],
+    seed: u64,
+    nodes: u64,
+    max_plies: usize,
+) -> Pgn {
+    let mut pgn = Pgn::new();
+    pgn.set_tag("White", &white.name);
+    pgn.set_tag("Black", &black.name);
+    let mut history = PositionHistory::new(pgn.get_board());
+    let mut rng = ChaCha20Rng::seed_from_u64(seed);
+
+    for ply in 0..max_plies {
+        let board = *pgn.get_board();
+        let mv = match opening.get(ply) {
+            Some(&mv) => mv,
+            None => {
+                let settings = match board.get_turn_color() {
+                    Color::White => white,
+                    Color::Black => black,
+                };
+                get_ai_move(
+                    &board,
+                    &settings.max_depths,
+                    rng.random(),
+                    &NodeLimit(nodes),
+                    settings.flags,
+                )
+            }
+        };
+        let result = board.play_move_with_history(mv, &mut history);
+        if matches!(result, GameResult::IllegalMove(_)) || mv == Move::Resign {
+            // resigning or an illegal move loses
+            pgn.set_result(match board.get_turn_color() {
+                Color::White => PgnResult::BlackWins,
+                Color::Black => PgnResult::WhiteWins,
+            });
+            return pgn;
+        }
+        // sets the result on checkmate and stalemate
+        pgn.play_move(mv).expect("the move is legal");
+        match result {
+            GameResult::Continuing(_) => {}
+            GameResult::ThreefoldRepetition | GameResult::FiftyMoveRule => {
+                pgn.set_result(PgnResult::Draw);
+                return pgn;
+            }
+            _ => return pgn,
+        }
+    }
+    pgn.set_result(PgnResult::Draw);
+    pgn
+}
+
+/// A random opening of `plies` plies: book moves where the opening book
+/// knows the position, random legal moves elsewhere.
+pub fn random_opening(seed: u64, plies: usize) -> Vec<Move> {
+    let mut rng = ChaCha20Rng::seed_from_u64(seed);
+    let mut board = Board::default();
+    let mut opening = Vec::with_capacity(plies);
+    for _ in 0..plies {
+        let legal: Vec<Move> = board.get_legal_moves().collect();
+        let book =
+            lookup_opening(board.zobrist_key(), rng.random()).filter(|mv| legal.contains(mv));
+        let Some(mv) = book.or_else(|| legal.choose(&mut rng).copied()) else {
+            break;
+        };
+        match board.play_move(mv) {
+            GameResult::Continuing(next) => board = next,
+            _ => break,
+        }
+        opening.push(mv);
+    }
+    opening
+}
+
+/// Wins, draws and losses of the first settings of a match.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MatchResult {
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+}
+
+/// An Elo difference with its 95% confidence interval.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EloEstimate {
+    pub elo: f64,
+    pub low: f64,
+    pub high: f64,
+}
+
+/// The Elo difference at which the stronger side scores `score`.
+fn elo_from_score(score: f64) -> f64 {
+    let score = score.clamp(1e-6, 1.0 - 1e-6);
+    400.0 * (score / (1.0 - score)).log10()
+}
+
+/// The expected score of the side that is `elo` stronger.
+fn score_from_elo(elo: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-elo / 400.0))
+}
+
+impl MatchResult {
+    pub fn games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+
+    /// Record a game from the point of view of `color`, the color the first
+    /// settings played.
+    pub fn add(&mut self, result: PgnResult, color: Color) {
+        match (result, color) {
+            (PgnResult::WhiteWins, Color::White) | (PgnResult::BlackWins, Color::Black) => {
+                self.wins += 1
+            }
+            (PgnResult::WhiteWins, Color::Black) | (PgnResult::BlackWins, Color::White) => {
+                self.losses += 1
+            }
+            (PgnResult::Draw, _) | (PgnResult::Unknown, _) => self.draws += 1,
+        }
+    }
+
+    /// The average points per game, between 0 and 1.
+    pub fn score(&self) -> f64 {
+        if self.games() == 0 {
+            return 0.5;
+        }
+        (self.wins as f64 + self.draws as f64 / 2.0) / self.games() as f64
+    }
+
+    /// The variance of the points of a single game.
+    fn variance(&self) -> f64 {
+        let score = self.score();
+        let games = self.games().max(1) as f64;
+        (self.wins as f64 * (1.0 - score).powi(2)
+            + self.draws as f64 * (0.5 - score).powi(2)
+            + self.losses as f64 * score.powi(2))
+            / games
+    }
+
+    /// The Elo difference to the other settings. Without any wins or losses
+    /// on one side the interval is bounded at ±1200.
+    pub fn elo(&self) -> EloEstimate {
+        let score = self.score();
+        let margin = Z_95 * (self.variance() / self.games().max(1) as f64).sqrt();
+        let bound = |score: f64| elo_from_score(score).clamp(-1200.0, 1200.0);
+        EloEstimate {
+            elo: bound(score),
+            low: bound(score - margin),
+            high: bound(score + margin),
+        }
+    }
+}
+
+/// Whether a sequential probability ratio test can stop.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SprtStatus {
+    Continue,
+    /// The first settings are no better than `elo0`.
+    AcceptH0,
+    /// The first settings are at least `elo1` better.
+    AcceptH1,
+}
+
+/// A sequential probability ratio test of `elo0` against `elo1`, with false
+/// positive rate `alpha` and false negative rate `beta`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sprt {
+    pub elo0: f64,
+    pub elo1: f64,
+    pub alpha: f64,
+    pub beta: f64,
+}
+
+impl Default for Sprt {
+    /// Is the change worth at least 10 Elo, or nothing at all?
+    fn default() -> Self {
+        Self {
+            elo0: 0.0,
+            elo1: 10.0,
+            alpha: 0.05,
+            beta: 0.05,
+        }
+    }
+}
+
+impl Sprt {
+    /// The log-likelihood ratio of `elo1` over `elo0`, using the normal
+    /// approximation of the game results.
+    pub fn llr(&self, result: &MatchResult) -> f64 {
+        let variance = result.variance();
+        if result.games() == 0 || variance == 0.0 {
+            return 0.0;
+        }
+        let (s0, s1) = (score_from_elo(self.elo0), score_from_elo(self.elo1));
+        result.games() as f64 * (s1 - s0) * (2.0 * result.score() - s0 - s1) / (2.0 * variance)
+    }
+
+    /// The log-likelihood ratios at which H0 and H1 are accepted.
+    pub fn bounds(&self) -> (f64, f64) {
+        (
+            (self.beta / (1.0 - self.alpha)).ln(),
+            ((1.0 - self.beta) / self.alpha).ln(),
+        )
+    }
+
+    pub fn status(&self, result: &MatchResult) -> SprtStatus {
+        let llr = self.llr(result);
+        let (lower, upper) = self.bounds();
+        if llr <= lower {
+            SprtStatus::AcceptH0
+        } else if llr >= upper {
+            SprtStatus::AcceptH1
+        } else {
+            SprtStatus::Continue
+        }
+    }
+}
+
+/// How a match is played.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MatchSettings {
+    /// The most games to play, rounded up to an even number.
+    pub games: u32,
+    /// The plies of every random opening.
+    pub opening_plies: usize,
+    /// Games are adjudicated as draws after this many plies.
+    pub max_plies: usize,
+    /// Boards each side may evaluate per move.
+    pub nodes: u64,
+    pub seed: u64,
+    /// Stop early once the test is decided.
+    pub sprt: Option<Sprt>,
+}
+
+impl Default for MatchSettings {
+    fn default() -> Self {
+        Self {
+            games: 100,
+            opening_plies: 8,
+            max_plies: 300,
+            nodes: 20_000,
+            seed: 0,
+            sprt: None,
+        }
+    }
+}
+
+/// Play `a` against `b` in pairs of games from the same opening. `on_game`
+/// is called after every game with the result so far, from the point of
+/// view of `a`.
+pub fn run_match(
+    a: &AiSettings,
+    b: &AiSettings,
+    settings: &MatchSettings,
+    mut on_game: impl FnMut(&Pgn, &MatchResult),
+) -> MatchResult {
+    let mut result = MatchResult::default();
+    let mut pair = 0;
+    while result.games() < settings.games {
+        let seed = settings.seed.wrapping_add(pair);
+        let opening = random_opening(seed, settings.opening_plies);
+        for color in [Color::White, Color::Black] {
+            let (white, black) = match color {
+                Color::White => (a, b),
+                Color::Black => (b, a),
+            };
+            let game = play_game(
+                white,
+                black,
+                &opening,
+                seed,
+                settings.nodes,
+                settings.max_plies,
+            );
+            result.add(game.get_result(), color);
+            on_game(&game, &result);
+        }
+        if settings
+            .sprt
+            .is_some_and(|sprt| sprt.status(&result) != SprtStatus::Continue)
+        {
+            break;
+        }
+        pair += 1;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FLAG_MOVE_ORDERING, FLAG_QUIESCENCE};
+
+    #[test]
+    fn elo_estimates() {
+        let even = MatchResult {
+            wins: 10,
+            draws: 20,
+            losses: 10,
+        };
+        let estimate = even.elo();
+        assert_eq!(estimate.elo, 0.0);
+        assert!(estimate.low < -30.0 && estimate.high > 30.0);
+        assert!((estimate.low + estimate.high).abs() < 1e-9);
+
+        // scoring 75% is about 191 Elo
+        let strong = MatchResult {
+            wins: 60,
+            draws: 30,
+            losses: 10,
+        };
+        assert_eq!(strong.score(), 0.75);
+        assert!((strong.elo().elo - 190.85).abs() < 0.1);
+        assert!(strong.elo().low > 100.0);
+
+        let perfect = MatchResult {
+            wins: 10,
+            ..Default::default()
+        };
+        assert_eq!(perfect.elo().elo, 1200.0);
+
+        let mut result = MatchResult::default();
+        result.add(PgnResult::BlackWins, Color::Black);
+        result.add(PgnResult::BlackWins, Color::White);
+        result.add(PgnResult::Unknown, Color::White);
+        assert_eq!((result.wins, result.draws, result.losses), (1, 1, 1));
+    }
+
+    #[test]
+    fn sprt_stops_once_decided() {
+        let sprt = Sprt::default();
+        let (lower, upper) = sprt.bounds();
+        assert!((lower + 2.944).abs() < 0.01 && (upper - 2.944).abs() < 0.01);
+
+        let few = MatchResult {
+            wins: 3,
+            draws: 2,
+            losses: 1,
+        };
+        assert_eq!(sprt.status(&few), SprtStatus::Continue);
+        let crushing = MatchResult {
+            wins: 300,
+            draws: 100,
+            losses: 100,
+        };
+        assert_eq!(sprt.status(&crushing), SprtStatus::AcceptH1);
+        let worse = MatchResult {
+            wins: 100,
+            draws: 100,
+            losses: 300,
+        };
+        assert_eq!(sprt.status(&worse), SprtStatus::AcceptH0);
+        assert_eq!(sprt.llr(&MatchResult::default()), 0.0);
+    }
+
+    #[test]
+    fn openings_are_legal_and_seeded() {
+        let opening = random_opening(3, 8);
+        assert_eq!(opening.len(), 8);
+        assert_eq!(opening, random_opening(3, 8));
+        let mut pgn = Pgn::new();
+        for &mv in &opening {
+            pgn.play_move(mv).unwrap();
+        }
+    }
+
+    #[test]
+    fn matches_are_played_in_pairs() {
+        let searching = AiSettings::new("search", FLAG_MOVE_ORDERING | FLAG_QUIESCENCE, &[4, 4]);
+        let first_move = AiSettings::new("first move", 0, &[1]);
+        let settings = MatchSettings {
+            games: 2,
+            opening_plies: 2,
+            max_plies: 60,
+            nodes: 2_000,
+            ..Default::default()
+        };
+        let mut games = Vec::new();
+        let result = run_match(&searching, &first_move, &settings, |game, _| {
+            games.push(game.clone())
+        });
+        assert_eq!(result.games(), 2);
+        assert_eq!(games.len(), 2);
+        // the same opening with colors swapped
+        assert_eq!(games[0].get_tag("White"), Some("search"));
+        assert_eq!(games[1].get_tag("White"), Some("first move"));
+        assert_eq!(games[0].get_moves()[..2], games[1].get_moves()[..2]);
+        for game in &games {
+            assert_ne!(game.get_result(), PgnResult::Unknown);
+        }
+        // playing the first legal move never beats a search
+        assert_eq!(result.losses, 0);
+    }
+}
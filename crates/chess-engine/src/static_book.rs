@@ -1,6 +1,6 @@
 // Auto-generated by scripts/generate_static_data.py
 // DO NOT EDIT MANUALLY — run `./build.sh --regen-data` to regenerate.
-use crate::{Move, Piece, Position};
+pub use crate::book::{decode_move, lookup_opening};
 
 pub const OPENING_BOOK: &[(u64, u16, u16, u16)] = &[
     (568410613089153u64, 1683u16, 659u16, 787u16),
@@ -8004,45 +8004,3 @@ pub const OPENING_BOOK: &[(u64, u16, u16, u16)] = &[
     (18445681700724833833u64, 2942u16, 2237u16, 2803u16),
     (18446313368089456172u64, 2267u16, 1350u16, 1282u16),
 ];
-
-/// Look up a position in the opening book. Returns a weighted-random move:
-/// 50% best, 33% second, 17% third (falls back if fewer moves available).
-pub fn lookup_opening(zobrist: u64, seed: u8) -> Option<Move> {
-    let idx = OPENING_BOOK
-        .binary_search_by_key(&zobrist, |&(k, _, _, _)| k)
-        .ok()?;
-    let (_, m1, m2, m3) = OPENING_BOOK[idx];
-    if m1 == 0 {
-        return None;
-    }
-    let r = seed % 100;
-    let chosen = if r < 50 || m2 == 0 {
-        m1
-    } else if r < 83 || m3 == 0 {
-        m2
-    } else {
-        m3
-    };
-    Some(decode_move(chosen))
-}
-
-pub fn decode_move(code: u16) -> Move {
-    let special = (code >> 12) & 0xF;
-    match special {
-        5 => Move::KingSideCastle,
-        6 => Move::QueenSideCastle,
-        _ => {
-            let fi = (code & 0x3F) as usize;
-            let ti = ((code >> 6) & 0x3F) as usize;
-            let f = Position::new((fi / 8) as i32, (fi % 8) as i32);
-            let t = Position::new((ti / 8) as i32, (ti % 8) as i32);
-            match special {
-                1 => Move::Promotion(f, t, Piece::Knight(crate::Color::White, t)),
-                2 => Move::Promotion(f, t, Piece::Bishop(crate::Color::White, t)),
-                3 => Move::Promotion(f, t, Piece::Rook(crate::Color::White, t)),
-                4 => Move::Promotion(f, t, Piece::Queen(crate::Color::White, t)),
-                _ => Move::Piece(f, t),
-            }
-        }
-    }
-}
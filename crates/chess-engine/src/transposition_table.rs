@@ -1,5 +1,4 @@
 use crate::Move;
-use std::collections::HashMap;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum TtFlag {
@@ -10,31 +9,84 @@ pub enum TtFlag {
 
 #[derive(Clone, Copy, Debug)]
 pub struct TtEntry {
+    /// The full key, to tell apart the positions sharing a bucket.
+    key: u64,
     pub depth: u8,
     pub flag: TtFlag,
-    pub value: f64,
+    pub value: i32,
     pub best_move: Option<Move>,
+    /// The search that stored the entry, see [`TranspositionTable::new_search`].
+    age: u8,
 }
 
-/// Simple transposition table with a fixed size ceiling.
-/// Entries are keyed by a position hash combined with the search-depth context.
+/// The number of entries of the table a search makes for itself when the
+/// caller does not pass one in.
+pub const TT_SIZE: usize = 8192;
+
+/// A depth-preferred slot, which keeps the deepest entry of the current
+/// search, and an always-replace slot for everything else.
+#[derive(Clone, Copy, Debug, Default)]
+struct Bucket {
+    depth_preferred: Option<TtEntry>,
+    always_replace: Option<TtEntry>,
+}
+
+/// Fixed-size transposition table of a power of two buckets of two entries,
+/// keyed by the Zobrist key of the position alone.
+///
+/// The table is meant to outlive a single search: a caller searching a
+/// game's positions one after the other keeps one table and starts each
+/// search with [`TranspositionTable::new_search`].
 pub struct TranspositionTable {
-    map: HashMap<u64, TtEntry>,
-    keys: Vec<u64>,
-    max_size: usize,
+    buckets: Vec<Bucket>,
+    age: u8,
 }
 
 impl TranspositionTable {
+    /// A table of at least `max_size` entries, rounded up to a power of two.
+    /// A table of size 0 stores nothing.
     pub fn new(max_size: usize) -> Self {
+        let buckets = if max_size == 0 {
+            0
+        } else {
+            max_size.div_ceil(2).next_power_of_two()
+        };
         Self {
-            map: HashMap::with_capacity(max_size),
-            keys: Vec::with_capacity(max_size),
-            max_size,
+            buckets: vec![Bucket::default(); buckets],
+            age: 0,
+        }
+    }
+
+    /// The number of entries the table holds at most.
+    pub fn capacity(&self) -> usize {
+        self.buckets.len() * 2
+    }
+
+    /// Start a new search. Entries of earlier searches are still found, but
+    /// give up their depth-preferred slot to any entry of the new search.
+    pub fn new_search(&mut self) {
+        self.age = self.age.wrapping_add(1);
+    }
+
+    /// Forget all entries, as for a new game.
+    pub fn clear(&mut self) {
+        self.buckets.fill(Bucket::default());
+    }
+
+    fn bucket(&self, key: u64) -> Option<usize> {
+        if self.buckets.is_empty() {
+            None
+        } else {
+            Some(key as usize & (self.buckets.len() - 1))
         }
     }
 
     pub fn get(&self, key: u64) -> Option<&TtEntry> {
-        self.map.get(&key)
+        let bucket = &self.buckets[self.bucket(key)?];
+        [&bucket.depth_preferred, &bucket.always_replace]
+            .into_iter()
+            .flatten()
+            .find(|entry| entry.key == key)
     }
 
     pub fn store(
@@ -42,67 +94,69 @@ impl TranspositionTable {
         key: u64,
         depth: u8,
         flag: TtFlag,
-        value: f64,
+        value: i32,
         best_move: Option<Move>,
     ) {
-        // Update existing entry if the new depth is at least as deep.
-        if let Some(existing) = self.map.get(&key) {
-            if existing.depth > depth {
-                return;
-            }
-            self.map.insert(
-                key,
-                TtEntry {
-                    depth,
-                    flag,
-                    value,
-                    best_move,
-                },
-            );
+        let Some(index) = self.bucket(key) else {
             return;
-        }
-
-        // New key. If the table is full, evict a pseudo-random entry to keep
-        // the table live (prevents the table from freezing when full).
-        if self.map.len() >= self.max_size && !self.keys.is_empty() {
-            let idx = (key as usize) % self.keys.len();
-            let victim = self.keys.swap_remove(idx);
-            self.map.remove(&victim);
-        }
-
-        self.map.insert(
+        };
+        let entry = TtEntry {
             key,
-            TtEntry {
-                depth,
-                flag,
-                value,
-                best_move,
-            },
-        );
-        self.keys.push(key);
+            depth,
+            flag,
+            value,
+            best_move,
+            age: self.age,
+        };
+        let bucket = &mut self.buckets[index];
+        match bucket.depth_preferred {
+            // a deeper entry of this search keeps its slot
+            Some(existing) if existing.age == self.age && existing.depth > depth => {
+                bucket.always_replace = Some(entry);
+            }
+            _ => bucket.depth_preferred = Some(entry),
+        }
     }
 }
 
-/// Build a key that includes the remaining search context.
-/// Because the engine samples a variable number of moves per ply,
-/// the same board reached with different remaining width lists is not
-/// safely interchangeable, so we fold the width list into the key.
-pub fn tt_context_key(
-    zobrist: u64,
-    depth: &either::Either<u8, (&[u8], rand_chacha::ChaCha20Rng)>,
-) -> u64 {
-    let ctx = match depth {
-        either::Either::Left(d) => {
-            // 64 distinguishes a fixed-depth search from a width-list search.
-            64u64.wrapping_shl(56) | (*d as u64)
-        }
-        either::Either::Right((widths, _)) => {
-            let mut h: u64 = widths.len() as u64;
-            for &w in *widths {
-                h = h.wrapping_mul(31).wrapping_add(w as u64);
-            }
-            h
-        }
-    };
-    zobrist ^ ctx.wrapping_mul(0x9E3779B97F4A7C15)
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buckets() {
+        let mut tt = TranspositionTable::new(5);
+        assert_eq!(tt.capacity(), 8);
+        // 1, 5 and 9 share a bucket
+        tt.store(1, 4, TtFlag::Exact, 10, None);
+        tt.store(5, 2, TtFlag::LowerBound, 20, None);
+        assert_eq!(tt.get(1).unwrap().value, 10);
+        assert_eq!(tt.get(5).unwrap().value, 20);
+        assert!(tt.get(2).is_none());
+
+        // the shallower entry is replaced, the deeper one is kept
+        tt.store(9, 3, TtFlag::UpperBound, 30, None);
+        assert!(tt.get(5).is_none());
+        assert_eq!(tt.get(1).unwrap().depth, 4);
+        assert_eq!(tt.get(9).unwrap().flag, TtFlag::UpperBound);
+
+        // a deeper search of the same position replaces it
+        tt.store(1, 6, TtFlag::Exact, 15, None);
+        assert_eq!(tt.get(1).unwrap().value, 15);
+
+        // an entry of an earlier search gives up its slot
+        tt.new_search();
+        tt.store(5, 2, TtFlag::Exact, 25, None);
+        tt.store(13, 1, TtFlag::Exact, 35, None);
+        assert!(tt.get(1).is_none());
+        assert_eq!(tt.get(5).unwrap().value, 25);
+        assert_eq!(tt.get(13).unwrap().value, 35);
+
+        tt.clear();
+        assert!(tt.get(13).is_none());
+
+        let mut empty = TranspositionTable::new(0);
+        empty.store(1, 4, TtFlag::Exact, 10, None);
+        assert!(empty.get(1).is_none());
+    }
 }
@@ -1,4 +1,6 @@
-use crate::{Board, BoardBuilder, Color, Move, Piece, Position};
+use crate::bitboard::{square_bit, square_index, BISHOP, EMPTY, KING, KNIGHT, PAWN, QUEEN, ROOK};
+use crate::{Board, BoardBuilder, Color, GameResult, Move, Piece, Position, Variant};
+use std::cmp::Ordering;
 
 // generate FEN
 // cannot calculate halfmove or fullmove based on board state,
@@ -60,25 +62,43 @@ pub fn format_fen(
     );
 
     // castling
+    // X-FEN: rights are "K" and "Q" when castling with the outermost rook on
+    // that side, which covers every standard position. Otherwise the rook's
+    // file is given instead, as in Shredder-FEN.
     fen.push(" ".to_string());
     let mut any_castling_rights = false;
-    let white_castling_rights = board.get_castling_rights(Color::White);
-    if white_castling_rights.can_kingside_castle() {
-        fen.push("K".to_string());
-        any_castling_rights = true;
-    }
-    if white_castling_rights.can_queenside_castle() {
-        fen.push("Q".to_string());
-        any_castling_rights = true
-    }
-    let black_castling_rights = board.get_castling_rights(Color::Black);
-    if black_castling_rights.can_kingside_castle() {
-        fen.push("k".to_string());
-        any_castling_rights = true;
-    }
-    if black_castling_rights.can_queenside_castle() {
-        fen.push("q".to_string());
-        any_castling_rights = true
+    for color in [Color::White, Color::Black] {
+        let castling_rights = board.get_castling_rights(color);
+        let row = Position::king_pos(color).get_row();
+        let has_rook = |col: i32| matches!(board.get_piece(Position::new(row, col)), Some(Piece::Rook(c, _)) if c == color);
+        for (allowed, rook_col, outer_cols, side) in [
+            (
+                castling_rights.can_kingside_castle(),
+                castling_rights.kingside_rook_col(),
+                castling_rights.kingside_rook_col() + 1..8,
+                'k',
+            ),
+            (
+                castling_rights.can_queenside_castle(),
+                castling_rights.queenside_rook_col(),
+                0..castling_rights.queenside_rook_col(),
+                'q',
+            ),
+        ] {
+            if !allowed {
+                continue;
+            }
+            let mut ch = if outer_cols.into_iter().any(has_rook) {
+                (b'a' + rook_col as u8) as char
+            } else {
+                side
+            };
+            if color == Color::White {
+                ch = ch.to_ascii_uppercase();
+            }
+            fen.push(ch.to_string());
+            any_castling_rights = true;
+        }
     }
     if !any_castling_rights {
         fen.push("-".to_string());
@@ -182,6 +202,10 @@ pub fn parse_fen(fen: &str) -> Result<Board, String> {
         return Err(String::from("incomplete position"));
     }
 
+    // castling rights refer to the rooks that were just placed
+    let placed = builder.build();
+    let mut builder = BoardBuilder::from(placed);
+
     builder = builder.set_turn(match active_color.unwrap() {
         "b" => Color::Black,
         "w" => Color::White,
@@ -190,6 +214,8 @@ pub fn parse_fen(fen: &str) -> Result<Board, String> {
         }
     });
 
+    // accepts "KQkq" (X-FEN: the outermost rook on that side) as well as
+    // the rooks' files (Shredder-FEN, e.g. "HAha") for Chess960 positions.
     match castling.unwrap() {
         "-" => {}
         castling => {
@@ -198,13 +224,41 @@ pub fn parse_fen(fen: &str) -> Result<Board, String> {
                     true => Color::White,
                     false => Color::Black,
                 };
-                match c.to_ascii_lowercase() {
-                    'k' => {
+                let row = Position::king_pos(color).get_row();
+                let king_col = placed
+                    .get_king_pos(color)
+                    .filter(|king| king.get_row() == row)
+                    .map(|king| king.get_col());
+                let is_rook = |col: &i32| matches!(placed.get_piece(Position::new(row, *col)), Some(Piece::Rook(c, _)) if c == color);
+                match (c.to_ascii_lowercase(), king_col) {
+                    ('k', Some(king_col)) => {
+                        builder = match (king_col + 1..8).rev().find(is_rook) {
+                            Some(col) => builder.enable_kingside_castle_with(color, col),
+                            None => builder.enable_kingside_castle(color),
+                        };
+                    }
+                    ('q', Some(king_col)) => {
+                        builder = match (0..king_col).find(is_rook) {
+                            Some(col) => builder.enable_queenside_castle_with(color, col),
+                            None => builder.enable_queenside_castle(color),
+                        };
+                    }
+                    ('k', None) => {
                         builder = builder.enable_kingside_castle(color);
                     }
-                    'q' => {
+                    ('q', None) => {
                         builder = builder.enable_queenside_castle(color);
                     }
+                    (file @ 'a'..='h', Some(king_col)) => {
+                        let col = file as i32 - 'a' as i32;
+                        builder = match col.cmp(&king_col) {
+                            Ordering::Greater => builder.enable_kingside_castle_with(color, col),
+                            Ordering::Less => builder.enable_queenside_castle_with(color, col),
+                            Ordering::Equal => {
+                                return Err(String::from("invalid castling side"));
+                            }
+                        };
+                    }
                     _ => {
                         return Err(String::from("invalid castling side"));
                     }
@@ -226,9 +280,255 @@ pub fn parse_fen(fen: &str) -> Result<Board, String> {
     Ok(builder.build())
 }
 
+/// Why [`parse_fen_strict`] rejected a FEN.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FenError {
+    /// A FEN has four to six space-separated fields.
+    FieldCount(usize),
+    /// The piece placement does not have eight ranks.
+    RankCount(usize),
+    /// A rank, counted from 8 down to 1, does not have eight squares.
+    RankLength(u8),
+    InvalidPiece(char),
+    InvalidActiveColor(String),
+    InvalidCastling(String),
+    InvalidEnPassant(String),
+    InvalidHalfmoveClock(String),
+    InvalidFullmoveNumber(String),
+    MissingKing(Color),
+    TooManyKings(Color),
+    TooManyPawns(Color),
+    /// More queens, rooks, bishops or knights than promotions could have
+    /// produced with the pawns that are gone.
+    TooManyPieces(Color),
+    PawnOnBackRank(Position),
+    /// The side that just moved is in check.
+    OpponentInCheck,
+    /// A color has castling rights, but its king is not on its back rank.
+    CastlingWithoutKing(Color),
+    /// A castling right refers to this square, which has no rook.
+    CastlingWithoutRook(Position),
+    /// A castling right refers to the rook on this square, which is on the
+    /// other side of the king.
+    CastlingRookOnWrongSide(Position),
+    /// No pawn can just have moved past this square.
+    ImpossibleEnPassant(Position),
+    /// Any other error of [`parse_fen`].
+    Parse(String),
+}
+
+impl core::fmt::Display for FenError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        match self {
+            Self::FieldCount(count) => write!(f, "expected 4 to 6 fields, got {}", count),
+            Self::RankCount(count) => write!(f, "expected 8 ranks, got {}", count),
+            Self::RankLength(rank) => write!(f, "rank {} does not have 8 squares", rank),
+            Self::InvalidPiece(c) => write!(f, "invalid piece `{}`", c),
+            Self::InvalidActiveColor(color) => write!(f, "invalid active color `{}`", color),
+            Self::InvalidCastling(castling) => write!(f, "invalid castling rights `{}`", castling),
+            Self::InvalidEnPassant(square) => write!(f, "invalid en passant square `{}`", square),
+            Self::InvalidHalfmoveClock(clock) => write!(f, "invalid halfmove clock `{}`", clock),
+            Self::InvalidFullmoveNumber(number) => {
+                write!(f, "invalid fullmove number `{}`", number)
+            }
+            Self::MissingKing(color) => write!(f, "{} has no king", color),
+            Self::TooManyKings(color) => write!(f, "{} has more than one king", color),
+            Self::TooManyPawns(color) => write!(f, "{} has more than 8 pawns", color),
+            Self::TooManyPieces(color) => {
+                write!(
+                    f,
+                    "{} has more pieces than its pawns could promote to",
+                    color
+                )
+            }
+            Self::PawnOnBackRank(position) => write!(f, "pawn on {}", position),
+            Self::OpponentInCheck => write!(f, "the side not to move is in check"),
+            Self::CastlingWithoutKing(color) => {
+                write!(
+                    f,
+                    "{} can castle, but its king is not on its back rank",
+                    color
+                )
+            }
+            Self::CastlingWithoutRook(position) => {
+                write!(f, "castling rights without a rook on {}", position)
+            }
+            Self::CastlingRookOnWrongSide(position) => {
+                write!(
+                    f,
+                    "the castling rook on {} is on the wrong side of its king",
+                    position
+                )
+            }
+            Self::ImpossibleEnPassant(position) => {
+                write!(f, "no pawn can have just moved past {}", position)
+            }
+            Self::Parse(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+/// Parse a FEN like [`parse_fen`], but reject anything that is malformed or
+/// cannot occur in a game of standard chess or Chess960, see
+/// [`validate_position`]. Use it for positions users submit.
+pub fn parse_fen_strict(fen: &str) -> Result<Board, FenError> {
+    let fields: Vec<&str> = fen.split_ascii_whitespace().collect();
+    if !(4..=6).contains(&fields.len()) {
+        return Err(FenError::FieldCount(fields.len()));
+    }
+
+    let ranks: Vec<&str> = fields[0].split('/').collect();
+    if ranks.len() != 8 {
+        return Err(FenError::RankCount(ranks.len()));
+    }
+    for (i, rank) in ranks.iter().enumerate() {
+        let mut squares = 0;
+        for c in rank.chars() {
+            match c {
+                '1'..='8' => squares += c.to_digit(10).unwrap(),
+                'p' | 'n' | 'b' | 'r' | 'q' | 'k' | 'P' | 'N' | 'B' | 'R' | 'Q' | 'K' => {
+                    squares += 1
+                }
+                _ => return Err(FenError::InvalidPiece(c)),
+            }
+        }
+        if squares != 8 {
+            return Err(FenError::RankLength(8 - i as u8));
+        }
+    }
+
+    if !matches!(fields[1], "w" | "b") {
+        return Err(FenError::InvalidActiveColor(fields[1].to_string()));
+    }
+
+    let castling = fields[2];
+    let mut seen = Vec::new();
+    for c in castling.chars().filter(|_| castling != "-") {
+        if !matches!(c, 'K' | 'Q' | 'A'..='H' | 'k' | 'q' | 'a'..='h') || seen.contains(&c) {
+            return Err(FenError::InvalidCastling(castling.to_string()));
+        }
+        seen.push(c);
+    }
+
+    let en_passant = fields[3];
+    if en_passant != "-" && Position::pgn(en_passant).is_err() {
+        return Err(FenError::InvalidEnPassant(en_passant.to_string()));
+    }
+
+    if let Some(clock) = fields.get(4) {
+        if clock.parse::<u8>().is_err() {
+            return Err(FenError::InvalidHalfmoveClock(clock.to_string()));
+        }
+    }
+    if let Some(number) = fields.get(5) {
+        if !number.parse::<u16>().is_ok_and(|number| number > 0) {
+            return Err(FenError::InvalidFullmoveNumber(number.to_string()));
+        }
+    }
+
+    // everything but a castling file on the king's own file was checked
+    let board = parse_fen(fen).map_err(|error| match error.as_str() {
+        "invalid castling side" => FenError::InvalidCastling(castling.to_string()),
+        _ => FenError::Parse(error),
+    })?;
+    validate_position(&board)?;
+    Ok(board)
+}
+
+/// Check that a position can occur in a game: both sides have exactly one
+/// king, no pawns stand on the first or last rank, there are no more pieces
+/// than promotions allow, the side not to move is not in check, castling
+/// rights have their king and rook, and an en passant square has the pawn
+/// that just moved past it.
+pub fn validate_position(board: &Board) -> Result<(), FenError> {
+    let bitboards = board.bitboards();
+    for color in [Color::White, Color::Black] {
+        let count = |kind| bitboards.pieces(kind, color).count_ones();
+        match count(KING) {
+            0 => return Err(FenError::MissingKing(color)),
+            1 => {}
+            _ => return Err(FenError::TooManyKings(color)),
+        }
+        let pawns = count(PAWN);
+        if pawns > 8 {
+            return Err(FenError::TooManyPawns(color));
+        }
+        let promoted = count(QUEEN).saturating_sub(1)
+            + count(ROOK).saturating_sub(2)
+            + count(BISHOP).saturating_sub(2)
+            + count(KNIGHT).saturating_sub(2);
+        if promoted > 8 - pawns {
+            return Err(FenError::TooManyPieces(color));
+        }
+    }
+
+    for row in [0, 7] {
+        for col in 0..8 {
+            let position = Position::new(row, col);
+            if let Some(Piece::Pawn(..)) = board.get_piece(position) {
+                return Err(FenError::PawnOnBackRank(position));
+            }
+        }
+    }
+
+    let turn = board.get_turn_color();
+    if board.is_in_check(!turn) {
+        return Err(FenError::OpponentInCheck);
+    }
+
+    for color in [Color::White, Color::Black] {
+        let rights = board.get_castling_rights(color);
+        let row = Position::king_pos(color).get_row();
+        let mut rooks = vec![];
+        if rights.can_kingside_castle() {
+            rooks.push((rights.kingside_rook_col(), true));
+        }
+        if rights.can_queenside_castle() {
+            rooks.push((rights.queenside_rook_col(), false));
+        }
+        if rooks.is_empty() {
+            continue;
+        }
+        let king_col = match board.get_king_pos(color) {
+            Some(king) if king.get_row() == row => king.get_col(),
+            _ => return Err(FenError::CastlingWithoutKing(color)),
+        };
+        for (col, kingside) in rooks {
+            let position = Position::new(row, col);
+            if !matches!(board.get_piece(position), Some(Piece::Rook(c, _)) if c == color) {
+                return Err(FenError::CastlingWithoutRook(position));
+            }
+            if (col > king_col) != kingside {
+                return Err(FenError::CastlingRookOnWrongSide(position));
+            }
+        }
+    }
+
+    if let Some(square) = board.get_en_passant() {
+        // the pawn that moved two squares is of the side not to move
+        let mover = !turn;
+        let origin = square.pawn_back(mover);
+        let pawn = square.pawn_up(mover);
+        // on the sixth rank from the point of view of the side to move
+        let row = match turn {
+            Color::White => 5,
+            Color::Black => 2,
+        };
+        let possible = square.get_row() == row
+            && board.get_piece(square).is_none()
+            && board.get_piece(origin).is_none()
+            && matches!(board.get_piece(pawn), Some(Piece::Pawn(c, _)) if c == mover);
+        if !possible {
+            return Err(FenError::ImpossibleEnPassant(square));
+        }
+    }
+    Ok(())
+}
+
 // parse Short Algebraic Notation (SAN)
 //
-// move parsing is strict and should not include any +, #, etc characters.
+// trailing +, #, ! and ? characters as well as the '=' of promotions
+// are ignored, so everything written by format_san_move can be read back.
 //
 // returns Err("ambiguous") if there are multiple possibilities
 // e.g. "Nxc4" when there are N at e2 and d6
@@ -237,9 +537,13 @@ pub fn parse_fen(fen: &str) -> Result<Board, String> {
 // returns Err(InvalidMove) if there is a parse error or the move is
 // not valid based on the current board position and turn.
 pub fn parse_san_move(board: &Board, move_str: &str) -> Result<Move, String> {
-    if move_str == "0-0" {
+    let move_str = move_str
+        .trim_end_matches(['+', '#', '!', '?'])
+        .replace('=', "");
+    let move_str = move_str.as_str();
+    if move_str == "0-0" || move_str == "O-O" {
         return Ok(Move::KingSideCastle {});
-    } else if move_str == "0-0-0" {
+    } else if move_str == "0-0-0" || move_str == "O-O-O" {
         return Ok(Move::QueenSideCastle {});
     }
 
@@ -356,6 +660,291 @@ pub fn parse_san_move(board: &Board, move_str: &str) -> Result<Move, String> {
     }
 }
 
+// generate Standard Algebraic Notation (SAN)
+//
+// the move is formatted for the given board, which must be the position
+// before the move is played. includes piece letters, disambiguation,
+// 'x' for captures, '=Q' for promotions and '+' or '#' suffixes.
+//
+// pawn moves to the last rank without explicit promotion are formatted
+// as queen promotions, as this is how the board plays them.
+//
+// returns Err if the move is not legal on the board.
+pub fn format_san_move(board: &Board, m: Move) -> Result<String, String> {
+    format_variant_san_move(board, m, Variant::Standard)
+}
+
+// generate SAN for a move played by the rules of a variant
+//
+// legality and disambiguation follow the variant's moves. '#' marks
+// checkmate only, not the other ways to win a variant, and Antichess moves
+// get no '+' as there is no check.
+pub fn format_variant_san_move(board: &Board, m: Move, variant: Variant) -> Result<String, String> {
+    if m == Move::Resign || !variant.is_legal_move(board, m) {
+        return Err("illegal move".to_string());
+    }
+    let next_turn = board.apply_eval_move(m);
+    let checkmate = variant.is_checkmate(&next_turn);
+    let mut san = san_without_suffix(board, m, variant)?;
+    push_check_suffix(&mut san, &next_turn, variant, checkmate);
+    Ok(san)
+}
+
+// generate SAN for a move that was just played by the rules of a variant
+//
+// `result` is what playing the move on `board`, the position before the
+// move, returned. the move is known to be legal then, and only a move that
+// won the game can be checkmate, so the move is not played again to format
+// it unless it ended the game.
+//
+// returns Err if the result is an illegal move, or for resigning.
+pub fn format_played_san_move(
+    board: &Board,
+    m: Move,
+    variant: Variant,
+    result: &GameResult,
+) -> Result<String, String> {
+    let (next_turn, checkmate) = match result {
+        _ if m == Move::Resign => return Err("illegal move".to_string()),
+        GameResult::IllegalMove(_) => return Err("illegal move".to_string()),
+        GameResult::Continuing(next_turn) => (*next_turn, false),
+        GameResult::Victory(_) => {
+            let next_turn = board.apply_eval_move(m);
+            (next_turn, variant.is_checkmate(&next_turn))
+        }
+        GameResult::Stalemate | GameResult::ThreefoldRepetition | GameResult::FiftyMoveRule => {
+            (board.apply_eval_move(m), false)
+        }
+    };
+    let mut san = san_without_suffix(board, m, variant)?;
+    push_check_suffix(&mut san, &next_turn, variant, checkmate);
+    Ok(san)
+}
+
+// the SAN of a legal move without the check or checkmate suffix
+fn san_without_suffix(board: &Board, m: Move, variant: Variant) -> Result<String, String> {
+    let color = board.get_turn_color();
+    match m {
+        Move::KingSideCastle => Ok("O-O".to_string()),
+        Move::QueenSideCastle => Ok("O-O-O".to_string()),
+        Move::Piece(from, to) | Move::Promotion(from, to, _) => {
+            let piece = match board.get_piece(from) {
+                Some(piece) => piece,
+                None => {
+                    return Err("illegal move".to_string());
+                }
+            };
+            let capture = board.is_capture(m);
+            let mut san = String::new();
+            if piece.is_pawn() {
+                if capture {
+                    san.push(file_char(from));
+                }
+            } else {
+                san.push(piece_char(piece));
+
+                // other pieces of the same type that can reach the target.
+                // the moves are only generated if another one attacks it.
+                let bitboards = board.bitboards();
+                let (kind, _) = piece.zobrist_indices();
+                let attackers =
+                    bitboards.attackers_to(square_index(to), color, bitboards.occupied())
+                        & bitboards.pieces(kind, color)
+                        & !square_bit(from);
+                let others: Vec<Position> = if attackers == EMPTY {
+                    vec![]
+                } else {
+                    variant
+                        .get_legal_moves(board)
+                        .into_iter()
+                        .filter_map(|legal_move| match legal_move {
+                            Move::Piece(other, other_to) if other != from && other_to == to => {
+                                Some(other)
+                            }
+                            _ => None,
+                        })
+                        .filter(|other| {
+                            board
+                                .get_piece(*other)
+                                .is_some_and(|other| other.get_name() == piece.get_name())
+                        })
+                        .collect()
+                };
+                if !others.is_empty() {
+                    if others.iter().all(|other| other.get_col() != from.get_col()) {
+                        san.push(file_char(from));
+                    } else if others.iter().all(|other| other.get_row() != from.get_row()) {
+                        san.push(rank_char(from));
+                    } else {
+                        san.push(file_char(from));
+                        san.push(rank_char(from));
+                    }
+                }
+            }
+            if capture {
+                san.push('x');
+            }
+            san.push_str(&to.to_string());
+
+            let promotion = match m {
+                Move::Promotion(_, _, promotion) => Some(promotion),
+                _ if piece.is_pawn() && (to.get_row() == 0 || to.get_row() == 7) => {
+                    Some(Piece::Queen(color, to))
+                }
+                _ => None,
+            };
+            if let Some(promotion) = promotion {
+                san.push('=');
+                san.push(piece_char(promotion));
+            }
+            Ok(san)
+        }
+        Move::Resign => Err("illegal move".to_string()),
+    }
+}
+
+// append '#' for checkmate or '+' for check to the SAN of a move that led to
+// `next_turn`
+fn push_check_suffix(san: &mut String, next_turn: &Board, variant: Variant, checkmate: bool) {
+    if checkmate {
+        san.push('#');
+    } else if variant != Variant::Antichess && next_turn.is_in_check(next_turn.get_turn_color()) {
+        san.push('+');
+    }
+}
+
+// generate long algebraic notation as used by UCI, e.g. "e2e4" or "e7e8q"
+//
+// castling is formatted as the king's move, e.g. "e1g1", or in Chess960
+// positions as the king moving onto its rook, e.g. "b1a1".
+// pawn moves to the last rank without explicit promotion are formatted
+// as queen promotions.
+//
+// returns Err for moves that have no UCI representation, i.e. resigning
+// or castling without a king on the board.
+pub fn format_uci_move(board: &Board, m: Move) -> Result<String, String> {
+    format_uci_move_with(board, m, false)
+}
+
+// like format_uci_move, but castling is always formatted as the king moving
+// onto its rook, e.g. "e1h1", as GUIs expect with UCI_Chess960 enabled.
+pub fn format_chess960_uci_move(board: &Board, m: Move) -> Result<String, String> {
+    format_uci_move_with(board, m, true)
+}
+
+fn format_uci_move_with(board: &Board, m: Move, chess960: bool) -> Result<String, String> {
+    match m {
+        Move::KingSideCastle | Move::QueenSideCastle => {
+            let from = match board.get_king_pos(board.get_turn_color()) {
+                Some(from) => from,
+                None => {
+                    return Err("no king to castle".to_string());
+                }
+            };
+            let castling_rights = board.get_castling_rights(board.get_turn_color());
+            let (rook_col, king_to_col) = match m {
+                Move::KingSideCastle => (castling_rights.kingside_rook_col(), 6),
+                _ => (castling_rights.queenside_rook_col(), 2),
+            };
+            // Chess960 castling is written as the king taking its own rook
+            let to_col = if !chess960 && from.get_col() == 4 && (rook_col == 0 || rook_col == 7) {
+                king_to_col
+            } else {
+                rook_col
+            };
+            Ok(format!("{}{}", from, Position::new(from.get_row(), to_col)))
+        }
+        Move::Piece(from, to) => {
+            let promotes = board.get_piece(from).is_some_and(|piece| piece.is_pawn())
+                && (to.get_row() == 0 || to.get_row() == 7);
+            if promotes {
+                Ok(format!("{}{}q", from, to))
+            } else {
+                Ok(format!("{}{}", from, to))
+            }
+        }
+        Move::Promotion(from, to, promotion) => Ok(format!(
+            "{}{}{}",
+            from,
+            to,
+            piece_char(promotion).to_ascii_lowercase()
+        )),
+        Move::Resign => Err("resign has no UCI notation".to_string()),
+    }
+}
+
+// parse long algebraic notation as used by UCI, e.g. "e2e4" or "e7e8q"
+//
+// king moves by two files and king moves onto an own rook (Chess960) are
+// read as castling. the move is not checked for legality.
+pub fn parse_uci_move(board: &Board, move_str: &str) -> Result<Move, String> {
+    parse_uci_move_with(board, move_str, false)
+}
+
+// like parse_uci_move, but only king moves onto an own rook are read as
+// castling, as GUIs send them with UCI_Chess960 enabled. a king move by two
+// files is then left as a plain (illegal) king move.
+pub fn parse_chess960_uci_move(board: &Board, move_str: &str) -> Result<Move, String> {
+    parse_uci_move_with(board, move_str, true)
+}
+
+fn parse_uci_move_with(board: &Board, move_str: &str, chess960: bool) -> Result<Move, String> {
+    if move_str.len() != 4 && move_str.len() != 5 {
+        return Err("invalid UCI move".to_string());
+    }
+    let from = Position::pgn(move_str.get(0..2).ok_or("invalid from position")?)?;
+    let to = Position::pgn(move_str.get(2..4).ok_or("invalid to position")?)?;
+    let color = board.get_turn_color();
+    match move_str.get(4..) {
+        Some("") => {}
+        Some("q") => return Ok(Move::Promotion(from, to, Piece::Queen(color, to))),
+        Some("r") => return Ok(Move::Promotion(from, to, Piece::Rook(color, to))),
+        Some("b") => return Ok(Move::Promotion(from, to, Piece::Bishop(color, to))),
+        Some("n") => return Ok(Move::Promotion(from, to, Piece::Knight(color, to))),
+        // only legal in Antichess, which the variant's legality check decides
+        Some("k") => return Ok(Move::Promotion(from, to, Piece::King(color, to))),
+        _ => return Err("invalid promotion".to_string()),
+    }
+    if let Some(Piece::King(color, _)) = board.get_piece(from) {
+        if board.has_ally_piece(to, color) && board.get_piece(to).is_some_and(|p| p.is_rook()) {
+            return Ok(if to.get_col() > from.get_col() {
+                Move::KingSideCastle
+            } else {
+                Move::QueenSideCastle
+            });
+        }
+        if chess960 {
+            return Ok(Move::Piece(from, to));
+        }
+        if to == from.next_right().next_right() {
+            return Ok(Move::KingSideCastle);
+        } else if to == from.next_left().next_left() {
+            return Ok(Move::QueenSideCastle);
+        }
+    }
+    Ok(Move::Piece(from, to))
+}
+
+// uppercase SAN piece letter, 'P' for pawns
+fn piece_char(piece: Piece) -> char {
+    match piece {
+        Piece::King(_, _) => 'K',
+        Piece::Queen(_, _) => 'Q',
+        Piece::Rook(_, _) => 'R',
+        Piece::Bishop(_, _) => 'B',
+        Piece::Knight(_, _) => 'N',
+        Piece::Pawn(_, _) => 'P',
+    }
+}
+
+fn file_char(position: Position) -> char {
+    (b'a' + position.get_col() as u8) as char
+}
+
+fn rank_char(position: Position) -> char {
+    (b'1' + position.get_row() as u8) as char
+}
+
 #[cfg(test)]
 mod tests {
     use alloc::string::{String, ToString};
@@ -363,7 +952,7 @@ mod tests {
     use super::*;
     use crate::board::*;
     use crate::position::*;
-    use crate::{GameResult, Move};
+    use crate::{CheckCounter, GameResult, Move, WHITE};
 
     #[test]
     fn test_fen() {
@@ -403,6 +992,146 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_chess960_fen() {
+        // Shredder-FEN files are read, and written back as X-FEN
+        let fen = "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9";
+        let board = parse_fen(fen).unwrap();
+        let rights = board.get_castling_rights(Color::White);
+        assert_eq!(rights.kingside_rook_col(), 7);
+        assert_eq!(rights.queenside_rook_col(), 5);
+        let xfen = format_fen(&board, 2, 9).unwrap();
+        assert_eq!(
+            xfen,
+            "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w KQkq - 2 9"
+        );
+        assert_eq!(parse_fen(&xfen).unwrap(), board);
+
+        // an inner rook keeps its file letter
+        let fen = "4k3/8/8/8/8/8/8/RR2K3 w B - 0 1";
+        let board = parse_fen(fen).unwrap();
+        assert_eq!(
+            board.get_castling_rights(Color::White).queenside_rook_col(),
+            1
+        );
+        assert_eq!(format_fen(&board, 0, 1).unwrap(), fen);
+
+        for index in [0, 518, 959] {
+            let board = Board::chess960(index);
+            let fen = format_fen(&board, 0, 1).unwrap();
+            assert_eq!(parse_fen(&fen).unwrap(), board);
+        }
+        assert!(parse_fen("4k3/8/8/8/8/8/8/4K3 w E - 0 1").is_err());
+    }
+
+    #[test]
+    fn test_strict_fen() {
+        let start = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+        assert_eq!(parse_fen_strict(start), Ok(Board::default()));
+        let fen = "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9";
+        assert_eq!(parse_fen_strict(fen).ok(), parse_fen(fen).ok());
+        let fen = "rnbqkbnr/pp1ppppp/8/2p5/4P3/8/PPPP1PPP/RNBQKBNR w KQkq c6 0 2";
+        assert!(parse_fen_strict(fen).is_ok());
+        assert!(parse_fen_strict("4k3/8/8/8/8/8/8/4K3 w - -").is_ok());
+
+        let error = |fen: &str| parse_fen_strict(fen).unwrap_err();
+        assert_eq!(error("4k3/8/8/8/8/8/8/4K3 w"), FenError::FieldCount(2));
+        assert_eq!(error("4k3/8/8/8/8/8/4K3 w - - 0 1"), FenError::RankCount(7));
+        assert_eq!(
+            error("4k3/8/8/8/8/9/8/4K3 w - - 0 1"),
+            FenError::InvalidPiece('9')
+        );
+        assert_eq!(
+            error("4k3/8/8/8/8/7/8/4K3 w - - 0 1"),
+            FenError::RankLength(3)
+        );
+        assert_eq!(
+            error("4k3/8/8/8/8/8/8/4K3 x - - 0 1"),
+            FenError::InvalidActiveColor("x".into())
+        );
+        assert_eq!(
+            error("4k3/8/8/8/8/8/8/4K3 w KK - 0 1"),
+            FenError::InvalidCastling("KK".into())
+        );
+        assert_eq!(
+            error("4k3/8/8/8/8/8/8/4K3 w E - 0 1"),
+            FenError::InvalidCastling("E".into())
+        );
+        assert_eq!(
+            error("4k3/8/8/8/8/8/8/4K3 w - e9 0 1"),
+            FenError::InvalidEnPassant("e9".into())
+        );
+        assert_eq!(
+            error("4k3/8/8/8/8/8/8/4K3 w - - x 1"),
+            FenError::InvalidHalfmoveClock("x".into())
+        );
+        assert_eq!(
+            error("4k3/8/8/8/8/8/8/4K3 w - - 0 0"),
+            FenError::InvalidFullmoveNumber("0".into())
+        );
+
+        assert_eq!(
+            error("8/8/8/8/8/8/8/4K3 w - - 0 1"),
+            FenError::MissingKing(Color::Black)
+        );
+        assert_eq!(
+            error("4k3/8/8/8/8/8/8/3KK3 w - - 0 1"),
+            FenError::TooManyKings(Color::White)
+        );
+        assert_eq!(
+            error("4k3/8/8/8/8/P7/PPPPPPPP/4K3 w - - 0 1"),
+            FenError::TooManyPawns(Color::White)
+        );
+        assert_eq!(
+            error("4k3/8/8/8/8/8/qqqqqqqq/q2qK3 w - - 0 1"),
+            FenError::TooManyPieces(Color::Black)
+        );
+        assert_eq!(
+            error("4k3/8/8/8/8/8/8/P3K3 w - - 0 1"),
+            FenError::PawnOnBackRank(Position::pgn("a1").unwrap())
+        );
+        assert_eq!(
+            error("4k3/8/8/8/8/8/8/4r1K1 b - - 0 1"),
+            FenError::OpponentInCheck
+        );
+        assert_eq!(
+            error("4k3/8/8/8/8/8/4K3/7R w K - 0 1"),
+            FenError::CastlingWithoutKing(Color::White)
+        );
+        assert_eq!(
+            error("4k3/8/8/8/8/8/8/4K3 w K - 0 1"),
+            FenError::CastlingWithoutRook(Position::pgn("h1").unwrap())
+        );
+        // a kingside right must not refer to a rook on the queenside
+        let board = parse_fen("4k3/8/8/8/8/8/8/2R1K3 w - - 0 1").unwrap();
+        let board = BoardBuilder::from(board)
+            .enable_kingside_castle_with(Color::White, 2)
+            .build();
+        assert_eq!(
+            validate_position(&board),
+            Err(FenError::CastlingRookOnWrongSide(
+                Position::pgn("c1").unwrap()
+            ))
+        );
+        // the en passant square must be behind a pawn that just moved
+        assert_eq!(
+            error("4k3/8/8/8/8/8/8/4K3 b - e3 0 1"),
+            FenError::ImpossibleEnPassant(Position::pgn("e3").unwrap())
+        );
+        assert_eq!(
+            error("4k3/8/8/8/4P3/8/8/4K3 w - e3 0 1"),
+            FenError::ImpossibleEnPassant(Position::pgn("e3").unwrap())
+        );
+        assert_eq!(
+            error("4k3/8/8/8/4P3/8/4B3/4K3 b - e3 0 1"),
+            FenError::ImpossibleEnPassant(Position::pgn("e3").unwrap())
+        );
+        assert_eq!(
+            FenError::MissingKing(Color::Black).to_string(),
+            "Black has no king"
+        );
+    }
+
     #[test]
     fn test_parse_san_move() {
         let mut board = Board::default();
@@ -438,4 +1167,203 @@ mod tests {
             "no matching move".to_string()
         );
     }
+
+    #[test]
+    fn test_parse_formatted_san_move() {
+        let fens = [
+            "rnbqkbnr/ppp2ppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3",
+            "2k5/8/8/8/4Q2Q/8/8/K6Q w - - 0 1",
+            "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1",
+            "3r4/4P3/8/8/8/8/k7/4K3 w - - 0 1",
+        ];
+        for fen in fens {
+            let board = parse_fen(fen).unwrap();
+            for m in board.get_legal_moves() {
+                let san = format_san_move(&board, m).unwrap();
+                let parsed = parse_san_move(&board, &san).expect(&san);
+                assert_eq!(
+                    format_san_move(&board, parsed).unwrap(),
+                    san,
+                    "{} in {}",
+                    san,
+                    fen
+                );
+            }
+        }
+    }
+
+    fn san(fen: &str, m: Move) -> String {
+        format_san_move(&parse_fen(fen).unwrap(), m).unwrap()
+    }
+
+    #[test]
+    fn test_format_san_move() {
+        let board = Board::default();
+        assert_eq!(format_san_move(&board, Move::Piece(E2, E4)).unwrap(), "e4");
+        assert_eq!(format_san_move(&board, Move::Piece(G1, F3)).unwrap(), "Nf3");
+        assert_eq!(
+            format_san_move(&board, Move::Piece(E2, E5)).expect_err("e5"),
+            "illegal move".to_string()
+        );
+
+        // captures
+        let fen = "rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 2";
+        assert_eq!(san(fen, Move::Piece(E4, D5)), "exd5");
+        let fen = "rnbqkbnr/ppp2ppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3";
+        assert_eq!(san(fen, Move::Piece(E5, D6)), "exd6");
+
+        // disambiguation by file, rank and both
+        let fen = "4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1";
+        assert_eq!(san(fen, Move::Piece(B1, D2)), "Nbd2");
+        let fen = "4k3/8/8/R7/8/8/8/R3K3 w - - 0 1";
+        assert_eq!(san(fen, Move::Piece(A1, A3)), "R1a3");
+        let fen = "2k5/8/8/8/4Q2Q/8/8/K6Q w - - 0 1";
+        assert_eq!(san(fen, Move::Piece(H4, E1)), "Qh4e1");
+
+        // castling
+        let fen = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1";
+        assert_eq!(san(fen, Move::KingSideCastle), "O-O");
+        assert_eq!(san(fen, Move::QueenSideCastle), "O-O-O");
+
+        // promotions, the board promotes to a queen by default
+        let fen = "8/4P3/8/8/8/8/k7/4K3 w - - 0 1";
+        assert_eq!(san(fen, Move::Piece(E7, E8)), "e8=Q");
+        assert_eq!(
+            san(fen, Move::Promotion(E7, E8, Piece::Knight(WHITE, E8))),
+            "e8=N"
+        );
+
+        // check and checkmate
+        let fen = "rnbqkbnr/ppppp2p/5p2/6p1/4P3/8/PPPP1PPP/RNBQKBNR w KQkq g6 0 3";
+        assert_eq!(san(fen, Move::Piece(D1, H5)), "Qh5#");
+        let fen = "4k3/8/8/8/8/8/8/R3K3 w - - 0 1";
+        assert_eq!(san(fen, Move::Piece(A1, A8)), "Ra8+");
+    }
+
+    #[test]
+    fn test_format_played_san_move() {
+        // formatting from the result of playing the move agrees with
+        // formatting before it, including checks, checkmate and the end of
+        // a variant game
+        let positions = [
+            (
+                "rnbqkbnr/ppppp2p/5p2/6p1/4P3/8/PPPP1PPP/RNBQKBNR w KQkq g6 0 3",
+                Variant::Standard,
+            ),
+            ("2k5/8/8/8/4Q2Q/8/8/K6Q w - - 0 1", Variant::Standard),
+            ("3r4/4P3/8/8/8/8/k7/4K3 w - - 0 1", Variant::Standard),
+            ("4k3/8/8/8/8/8/8/R3K3 w - - 0 1", Variant::ThreeCheck),
+            ("4k3/8/8/8/8/8/8/R3K3 w - - 0 1", Variant::Antichess),
+        ];
+        for (fen, variant) in positions {
+            let board = parse_fen(fen).unwrap();
+            for m in variant.get_legal_moves(&board) {
+                let mut checks = CheckCounter::default();
+                let result = variant.play_move(&board, m, &mut checks);
+                assert_eq!(
+                    format_played_san_move(&board, m, variant, &result),
+                    format_variant_san_move(&board, m, variant),
+                    "{:?} in {}",
+                    m,
+                    fen
+                );
+            }
+        }
+
+        let board = Board::default();
+        let result = board.play_move(Move::Piece(E2, E5));
+        assert!(
+            format_played_san_move(&board, Move::Piece(E2, E5), Variant::Standard, &result)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_format_uci_move() {
+        let board = Board::default();
+        assert_eq!(
+            format_uci_move(&board, Move::Piece(E2, E4)).unwrap(),
+            "e2e4"
+        );
+        assert!(format_uci_move(&board, Move::Resign).is_err());
+
+        let board = parse_fen("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1").unwrap();
+        assert_eq!(
+            format_uci_move(&board, Move::KingSideCastle).unwrap(),
+            "e8g8"
+        );
+        assert_eq!(
+            format_uci_move(&board, Move::QueenSideCastle).unwrap(),
+            "e8c8"
+        );
+
+        let board = parse_fen("8/4P3/8/8/8/8/k7/4K3 w - - 0 1").unwrap();
+        assert_eq!(
+            format_uci_move(&board, Move::Piece(E7, E8)).unwrap(),
+            "e7e8q"
+        );
+        assert_eq!(
+            format_uci_move(&board, Move::Promotion(E7, E8, Piece::Knight(WHITE, E8))).unwrap(),
+            "e7e8n"
+        );
+
+        // Chess960 castling is the king taking its own rook
+        let board = parse_fen("4k3/8/8/8/8/8/r7/1R4K1 w B - 0 1").unwrap();
+        assert_eq!(
+            format_uci_move(&board, Move::QueenSideCastle).unwrap(),
+            "g1b1"
+        );
+        assert_eq!(
+            parse_uci_move(&board, "g1b1").unwrap(),
+            Move::QueenSideCastle
+        );
+
+        // with UCI_Chess960 on, castling is always the king taking its rook
+        let board = parse_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+        assert_eq!(
+            format_chess960_uci_move(&board, Move::KingSideCastle).unwrap(),
+            "e1h1"
+        );
+        assert_eq!(
+            format_chess960_uci_move(&board, Move::QueenSideCastle).unwrap(),
+            "e1a1"
+        );
+        assert_eq!(
+            parse_chess960_uci_move(&board, "e1h1").unwrap(),
+            Move::KingSideCastle
+        );
+        assert_eq!(
+            parse_chess960_uci_move(&board, "e1a1").unwrap(),
+            Move::QueenSideCastle
+        );
+        assert_eq!(
+            parse_chess960_uci_move(&board, "e1g1").unwrap(),
+            Move::Piece(E1, G1)
+        );
+
+        for fen in [
+            "r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1",
+            "4k3/8/8/8/8/8/r7/1R4K1 w B - 0 1",
+            "3r4/4P3/8/8/8/8/k7/4K3 w - - 0 1",
+            "rnbqkbnr/ppp2ppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3",
+        ] {
+            let board = parse_fen(fen).unwrap();
+            for m in board.get_legal_moves() {
+                let uci = format_uci_move(&board, m).unwrap();
+                let parsed = parse_uci_move(&board, &uci).unwrap();
+                assert_eq!(format_uci_move(&board, parsed).unwrap(), uci);
+                assert!(!matches!(
+                    board.play_move(parsed),
+                    GameResult::IllegalMove(_)
+                ));
+            }
+        }
+
+        // promotion to a king, as in Antichess, round-trips
+        let board = parse_fen("3r4/4P3/8/8/8/8/k7/4K3 w - - 0 1").unwrap();
+        let promotion = parse_uci_move(&board, "e7e8k").unwrap();
+        assert_eq!(promotion, Move::Promotion(E7, E8, Piece::King(WHITE, E8)));
+        assert_eq!(format_uci_move(&board, promotion).unwrap(), "e7e8k");
+        assert!(parse_uci_move(&board, "e7e8p").is_err());
+    }
 }
@@ -0,0 +1,520 @@
+use crate::{
+    bitboard::{
+        bishop_attacks, queen_attacks, rook_attacks, square_bit, square_position, squares,
+        Bitboard, BISHOP, EMPTY, KING, KING_ATTACKS, KNIGHT, KNIGHT_ATTACKS, PAWN, PAWN_ATTACKS,
+        ROOK,
+    },
+    Board, BoardBuilder, Color, GameResult, Move, PositionHistory, BLACK, WHITE,
+};
+use near_sdk::{
+    borsh::{BorshDeserialize, BorshSerialize},
+    serde::{Deserialize, Serialize},
+    NearSchema,
+};
+
+/// Number of checks a player has to give to win a Three-check game.
+pub const THREE_CHECK_LIMIT: u8 = 3;
+
+/// The d4, e4, d5 and e5 squares a king has to reach in King of the Hill.
+const HILL: Bitboard = (1 << 27) | (1 << 28) | (1 << 35) | (1 << 36);
+
+/// The rules a game is played by.
+///
+/// All variants share the moves of standard chess, except for Antichess, and
+/// differ in how a game is won:
+///
+/// - `Horde`: White starts with 36 pawns and no king, see [`Board::horde`].
+///   Black wins by capturing all of White's pieces, White by checkmate.
+///   White's pawns on the first rank only move one square at a time.
+/// - `KingOfTheHill`: moving the king to one of the four center squares
+///   also wins.
+/// - `ThreeCheck`: giving check for the third time also wins. A position
+///   only repeats if both players have given as many checks as before.
+/// - `Antichess`: captures are compulsory, the king is an ordinary piece and
+///   there is no check or castling. A player without legal moves, usually
+///   because all of their pieces were captured, wins. Pawns can also
+///   promote to a king.
+///
+/// Insufficient material only draws in Standard and Horde; in the other
+/// variants a lone king can still win or be forced to.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    BorshDeserialize,
+    BorshSerialize,
+    Deserialize,
+    Serialize,
+    NearSchema,
+)]
+#[serde(crate = "near_sdk::serde")]
+#[borsh(crate = "near_sdk::borsh")]
+pub enum Variant {
+    #[default]
+    Standard,
+    Horde,
+    KingOfTheHill,
+    ThreeCheck,
+    Antichess,
+}
+
+/// How many checks each player has given, which decides Three-check games.
+/// Like [`PositionHistory`], this is game state that a `Board` does not know
+/// about.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    BorshDeserialize,
+    BorshSerialize,
+    Deserialize,
+    Serialize,
+    NearSchema,
+)]
+#[serde(crate = "near_sdk::serde")]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct CheckCounter {
+    white: u8,
+    black: u8,
+}
+
+impl CheckCounter {
+    /// The number of checks `color` has given.
+    pub fn get(&self, color: Color) -> u8 {
+        match color {
+            WHITE => self.white,
+            BLACK => self.black,
+        }
+    }
+
+    /// Count a check given by `color` and return the new total.
+    fn record(&mut self, color: Color) -> u8 {
+        let checks = match color {
+            WHITE => &mut self.white,
+            BLACK => &mut self.black,
+        };
+        *checks = checks.saturating_add(1);
+        *checks
+    }
+}
+
+impl core::fmt::Display for Variant {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Standard => "Standard",
+                Self::Horde => "Horde",
+                Self::KingOfTheHill => "King of the Hill",
+                Self::ThreeCheck => "Three-check",
+                Self::Antichess => "Antichess",
+            }
+        )
+    }
+}
+
+impl Variant {
+    /// The position a game of this variant starts from.
+    pub fn starting_board(self) -> Board {
+        match self {
+            Self::Horde => Board::horde(),
+            Self::Antichess => Self::without_castling(Board::default()),
+            _ => Board::default(),
+        }
+    }
+
+    /// Can games of this variant start from a Chess960 position? Horde has
+    /// its own fixed starting position.
+    pub fn supports_chess960(self) -> bool {
+        self != Self::Horde
+    }
+
+    /// The Chess960 starting position `index` for this variant.
+    ///
+    /// Returns Err if the variant does not [support](Variant::supports_chess960)
+    /// Chess960.
+    pub fn chess960_board(self, index: u16) -> Result<Board, String> {
+        match self {
+            _ if !self.supports_chess960() => {
+                Err(format!("{} has no chess960 starting positions", self))
+            }
+            Self::Antichess => Ok(Self::without_castling(Board::chess960(index))),
+            _ => Ok(Board::chess960(index)),
+        }
+    }
+
+    fn without_castling(board: Board) -> Board {
+        BoardBuilder::from(board)
+            .disable_kingside_castle(WHITE)
+            .disable_queenside_castle(WHITE)
+            .disable_kingside_castle(BLACK)
+            .disable_queenside_castle(BLACK)
+            .build()
+    }
+
+    /// All legal moves of the player to move.
+    pub fn get_legal_moves(self, board: &Board) -> Vec<Move> {
+        match self {
+            Self::Antichess => get_antichess_moves(board),
+            _ => board.get_legal_moves().collect(),
+        }
+    }
+
+    /// Can the player to move play `m`?
+    pub fn is_legal_move(self, board: &Board, m: Move) -> bool {
+        match (self, m) {
+            (_, Move::Resign) => true,
+            (Self::Antichess, Move::Piece(from, to)) => {
+                get_antichess_moves(board).contains(&Move::Piece(from, to))
+            }
+            (Self::Antichess, Move::Promotion(from, to, promotion)) => {
+                board.get_piece(from).is_some_and(|piece| piece.is_pawn())
+                    && (to.get_row() == 0 || to.get_row() == 7)
+                    && !promotion.is_pawn()
+                    && get_antichess_moves(board).contains(&Move::Piece(from, to))
+            }
+            (Self::Antichess, _) => false,
+            _ => board.is_legal_move(m, board.get_turn_color()),
+        }
+    }
+
+    /// Is the player to move checkmated? There is no check in Antichess, so
+    /// this is always false there.
+    pub fn is_checkmate(self, board: &Board) -> bool {
+        match self {
+            Self::Antichess => false,
+            _ => board.is_checkmate(),
+        }
+    }
+
+    /// Is the game drawn because the player to move has no legal moves while
+    /// not in check or, in Standard and Horde, because of insufficient
+    /// material? In Antichess having no legal moves wins instead.
+    pub fn is_stalemate(self, board: &Board) -> bool {
+        match self {
+            Self::Standard | Self::Horde => board.is_stalemate(),
+            Self::KingOfTheHill | Self::ThreeCheck => {
+                board.get_legal_moves().next().is_none()
+                    && !board.is_in_check(board.get_turn_color())
+            }
+            Self::Antichess => false,
+        }
+    }
+
+    /// Play a move by the rules of this variant. `checks` is only used, and
+    /// updated, in Three-check.
+    pub fn play_move(self, board: &Board, m: Move, checks: &mut CheckCounter) -> GameResult {
+        let current_color = board.get_turn_color();
+
+        if m == Move::Resign {
+            return GameResult::Victory(!current_color);
+        }
+        if !self.is_legal_move(board, m) {
+            return GameResult::IllegalMove(m);
+        }
+
+        let next_turn = board.apply_eval_move(m);
+        if let Some(winner) = self.winner(&next_turn, current_color, checks) {
+            GameResult::Victory(winner)
+        } else if self.is_stalemate(&next_turn) {
+            GameResult::Stalemate
+        } else {
+            GameResult::Continuing(next_turn)
+        }
+    }
+
+    /// Play a move like [`Variant::play_move`], additionally recording it in
+    /// `history` to detect draws by threefold repetition and by the
+    /// fifty-move rule, like [`Board::play_move_with_history`].
+    pub fn play_move_with_history(
+        self,
+        board: &Board,
+        m: Move,
+        history: &mut PositionHistory,
+        checks: &mut CheckCounter,
+    ) -> GameResult {
+        let given = *checks;
+        let result = self.play_move(board, m, checks);
+        if let GameResult::Continuing(next_turn) = result {
+            // the check counts only go up, so no earlier position can repeat
+            // after a check
+            if *checks != given {
+                history.clear_repetitions();
+            }
+            history.push(board, m, &next_turn);
+            if history.is_threefold_repetition() {
+                return GameResult::ThreefoldRepetition;
+            }
+            if history.is_fifty_move_rule() {
+                return GameResult::FiftyMoveRule;
+            }
+        }
+        result
+    }
+
+    /// The winner after `mover` played a legal move leading to `next_turn`,
+    /// if the game is won.
+    fn winner(self, next_turn: &Board, mover: Color, checks: &mut CheckCounter) -> Option<Color> {
+        let bitboards = next_turn.bitboards();
+        let won = match self {
+            Self::Standard => false,
+            Self::Horde => bitboards.color(!mover) == EMPTY,
+            Self::KingOfTheHill => bitboards.pieces(KING, mover) & HILL != EMPTY,
+            Self::ThreeCheck => {
+                next_turn.is_in_check(!mover) && checks.record(mover) >= THREE_CHECK_LIMIT
+            }
+            Self::Antichess => {
+                return get_antichess_moves(next_turn).is_empty().then_some(!mover);
+            }
+        };
+        if won || next_turn.is_checkmate() {
+            Some(mover)
+        } else {
+            None
+        }
+    }
+}
+
+/// Generate the Antichess moves of the player to move: every piece moves as
+/// in standard chess without regard for check, there is no castling, and if
+/// any capture is possible only captures may be played.
+fn get_antichess_moves(board: &Board) -> Vec<Move> {
+    let color = board.get_turn_color();
+    let bitboards = board.bitboards();
+    let occupied = bitboards.occupied();
+    let own = bitboards.color(color);
+    let enemy = bitboards.color(!color);
+
+    let mut moves = Vec::new();
+    for from in squares(own) {
+        let from_pos = square_position(from);
+        let bit = 1 << from;
+        let targets = if bitboards.kind(PAWN) & bit != EMPTY {
+            let mut targets = PAWN_ATTACKS[color as usize][from] & enemy;
+            if let Some(en_passant) = board.get_en_passant() {
+                targets |= PAWN_ATTACKS[color as usize][from] & square_bit(en_passant);
+            }
+            let up = from_pos.pawn_up(color);
+            if up.is_on_board() && board.get_piece(up).is_none() {
+                targets |= square_bit(up);
+                let next_up = up.pawn_up(color);
+                if from_pos.is_starting_pawn(color) && board.get_piece(next_up).is_none() {
+                    targets |= square_bit(next_up);
+                }
+            }
+            targets
+        } else if bitboards.kind(KNIGHT) & bit != EMPTY {
+            KNIGHT_ATTACKS[from] & !own
+        } else if bitboards.kind(KING) & bit != EMPTY {
+            KING_ATTACKS[from] & !own
+        } else if bitboards.kind(BISHOP) & bit != EMPTY {
+            bishop_attacks(from, occupied) & !own
+        } else if bitboards.kind(ROOK) & bit != EMPTY {
+            rook_attacks(from, occupied) & !own
+        } else {
+            queen_attacks(from, occupied) & !own
+        };
+        for to in squares(targets) {
+            moves.push(Move::Piece(from_pos, square_position(to)));
+        }
+    }
+
+    if moves.iter().any(|m| board.is_capture(*m)) {
+        moves.retain(|m| board.is_capture(*m));
+    }
+    moves
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        parse_fen, Piece, A1, A4, A5, A6, A7, A8, D1, D2, D4, D5, D8, E1, E2, E3, E4, E5, E6, E7,
+        E8, F1, F3, G1, G8, H4, H7, H8,
+    };
+
+    fn from_fen(fen: &str) -> Board {
+        parse_fen(fen).unwrap()
+    }
+
+    #[test]
+    fn standard_matches_board() {
+        let start = Board::default();
+        let mut checks = CheckCounter::default();
+        let m = Move::Piece(E2, E4);
+        assert_eq!(
+            Variant::Standard.play_move(&start, m, &mut checks),
+            start.play_move(m)
+        );
+        assert_eq!(
+            Variant::Standard.get_legal_moves(&start).len(),
+            start.get_legal_moves().count()
+        );
+
+        // fool's mate
+        let board = from_fen("rnbqkbnr/pppp1ppp/8/4p3/6P1/5P2/PPPPP2P/RNBQKBNR b KQkq g3 0 2");
+        assert_eq!(
+            Variant::Standard.play_move(&board, Move::Piece(D8, H4), &mut checks),
+            GameResult::Victory(BLACK)
+        );
+    }
+
+    #[test]
+    fn horde() {
+        let start = Variant::Horde.starting_board();
+        assert!(start.get_king_pos(WHITE).is_none());
+        assert!(!start.get_castling_rights(WHITE).can_kingside_castle());
+        assert_eq!(start.count_pieces(), 36 + 16);
+        assert!(Variant::Horde.chess960_board(0).is_err());
+        assert!(Variant::Horde.is_legal_move(&start, Move::Piece(A4, A5)));
+
+        // capturing White's last piece wins for Black
+        let board = from_fen("4k3/8/8/8/8/8/4p3/3P4 b - - 0 1");
+        let mut checks = CheckCounter::default();
+        assert_eq!(
+            Variant::Horde.play_move(&board, Move::Piece(E2, D1), &mut checks),
+            GameResult::Victory(BLACK)
+        );
+        // by the standard rules White is just left without moves
+        assert_eq!(
+            Variant::Standard.play_move(&board, Move::Piece(E2, D1), &mut checks),
+            GameResult::Stalemate
+        );
+    }
+
+    #[test]
+    fn king_of_the_hill() {
+        let board = from_fen("4k3/8/8/8/8/4K3/8/8 w - - 0 1");
+        let mut checks = CheckCounter::default();
+        assert_eq!(
+            Variant::KingOfTheHill.play_move(&board, Move::Piece(E3, E4), &mut checks),
+            GameResult::Victory(WHITE)
+        );
+        assert!(matches!(
+            Variant::KingOfTheHill.play_move(&board, Move::Piece(E3, F3), &mut checks),
+            GameResult::Continuing(_)
+        ));
+        // bare kings are only a draw in standard chess
+        assert_eq!(
+            Variant::Standard.play_move(&board, Move::Piece(E3, D4), &mut checks),
+            GameResult::Stalemate
+        );
+    }
+
+    #[test]
+    fn three_check() {
+        let mut board = from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1");
+        let mut checks = CheckCounter::default();
+        for m in [
+            Move::Piece(A1, A8),
+            Move::Piece(E8, E7),
+            Move::Piece(A8, A7),
+            Move::Piece(E7, E6),
+        ] {
+            match Variant::ThreeCheck.play_move(&board, m, &mut checks) {
+                GameResult::Continuing(next) => board = next,
+                result => panic!("unexpected {result:?} after {m}"),
+            }
+        }
+        assert_eq!(checks.get(WHITE), 2);
+        assert_eq!(
+            Variant::ThreeCheck.play_move(&board, Move::Piece(A7, A6), &mut checks),
+            GameResult::Victory(WHITE)
+        );
+        assert_eq!(checks.get(WHITE), THREE_CHECK_LIMIT);
+        assert_eq!(checks.get(BLACK), 0);
+    }
+
+    #[test]
+    fn three_check_repetition() {
+        let start = from_fen("7k/8/8/8/8/8/8/R5K1 w - - 0 1");
+        let play = |variant: Variant, cycle: [Move; 4]| {
+            let mut board = start;
+            let mut history = PositionHistory::new(&board);
+            let mut checks = CheckCounter::default();
+            let mut result = GameResult::Continuing(board);
+            for m in cycle.iter().chain(cycle.iter()) {
+                result = variant.play_move_with_history(&board, *m, &mut history, &mut checks);
+                if let GameResult::Continuing(next) = result {
+                    board = next;
+                }
+            }
+            (result, checks)
+        };
+
+        // the start position comes back twice, but with a check given each time
+        let checking = [
+            Move::Piece(A1, A8),
+            Move::Piece(H8, H7),
+            Move::Piece(A8, A1),
+            Move::Piece(H7, H8),
+        ];
+        let (result, checks) = play(Variant::ThreeCheck, checking);
+        assert_eq!(result, GameResult::Continuing(start));
+        assert_eq!(checks.get(WHITE), 2);
+        assert_eq!(
+            play(Variant::Standard, checking).0,
+            GameResult::ThreefoldRepetition
+        );
+
+        // without checks it is an ordinary repetition
+        let quiet = [
+            Move::Piece(G1, F1),
+            Move::Piece(H8, G8),
+            Move::Piece(F1, G1),
+            Move::Piece(G8, H8),
+        ];
+        assert_eq!(
+            play(Variant::ThreeCheck, quiet).0,
+            GameResult::ThreefoldRepetition
+        );
+    }
+
+    #[test]
+    fn antichess() {
+        let start = Variant::Antichess.starting_board();
+        assert_eq!(Variant::Antichess.get_legal_moves(&start).len(), 20);
+        assert!(!Variant::Antichess.is_legal_move(&start, Move::KingSideCastle));
+
+        // a capture is available, so it has to be played
+        let board = from_fen("4k3/8/8/3p4/4P3/8/8/4K3 w - - 0 1");
+        assert_eq!(
+            Variant::Antichess.get_legal_moves(&board),
+            vec![Move::Piece(E4, D5)]
+        );
+        assert!(!Variant::Antichess.is_legal_move(&board, Move::Piece(E4, E5)));
+
+        // taking Black's last piece leaves Black without moves, which wins
+        let board = from_fen("8/8/8/8/8/8/3q4/4K3 w - - 0 1");
+        assert!(!Variant::Antichess.is_checkmate(&board));
+        let mut checks = CheckCounter::default();
+        assert_eq!(
+            Variant::Antichess.play_move(&board, Move::Piece(E1, D2), &mut checks),
+            GameResult::Victory(BLACK)
+        );
+
+        // pawns can promote to a king, unlike in standard chess
+        let board = from_fen("8/4P3/8/8/8/8/8/k7 w - - 0 1");
+        let promotion = Move::Promotion(E7, E8, Piece::King(WHITE, E8));
+        assert!(Variant::Antichess.is_legal_move(&board, promotion));
+        assert!(!Variant::Standard.is_legal_move(&board, promotion));
+        match Variant::Antichess.play_move(&board, promotion, &mut checks) {
+            GameResult::Continuing(next) => {
+                assert_eq!(next.get_piece(E8), Some(Piece::King(WHITE, E8)))
+            }
+            result => panic!("unexpected {result:?}"),
+        }
+        let parsed = Move::parse("e7 to e8 king".to_string()).unwrap();
+        assert!(Variant::Antichess.is_legal_move(&board, parsed));
+        assert!(!Variant::Standard.is_legal_move(&board, parsed));
+    }
+}
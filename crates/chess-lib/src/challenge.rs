@@ -1,4 +1,5 @@
 use crate::ContractError;
+use chess_engine::Variant;
 use near_sdk::{
     borsh::{BorshDeserialize, BorshSerialize},
     env,
@@ -32,6 +33,34 @@ pub struct Challenge {
     challenger: AccountId,
     challenged: AccountId,
     wager: Wager,
+    /// Start from a random Chess960 position instead of the standard one.
+    chess960: bool,
+    variant: Variant,
+}
+
+/// Layout of [`Challenge`] before the `chess960` and `variant` options were
+/// added.
+/// Used only by [`Chess::migrate`](crate::Chess::migrate).
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct ChallengeV1 {
+    id: String,
+    challenger: AccountId,
+    challenged: AccountId,
+    wager: Wager,
+}
+
+impl From<ChallengeV1> for Challenge {
+    fn from(challenge: ChallengeV1) -> Self {
+        Self {
+            id: challenge.id,
+            challenger: challenge.challenger,
+            challenged: challenge.challenged,
+            wager: challenge.wager,
+            chess960: false,
+            variant: Variant::Standard,
+        }
+    }
 }
 
 pub fn create_challenge_id<T: fmt::Display>(challenger: T, challenged: T) -> String {
@@ -39,12 +68,20 @@ pub fn create_challenge_id<T: fmt::Display>(challenger: T, challenged: T) -> Str
 }
 
 impl Challenge {
-    pub fn new(challenger: AccountId, challenged: AccountId, wager: Wager) -> Self {
+    pub fn new(
+        challenger: AccountId,
+        challenged: AccountId,
+        wager: Wager,
+        chess960: bool,
+        variant: Variant,
+    ) -> Self {
         Self {
             id: create_challenge_id(&challenger, &challenged),
             challenger,
             challenged,
             wager,
+            chess960,
+            variant,
         }
     }
 
@@ -90,4 +127,12 @@ impl Challenge {
     pub fn get_challenged(&self) -> &AccountId {
         &self.challenged
     }
+
+    pub fn is_chess960(&self) -> bool {
+        self.chess960
+    }
+
+    pub fn get_variant(&self) -> Variant {
+        self.variant
+    }
 }
@@ -2,6 +2,7 @@ use near_sdk::{borsh::BorshSerialize, AccountId, FunctionError};
 use thiserror::Error;
 
 use crate::ChallengeId;
+use chess_engine::Variant;
 
 #[derive(BorshSerialize, Debug, Error, FunctionError)]
 #[borsh(crate = "near_sdk::borsh")]
@@ -79,4 +80,6 @@ pub enum ContractError {
     MatchmakingQueueFull,
     #[error("Minimum elo cannot be greater than maximum elo")]
     InvalidEloRange,
+    #[error("{} can't be played from a Chess960 starting position", _0)]
+    Chess960NotSupported(Variant),
 }
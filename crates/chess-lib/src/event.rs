@@ -21,13 +21,20 @@ pub enum ChessEvent {
         black: Player,
         board: [String; 8],
     },
-    #[event_version("1.0.0")]
+    #[event_version("1.1.0")]
     PlayMove {
         game_id: GameId,
         color: Color,
         mv: MoveStr,
+        /// Move in Standard Algebraic Notation, e.g. "Nxf7+"
+        san: MoveStr,
         board: [String; 8],
         outcome: Option<GameOutcome>,
+        /// Summary of the search behind an AI move, e.g. `depth 3 nodes 5210
+        /// ...`. Only logged by contracts built with the `search-stats`
+        /// feature.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        search: Option<String>,
     },
     #[event_version("1.0.0")]
     ResignGame {
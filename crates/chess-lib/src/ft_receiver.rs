@@ -1,4 +1,5 @@
 use crate::{ChallengeId, Chess, ChessExt, ContractError};
+use chess_engine::Variant;
 use near_contract_standards::fungible_token::receiver::FungibleTokenReceiver;
 use near_sdk::{
     env,
@@ -21,6 +22,11 @@ pub enum FtReceiverMsg {
 #[serde(crate = "near_sdk::serde")]
 pub struct ChallengeMsg {
     pub challenged_id: AccountId,
+    /// Start from a random Chess960 position instead of the standard one.
+    #[serde(default)]
+    pub chess960: bool,
+    #[serde(default)]
+    pub variant: Variant,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -41,6 +47,12 @@ pub struct BetMsg {
 pub struct MatchmakingMsg {
     pub min_elo: f64,
     pub max_elo: f64,
+    /// Only match with players who also want a random Chess960 position.
+    #[serde(default)]
+    pub chess960: bool,
+    /// Only match with players who also want to play this variant.
+    #[serde(default)]
+    pub variant: Variant,
 }
 
 #[near_bindgen]
@@ -74,9 +86,19 @@ impl Chess {
         }
 
         let refund = match msg {
-            FtReceiverMsg::Challenge(ChallengeMsg { challenged_id }) => {
+            FtReceiverMsg::Challenge(ChallengeMsg {
+                challenged_id,
+                chess960,
+                variant,
+            }) => {
                 let challenger_id = sender_id;
-                self.internal_challenge(challenger_id, challenged_id, Some((token_id, amount)))?;
+                self.internal_challenge(
+                    challenger_id,
+                    challenged_id,
+                    Some((token_id, amount)),
+                    chess960,
+                    variant,
+                )?;
                 None
             }
             FtReceiverMsg::AcceptChallenge(AcceptChallengeMsg { challenge_id }) => {
@@ -92,12 +114,19 @@ impl Chess {
                 self.internal_bet(sender_id, token_id, amount.0, players, winner)?;
                 None
             }
-            FtReceiverMsg::Matchmaking(MatchmakingMsg { min_elo, max_elo }) => {
+            FtReceiverMsg::Matchmaking(MatchmakingMsg {
+                min_elo,
+                max_elo,
+                chess960,
+                variant,
+            }) => {
                 self.internal_join_matchmaking(
                     sender_id,
                     min_elo,
                     max_elo,
                     Some((token_id, amount)),
+                    chess960,
+                    variant,
                 )?;
                 None
             }
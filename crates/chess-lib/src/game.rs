@@ -3,10 +3,9 @@ use crate::{
     AI_VERY_HARD_GAS,
 };
 use chess_engine::{
-    get_endgame_move, static_book::lookup_opening, Board, Color, GameResult, Move, Piece, Position,
-    FLAG_CHECK_EXTENSIONS, FLAG_ENDGAME_HEURISTICS, FLAG_ITERATIVE_DEEPENING,
-    FLAG_KILLER_HEURISTIC, FLAG_LATE_MOVE_REDUCTION, FLAG_MOVE_ORDERING, FLAG_NULL_MOVE_PRUNING,
-    FLAG_OPENING_BOOK, FLAG_QUIESCENCE,
+    format_played_san_move, get_ai_move_with_stats, Board, CheckCounter, Color, GameResult,
+    GasLimit, Move, Piece, Position, PositionHistory, Variant, AI_FLAGS_EASY, AI_FLAGS_HARD,
+    AI_FLAGS_MEDIUM, AI_FLAGS_VERY_HARD,
 };
 use near_sdk::{
     borsh::{BorshDeserialize, BorshSerialize},
@@ -22,9 +21,6 @@ const AI_MAX_DEPTHS_EASY: &[u8] = &[1];
 const AI_MAX_DEPTHS_MEDIUM: &[u8] = &[14, 14];
 const AI_MAX_DEPTHS_HARD: &[u8] = &[10, 12, 8];
 const AI_MAX_DEPTHS_VERY_HARD: &[u8] = &[9, 10, 8, 6];
-const AI_PIECE_COUNT_CLAMP_MIN: f64 = 4.0;
-const AI_PIECE_COUNT_CLAMP_MAX: f64 = 32.0;
-const AI_PIECE_SCALE_DIVISOR: f64 = 16.0;
 
 /// Unique game ID, which consists of:
 ///
@@ -132,44 +128,34 @@ pub enum Difficulty {
 }
 
 impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [
+        Difficulty::Easy,
+        Difficulty::Medium,
+        Difficulty::Hard,
+        Difficulty::VeryHard,
+    ];
+
+    /// The sampling widths per ply of the AI's search before
+    /// [`scale_depths`](chess_engine::scale_depths) widens them.
+    pub fn max_depths(&self) -> &'static [u8] {
+        match self {
+            Self::Easy => AI_MAX_DEPTHS_EASY,
+            Self::Medium => AI_MAX_DEPTHS_MEDIUM,
+            Self::Hard => AI_MAX_DEPTHS_HARD,
+            Self::VeryHard => AI_MAX_DEPTHS_VERY_HARD,
+        }
+    }
+
     pub fn to_flags(&self) -> u16 {
         #[cfg(feature = "integration-test")]
         if matches!(self, Self::Easy) {
             return 0;
         }
         match self {
-            // Easy:       check extensions + move ordering (MVV-LVA)
-            // Medium:     + opening book + null-move pruning + quiescence search
-            // Hard:       + endgame heuristics + iterative deepening
-            // Very Hard:  + killer heuristic + late-move reduction
-            Self::Easy => FLAG_CHECK_EXTENSIONS | FLAG_MOVE_ORDERING,
-            Self::Medium => {
-                FLAG_CHECK_EXTENSIONS
-                    | FLAG_NULL_MOVE_PRUNING
-                    | FLAG_MOVE_ORDERING
-                    | FLAG_QUIESCENCE
-                    | FLAG_OPENING_BOOK
-            }
-            Self::Hard => {
-                FLAG_CHECK_EXTENSIONS
-                    | FLAG_NULL_MOVE_PRUNING
-                    | FLAG_MOVE_ORDERING
-                    | FLAG_QUIESCENCE
-                    | FLAG_ITERATIVE_DEEPENING
-                    | FLAG_OPENING_BOOK
-                    | FLAG_ENDGAME_HEURISTICS
-            }
-            Self::VeryHard => {
-                FLAG_CHECK_EXTENSIONS
-                    | FLAG_NULL_MOVE_PRUNING
-                    | FLAG_MOVE_ORDERING
-                    | FLAG_QUIESCENCE
-                    | FLAG_ITERATIVE_DEEPENING
-                    | FLAG_KILLER_HEURISTIC
-                    | FLAG_LATE_MOVE_REDUCTION
-                    | FLAG_OPENING_BOOK
-                    | FLAG_ENDGAME_HEURISTICS
-            }
+            Self::Easy => AI_FLAGS_EASY,
+            Self::Medium => AI_FLAGS_MEDIUM,
+            Self::Hard => AI_FLAGS_HARD,
+            Self::VeryHard => AI_FLAGS_VERY_HARD,
         }
     }
 }
@@ -182,18 +168,36 @@ pub enum Game {
     V3(()),
     V4(GameV4),
     V5(GameV5),
+    V6(GameV6),
 }
 
-macro_rules! access_v4_v5 {
+macro_rules! access_game {
     ($self:expr, $var:ident, $body:expr) => {
         match $self {
             Game::V4($var) => $body,
             Game::V5($var) => $body,
+            Game::V6($var) => $body,
             _ => panic!("migration required"),
         }
     };
 }
 
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct GameV6 {
+    game_id: GameId,
+    white: Player,
+    black: Player,
+    board: Board,
+    wager: Wager,
+    last_move_block_height: u64,
+    has_bets: bool,
+    move_count: u32,
+    history: PositionHistory,
+    variant: Variant,
+    checks: CheckCounter,
+}
+
 #[derive(BorshDeserialize, BorshSerialize)]
 #[borsh(crate = "near_sdk::borsh")]
 pub struct GameV5 {
@@ -227,6 +231,7 @@ pub struct GameInfo {
     pub turn_color: Color,
     pub last_block_height: u64,
     pub has_bets: bool,
+    pub variant: Variant,
 }
 
 #[derive(
@@ -245,25 +250,64 @@ pub struct GameInfo {
 pub enum GameOutcome {
     Victory(Color),
     Stalemate,
+    /// Draw by the same position occurring three times.
+    ThreefoldRepetition,
+    /// Draw after fifty moves by each side without a capture or pawn move.
+    FiftyMoveRule,
+}
+
+impl GameOutcome {
+    /// The outcome of a drawn [`GameResult`].
+    fn draw(result: &GameResult) -> Self {
+        match result {
+            GameResult::ThreefoldRepetition => GameOutcome::ThreefoldRepetition,
+            GameResult::FiftyMoveRule => GameOutcome::FiftyMoveRule,
+            _ => GameOutcome::Stalemate,
+        }
+    }
 }
 
 impl Game {
-    pub fn new(white: Player, black: Player, wager: Wager, has_bets: bool) -> Self {
+    /// The board a new game of `variant` starts from: its usual starting
+    /// position, or for Chess960 one of the 960 starting positions picked
+    /// with the block's random seed.
+    pub fn starting_board(variant: Variant, chess960: bool) -> Result<Board, ContractError> {
+        if !chess960 {
+            return Ok(variant.starting_board());
+        }
+        let seed = env::random_seed();
+        let index = u32::from_le_bytes([seed[0], seed[1], seed[2], seed[3]]) % 960;
+        variant
+            .chess960_board(index as u16)
+            .map_err(|_| ContractError::Chess960NotSupported(variant))
+    }
+
+    pub fn new(
+        white: Player,
+        black: Player,
+        wager: Wager,
+        has_bets: bool,
+        board: Board,
+        variant: Variant,
+    ) -> Self {
         let block_height = env::block_height();
         let game_id = GameId(
             block_height,
             white.get_account_id().unwrap(),
             black.get_account_id(),
         );
-        Game::V5(GameV5 {
+        Game::V6(GameV6 {
             game_id,
             white,
             black,
-            board: Board::default(),
+            board,
             wager,
             last_move_block_height: env::block_height(),
             has_bets,
             move_count: 0,
+            history: PositionHistory::new(&board),
+            variant,
+            checks: CheckCounter::default(),
         })
     }
 
@@ -277,7 +321,7 @@ impl Game {
                 wager,
                 last_move_block_height,
                 has_bets,
-            }) => Self::V5(GameV5 {
+            }) => Self::V6(GameV6 {
                 game_id,
                 white,
                 black,
@@ -286,37 +330,64 @@ impl Game {
                 last_move_block_height,
                 has_bets,
                 move_count: 0,
+                history: PositionHistory::new(&board),
+                variant: Variant::Standard,
+                checks: CheckCounter::default(),
+            }),
+            // positions before the migration are unknown, so repetition
+            // counting and the fifty-move rule start from the current board
+            Self::V5(GameV5 {
+                game_id,
+                white,
+                black,
+                board,
+                wager,
+                last_move_block_height,
+                has_bets,
+                move_count,
+            }) => Self::V6(GameV6 {
+                game_id,
+                white,
+                black,
+                board,
+                wager,
+                last_move_block_height,
+                has_bets,
+                move_count,
+                history: PositionHistory::new(&board),
+                variant: Variant::Standard,
+                checks: CheckCounter::default(),
             }),
             other => other,
         }
     }
 
     pub fn get_game_id(&self) -> &GameId {
-        access_v4_v5!(self, game, &game.game_id)
+        access_game!(self, game, &game.game_id)
     }
 
     pub fn get_white(&self) -> &Player {
-        access_v4_v5!(self, game, &game.white)
+        access_game!(self, game, &game.white)
     }
 
     pub fn get_black(&self) -> &Player {
-        access_v4_v5!(self, game, &game.black)
+        access_game!(self, game, &game.black)
     }
 
     pub fn get_board(&self) -> &Board {
-        access_v4_v5!(self, game, &game.board)
+        access_game!(self, game, &game.board)
     }
 
     pub fn get_wager(&self) -> &Wager {
-        access_v4_v5!(self, game, &game.wager)
+        access_game!(self, game, &game.wager)
     }
 
     pub fn get_last_block_height(&self) -> u64 {
-        access_v4_v5!(self, game, game.last_move_block_height)
+        access_game!(self, game, game.last_move_block_height)
     }
 
     pub fn is_turn(&self, account_id: &AccountId) -> bool {
-        access_v4_v5!(self, game, {
+        access_game!(self, game, {
             let player = match game.board.get_turn_color() {
                 Color::White => &game.white,
                 Color::Black => &game.black,
@@ -330,7 +401,7 @@ impl Game {
     }
 
     pub fn is_player(&self, account_id: &AccountId) -> bool {
-        access_v4_v5!(self, game, {
+        access_game!(self, game, {
             if let Player::Human(id) = &game.white {
                 if id == account_id {
                     return true;
@@ -346,12 +417,20 @@ impl Game {
     }
 
     pub fn has_bets(&self) -> bool {
-        access_v4_v5!(self, game, game.has_bets)
+        access_game!(self, game, game.has_bets)
+    }
+
+    pub fn get_variant(&self) -> Variant {
+        match self {
+            Game::V6(game) => game.variant,
+            _ => Variant::Standard,
+        }
     }
 
     pub fn get_move_count(&self) -> u32 {
         match self {
             Game::V5(game) => game.move_count,
+            Game::V6(game) => game.move_count,
             _ => 0,
         }
     }
@@ -361,12 +440,23 @@ impl Game {
         &mut self,
         mv: Move,
     ) -> Result<(Option<(GameOutcome, [String; 8])>, Color), ContractError> {
-        let Game::V5(game) = self else {
+        if matches!(self, Game::V4(_) | Game::V5(_)) {
+            *self = std::mem::replace(self, Game::V1(())).migrate();
+        }
+        let Game::V6(game) = self else {
             panic!("migration required")
         };
 
         let turn_color = game.board.get_turn_color();
-        let (outcome, board_state, board) = match game.board.play_move(mv) {
+        let result = game.variant.play_move_with_history(
+            &game.board,
+            mv,
+            &mut game.history,
+            &mut game.checks,
+        );
+        let san = format_played_san_move(&game.board, mv, game.variant, &result)
+            .map_err(|_| ContractError::IllegalMove)?;
+        let (outcome, board_state, board) = match result {
             GameResult::Continuing(board) => {
                 game.board = board;
                 (None, Self::_get_board_state(&board), Some(board))
@@ -375,9 +465,9 @@ impl Game {
                 let board_state = Self::_get_board_state(&game.board.apply_eval_move(mv));
                 (Some(GameOutcome::Victory(color)), board_state, None)
             }
-            GameResult::Stalemate => {
+            GameResult::Stalemate | GameResult::ThreefoldRepetition | GameResult::FiftyMoveRule => {
                 let board_state = Self::_get_board_state(&game.board.apply_eval_move(mv));
-                (Some(GameOutcome::Stalemate), board_state, None)
+                (Some(GameOutcome::draw(&result)), board_state, None)
             }
             GameResult::IllegalMove(_) => return Err(ContractError::IllegalMove),
         };
@@ -385,28 +475,15 @@ impl Game {
             game_id: game.game_id.clone(),
             color: turn_color,
             mv: mv.to_string(),
+            san,
             board: board_state.clone(),
             outcome: outcome.clone(),
+            search: None,
         };
         event.emit();
 
         let mut outcome_with_board = outcome.map(|outcome| (outcome, board_state));
         if let (Player::Ai(difficulty), Some(board)) = (&game.black, board) {
-            let max_depths: &[u8] = match difficulty {
-                Difficulty::Easy => AI_MAX_DEPTHS_EASY,
-                Difficulty::Medium => AI_MAX_DEPTHS_MEDIUM,
-                Difficulty::Hard => AI_MAX_DEPTHS_HARD,
-                Difficulty::VeryHard => AI_MAX_DEPTHS_VERY_HARD,
-            };
-
-            let piece_count = (board.count_pieces() as f64)
-                .clamp(AI_PIECE_COUNT_CLAMP_MIN, AI_PIECE_COUNT_CLAMP_MAX);
-            let scale = (AI_PIECE_SCALE_DIVISOR / piece_count).max(1.0);
-            let depths: Vec<u8> = max_depths
-                .iter()
-                .map(|d| (*d as f64 * scale).round().max(1.0) as u8)
-                .collect();
-
             let gas_budget = match difficulty {
                 Difficulty::Easy => AI_EASY_GAS,
                 Difficulty::Medium => AI_MEDIUM_GAS,
@@ -414,38 +491,23 @@ impl Game {
                 Difficulty::VeryHard => AI_VERY_HARD_GAS,
             };
 
-            let flags = difficulty.to_flags();
-
-            let seed = env::random_seed_array();
-
-            let book_move = if (flags & FLAG_OPENING_BOOK) != 0 {
-                lookup_opening(board.zobrist_key(), seed[0])
-            } else {
-                None
-            };
-
-            let endgame_move = if (flags & FLAG_ENDGAME_HEURISTICS) != 0 {
-                get_endgame_move(&board)
-            } else {
-                None
-            };
-
             let turn_color = game.board.get_turn_color();
-            let (ai_mv, _, _) = if let Some(mv) = endgame_move {
-                (mv, 0, 0.0)
-            } else if let Some(mv) = book_move {
-                if board.move_blunders_material(mv) {
-                    board.get_next_move(&depths, seed, gas_budget, flags)
-                } else {
-                    (mv, 0, 0.0)
-                }
-            } else if flags == 0 {
-                let mv = board.get_legal_moves().next().unwrap_or(Move::Resign);
-                (mv, 0, 0.0)
-            } else {
-                board.get_next_move(&depths, seed, gas_budget, flags)
-            };
-            let (outcome, board_state) = match board.play_move(ai_mv) {
+            let (ai_mv, stats) = get_ai_move_with_stats(
+                &board,
+                difficulty.max_depths(),
+                env::random_seed_array(),
+                &GasLimit(gas_budget),
+                difficulty.to_flags(),
+            );
+            let result = game.variant.play_move_with_history(
+                &board,
+                ai_mv,
+                &mut game.history,
+                &mut game.checks,
+            );
+            let san = format_played_san_move(&board, ai_mv, game.variant, &result)
+                .map_err(|_| ContractError::IllegalMove)?;
+            let (outcome, board_state) = match result {
                 GameResult::Continuing(board) => {
                     game.board = board;
                     (None, Self::_get_board_state(&board))
@@ -454,9 +516,11 @@ impl Game {
                     let board_state = Self::_get_board_state(&board.apply_eval_move(ai_mv));
                     (Some(GameOutcome::Victory(color)), board_state)
                 }
-                GameResult::Stalemate => {
+                GameResult::Stalemate
+                | GameResult::ThreefoldRepetition
+                | GameResult::FiftyMoveRule => {
                     let board_state = Self::_get_board_state(&board.apply_eval_move(ai_mv));
-                    (Some(GameOutcome::Stalemate), board_state)
+                    (Some(GameOutcome::draw(&result)), board_state)
                 }
                 GameResult::IllegalMove(_) => return Err(ContractError::IllegalMove),
             };
@@ -464,8 +528,14 @@ impl Game {
                 game_id: game.game_id.clone(),
                 color: turn_color,
                 mv: ai_mv.to_string(),
+                san,
                 board: board_state.clone(),
                 outcome: outcome.clone(),
+                search: if cfg!(feature = "search-stats") {
+                    stats.map(|stats| stats.to_string())
+                } else {
+                    None
+                },
             };
             event.emit();
 
@@ -478,7 +548,7 @@ impl Game {
     }
 
     pub fn get_board_state(&self) -> [String; 8] {
-        access_v4_v5!(self, game, Self::_get_board_state(&game.board))
+        access_game!(self, game, Self::_get_board_state(&game.board))
     }
 
     pub fn _get_board_state(board: &Board) -> [String; 8] {
@@ -513,7 +583,7 @@ impl Game {
     }
 
     pub fn render_board(&self) -> String {
-        access_v4_v5!(self, game, {
+        access_game!(self, game, {
             (-1..8)
                 .rev()
                 .flat_map(|row| {
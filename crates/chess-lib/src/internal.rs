@@ -4,7 +4,7 @@ use crate::{
     Player, Quest, Wager, FT_TRANSFER_GAS, MIN_GAME_DEVELOPMENT, MIN_GAME_DURATION_BLOCKS,
     MIN_GAME_MOVES, ONE_YOCTO, WAGER_PAYOUT_CALLBACK_GAS,
 };
-use chess_engine::Color;
+use chess_engine::{Color, Variant};
 use near_contract_standards::fungible_token::core::ext_ft_core;
 use near_sdk::{env, AccountId, NearToken};
 use primitive_types::U128;
@@ -16,8 +16,19 @@ impl Chess {
         challenger_id: AccountId,
         challenged_id: AccountId,
         wager: Wager,
+        chess960: bool,
+        variant: Variant,
     ) -> Result<(), ContractError> {
-        let challenge = Challenge::new(challenger_id.clone(), challenged_id.clone(), wager);
+        if chess960 && !variant.supports_chess960() {
+            return Err(ContractError::Chess960NotSupported(variant));
+        }
+        let challenge = Challenge::new(
+            challenger_id.clone(),
+            challenged_id.clone(),
+            wager,
+            chess960,
+            variant,
+        );
 
         if self.challenges.contains_key(challenge.id())
             || self
@@ -101,6 +112,8 @@ impl Chess {
             Player::Human(challenged_id.clone()),
             paid_wager,
             has_bets,
+            Game::starting_board(challenge.get_variant(), challenge.is_chess960())?,
+            challenge.get_variant(),
         );
         let game_id = game.get_game_id().clone();
 
@@ -305,7 +318,9 @@ impl Chess {
                         )
                         .detach();
                 }
-                GameOutcome::Stalemate => {
+                GameOutcome::Stalemate
+                | GameOutcome::ThreefoldRepetition
+                | GameOutcome::FiftyMoveRule => {
                     let white_id = game.get_white().get_account_id().unwrap().clone();
                     let black_id = game.get_black().get_account_id().unwrap().clone();
                     ext_ft_core::ext(token_id.clone())
@@ -441,7 +456,9 @@ impl Chess {
                         }
                     }
                 }
-                GameOutcome::Stalemate => {
+                GameOutcome::Stalemate
+                | GameOutcome::ThreefoldRepetition
+                | GameOutcome::FiftyMoveRule => {
                     for (token_id, bets) in all_bets.bets.iter() {
                         for (account_id, bet) in bets {
                             self.accounts
@@ -24,7 +24,7 @@ pub use matchmaking::*;
 pub use points::*;
 pub use storage::*;
 
-use chess_engine::{Color, Move};
+use chess_engine::{Board, Color, Move, Variant};
 use near_contract_standards::fungible_token::core::ext_ft_core;
 use near_sdk::{
     assert_one_yocto,
@@ -113,6 +113,8 @@ pub enum StorageKey {
     V9BetsInner,
     ChallengesV2,
     MatchmakingQueue,
+    ChallengesV3,
+    MatchmakingQueueV2,
 }
 
 #[near_bindgen]
@@ -135,7 +137,8 @@ pub struct Chess {
 
 impl near_sdk::state::ContractState for Chess {}
 
-/// Previous top-level contract state (before `matchmaking_queue` was added).
+/// Previous top-level contract state (before challenges and matchmaking
+/// entries gained the `chess960` and `variant` options).
 /// Used only by [`Chess::migrate`].
 #[derive(BorshDeserialize)]
 #[borsh(crate = "near_sdk::borsh")]
@@ -144,7 +147,7 @@ struct OldChess {
     owner_id: AccountId,
     accounts: IterableMap<AccountId, Account>,
     games: IterableMap<GameId, Game>,
-    challenges: IterableMap<ChallengeId, Challenge>,
+    challenges: IterableMap<ChallengeId, ChallengeV1>,
     treasury: IterableMap<AccountId, u128>,
     fees: Lazy<u16>,
     token_whitelist: Lazy<Vec<AccountId>>,
@@ -152,6 +155,7 @@ struct OldChess {
     bettor_active_bets: IterableMap<AccountId, u32>,
     is_running: bool,
     points_total_supply: u128,
+    matchmaking_queue: IterableMap<AccountId, MatchmakingEntryV1>,
 }
 
 /// A valid move will be parsed from a string.
@@ -176,7 +180,7 @@ impl Chess {
             owner_id,
             accounts: IterableMap::new(StorageKey::VAccounts),
             games: IterableMap::new(StorageKey::Games),
-            challenges: IterableMap::new(StorageKey::Challenges),
+            challenges: IterableMap::new(StorageKey::ChallengesV3),
             treasury: IterableMap::new(StorageKey::Treasury),
             fees: Lazy::new(StorageKey::Fees, 0),
             token_whitelist: Lazy::new(StorageKey::TokenWhitelist, Vec::new()),
@@ -184,19 +188,26 @@ impl Chess {
             bettor_active_bets: IterableMap::new(StorageKey::BettorActiveBets),
             is_running: true,
             points_total_supply: 0,
-            matchmaking_queue: IterableMap::new(StorageKey::MatchmakingQueue),
+            matchmaking_queue: IterableMap::new(StorageKey::MatchmakingQueueV2),
         })
     }
 
     #[private]
     #[init(ignore_state)]
     pub fn migrate() -> Self {
-        let old: OldChess = env::state_read().unwrap();
+        let mut old: OldChess = env::state_read().unwrap();
+        // open challenges are rewritten with the new layout under a new prefix
+        let mut challenges = IterableMap::new(StorageKey::ChallengesV3);
+        for (challenge_id, challenge) in old.challenges.drain() {
+            challenges.insert(challenge_id, Challenge::from(challenge));
+        }
+        // and so are queued players
+        let matchmaking_queue = migrate_matchmaking_queue(&mut old.matchmaking_queue);
         Self {
             owner_id: old.owner_id,
             accounts: old.accounts,
             games: old.games,
-            challenges: old.challenges,
+            challenges,
             treasury: old.treasury,
             fees: old.fees,
             token_whitelist: old.token_whitelist,
@@ -204,7 +215,7 @@ impl Chess {
             bettor_active_bets: old.bettor_active_bets,
             is_running: old.is_running,
             points_total_supply: old.points_total_supply,
-            matchmaking_queue: IterableMap::new(StorageKey::MatchmakingQueue),
+            matchmaking_queue,
         }
     }
 
@@ -313,6 +324,8 @@ impl Chess {
             Player::Ai(difficulty),
             None,
             false,
+            Board::default(),
+            Variant::Standard,
         );
         let game_id = game.get_game_id().clone();
 
@@ -332,16 +345,31 @@ impl Chess {
 
     /// Challenges a player to a non-money match.
     ///
+    /// With `chess960` the game starts from a random Chess960 (Fischer
+    /// Random) position, picked when the challenge is accepted. `variant`
+    /// picks the rules the game is played by and defaults to standard chess.
+    ///
     /// Returns game ID.
     /// There can only ever be 10 open games due to storage limitations.
     #[handle_result]
-    pub fn challenge(&mut self, challenged_id: AccountId) -> Result<(), ContractError> {
+    pub fn challenge(
+        &mut self,
+        challenged_id: AccountId,
+        chess960: Option<bool>,
+        variant: Option<Variant>,
+    ) -> Result<(), ContractError> {
         require!(self.is_running, "Contract is paused");
         let challenger_id = env::predecessor_account_id();
         if challenger_id == challenged_id {
             return Err(ContractError::SelfChallenge);
         }
-        self.internal_challenge(challenger_id, challenged_id, None)
+        self.internal_challenge(
+            challenger_id,
+            challenged_id,
+            None,
+            chess960.unwrap_or_default(),
+            variant.unwrap_or_default(),
+        )
     }
 
     /// Accepts a challenge.
@@ -443,6 +471,9 @@ impl Chess {
     /// queued.
     ///
     /// `min_elo` / `max_elo` define the acceptable opponent rating window.
+    /// With `chess960` the player is only matched with others asking for a
+    /// random Chess960 (Fischer Random) starting position, and likewise only
+    /// with others asking for the same `variant`.
     /// Returns `Some(game_id)` when matched right away, `None` when queued.
     /// This is the non-money entry point; for a wager use `ft_transfer_call`
     /// with a [`FtReceiverMsg::Matchmaking`] message.
@@ -451,10 +482,19 @@ impl Chess {
         &mut self,
         min_elo: f64,
         max_elo: f64,
+        chess960: Option<bool>,
+        variant: Option<Variant>,
     ) -> Result<Option<GameId>, ContractError> {
         require!(self.is_running, "Contract is paused");
         let account_id = env::predecessor_account_id();
-        self.internal_join_matchmaking(account_id, min_elo, max_elo, None)
+        self.internal_join_matchmaking(
+            account_id,
+            min_elo,
+            max_elo,
+            None,
+            chess960.unwrap_or_default(),
+            variant.unwrap_or_default(),
+        )
     }
 
     /// Leave the matchmaking queue. If a wager was deposited when joining, it is
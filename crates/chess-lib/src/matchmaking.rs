@@ -1,11 +1,13 @@
 use crate::{
-    Chess, ChessEvent, ContractError, Game, GameId, Player, Wager, MATCHMAKING_EXPIRY_NS,
-    MAX_MATCHMAKING_QUEUE,
+    Chess, ChessEvent, ContractError, Game, GameId, Player, StorageKey, Wager,
+    MATCHMAKING_EXPIRY_NS, MAX_MATCHMAKING_QUEUE,
 };
+use chess_engine::Variant;
 use near_sdk::{
     borsh::{BorshDeserialize, BorshSerialize},
     env,
     serde::{Deserialize, Serialize},
+    store::IterableMap,
     AccountId, NearSchema,
 };
 
@@ -14,6 +16,8 @@ use near_sdk::{
 /// `min_elo` / `max_elo` describe the range of opponent ratings the player is
 /// willing to accept. `wager` is `None` for a non-money match, or a
 /// `(token_id, amount)` pair that a matched opponent must match exactly.
+/// Players are only matched with opponents making the same `chess960` and
+/// `variant` choices.
 #[derive(BorshDeserialize, BorshSerialize, Clone, Debug, Deserialize, Serialize, NearSchema)]
 #[serde(crate = "near_sdk::serde")]
 #[borsh(crate = "near_sdk::borsh")]
@@ -22,6 +26,45 @@ pub struct MatchmakingEntry {
     pub max_elo: f64,
     pub wager: Wager,
     pub joined_timestamp: u64,
+    pub chess960: bool,
+    pub variant: Variant,
+}
+
+/// Layout of [`MatchmakingEntry`] before the `chess960` and `variant` options
+/// were added.
+/// Used only by [`Chess::migrate`](crate::Chess::migrate).
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct MatchmakingEntryV1 {
+    pub min_elo: f64,
+    pub max_elo: f64,
+    pub wager: Wager,
+    pub joined_timestamp: u64,
+}
+
+impl From<MatchmakingEntryV1> for MatchmakingEntry {
+    fn from(entry: MatchmakingEntryV1) -> Self {
+        Self {
+            min_elo: entry.min_elo,
+            max_elo: entry.max_elo,
+            wager: entry.wager,
+            joined_timestamp: entry.joined_timestamp,
+            chess960: false,
+            variant: Variant::Standard,
+        }
+    }
+}
+
+/// Rewrites a queue of [`MatchmakingEntryV1`] with the current layout under
+/// a new prefix, keeping the players' places and wagers.
+pub(crate) fn migrate_matchmaking_queue(
+    old: &mut IterableMap<AccountId, MatchmakingEntryV1>,
+) -> IterableMap<AccountId, MatchmakingEntry> {
+    let mut queue = IterableMap::new(StorageKey::MatchmakingQueueV2);
+    for (account_id, entry) in old.drain() {
+        queue.insert(account_id, MatchmakingEntry::from(entry));
+    }
+    queue
 }
 
 /// Two wagers are compatible for matchmaking when both are `None` (non-money)
@@ -47,10 +90,15 @@ impl Chess {
         min_elo: f64,
         max_elo: f64,
         wager: Wager,
+        chess960: bool,
+        variant: Variant,
     ) -> Result<Option<GameId>, ContractError> {
         if min_elo > max_elo {
             return Err(ContractError::InvalidEloRange);
         }
+        if chess960 && !variant.supports_chess960() {
+            return Err(ContractError::Chess960NotSupported(variant));
+        }
         if self.matchmaking_queue.contains_key(&joiner_id) {
             return Err(ContractError::AlreadyInMatchmaking);
         }
@@ -101,7 +149,10 @@ impl Chess {
             if !elo_ok {
                 continue;
             }
-            if !wager_compatible(&entry.wager, &wager) {
+            if !wager_compatible(&entry.wager, &wager)
+                || entry.chess960 != chess960
+                || entry.variant != variant
+            {
                 continue;
             }
             // queued player must still have a free game slot
@@ -135,6 +186,8 @@ impl Chess {
                 Player::Human(joiner_id.clone()),
                 entry.wager,
                 false,
+                Game::starting_board(variant, chess960)?,
+                variant,
             );
             let game_id = game.get_game_id().clone();
             self.accounts
@@ -157,7 +210,7 @@ impl Chess {
         }
 
         // No match found — queue the joiner.
-        if (self.matchmaking_queue.len() as u32) >= MAX_MATCHMAKING_QUEUE {
+        if self.matchmaking_queue.len() >= MAX_MATCHMAKING_QUEUE {
             return Err(ContractError::MatchmakingQueueFull);
         }
         self.matchmaking_queue.insert(
@@ -167,8 +220,68 @@ impl Chess {
                 max_elo,
                 wager,
                 joined_timestamp: now,
+                chess960,
+                variant,
             },
         );
         Ok(None)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::{borsh, json_types::U128};
+
+    #[test]
+    fn test_migrate_matchmaking_queue() {
+        let alice: AccountId = "alice.near".parse().unwrap();
+        let bob: AccountId = "bob.near".parse().unwrap();
+        let token_id: AccountId = "token.near".parse().unwrap();
+
+        // a queue as stored before the `chess960` and `variant` options
+        let mut old = IterableMap::new(StorageKey::MatchmakingQueue);
+        old.insert(
+            alice.clone(),
+            MatchmakingEntryV1 {
+                min_elo: 900.,
+                max_elo: 1_100.,
+                wager: None,
+                joined_timestamp: 1,
+            },
+        );
+        old.insert(
+            bob.clone(),
+            MatchmakingEntryV1 {
+                min_elo: 0.,
+                max_elo: 2_000.,
+                wager: Some((token_id.clone(), U128(5))),
+                joined_timestamp: 2,
+            },
+        );
+        old.flush();
+        let mut old: IterableMap<AccountId, MatchmakingEntryV1> =
+            borsh::from_slice(&borsh::to_vec(&old).unwrap()).unwrap();
+
+        let mut queue = migrate_matchmaking_queue(&mut old);
+        assert!(old.is_empty());
+        queue.flush();
+        let queue: IterableMap<AccountId, MatchmakingEntry> =
+            borsh::from_slice(&borsh::to_vec(&queue).unwrap()).unwrap();
+        assert_eq!(queue.len(), 2);
+
+        let entry = queue.get(&alice).unwrap();
+        assert_eq!(entry.min_elo, 900.);
+        assert_eq!(entry.max_elo, 1_100.);
+        assert_eq!(entry.wager, None);
+        assert_eq!(entry.joined_timestamp, 1);
+        assert!(!entry.chess960);
+        assert_eq!(entry.variant, Variant::Standard);
+
+        let entry = queue.get(&bob).unwrap();
+        assert_eq!(entry.wager, Some((token_id, U128(5))));
+        assert_eq!(entry.joined_timestamp, 2);
+        assert!(!entry.chess960);
+        assert_eq!(entry.variant, Variant::Standard);
+    }
+}
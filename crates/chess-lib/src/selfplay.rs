@@ -0,0 +1,178 @@
+//! Self-play runner for calibrating the AI difficulties.
+//!
+//! Build with `cargo build --release -p chess-lib --features selfplay`, then
+//!
+//! ```text
+//! chess-selfplay <a> <b> [--games <n>] [--nodes <n>] [--opening-plies <n>]
+//!                [--max-plies <n>] [--seed <n>] [--sprt <elo0>,<elo1>]
+//!                [--pgn <file>]
+//! ```
+//!
+//! Each side is a difficulty (`easy`, `medium`, `hard`, `very-hard`) or
+//! custom settings `<flags>:<depths>`, e.g. `0x3ff:9,10,8,6`, which plays
+//! like a difficulty with these flags and depth list. Games are played with
+//! `chess_engine::selfplay`, so a difficulty plays exactly like the
+//! contract's AI, limited by nodes instead of gas. The result is reported
+//! from the point of view of `<a>`, with an SPRT stopping the match early
+//! if `--sprt` is given.
+
+use chess_engine::selfplay::{run_match, AiSettings, EloEstimate, MatchSettings, Sprt};
+use chess_lib::Difficulty;
+use std::{fs::File, io::Write, process::exit};
+
+const USAGE: &str = "usage: chess-selfplay <a> <b> [--games <n>] [--nodes <n>] \
+                     [--opening-plies <n>] [--max-plies <n>] [--seed <n>] \
+                     [--sprt <elo0>,<elo1>] [--pgn <file>]\n\
+                     where <a> and <b> are easy, medium, hard, very-hard or <flags>:<depths>";
+
+struct Args {
+    a: AiSettings,
+    b: AiSettings,
+    settings: MatchSettings,
+    pgn: Option<String>,
+}
+
+impl Args {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut settings = MatchSettings::default();
+        let mut sides = Vec::new();
+        let mut pgn = None;
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or_else(|| format!("{} needs a value", arg));
+            match arg.as_str() {
+                "--games" => settings.games = number(&value()?)?,
+                "--nodes" => settings.nodes = number(&value()?)?,
+                "--opening-plies" => settings.opening_plies = number(&value()?)?,
+                "--max-plies" => settings.max_plies = number(&value()?)?,
+                "--seed" => settings.seed = number(&value()?)?,
+                "--sprt" => {
+                    let value = value()?;
+                    let (elo0, elo1) = value
+                        .split_once(',')
+                        .ok_or_else(|| format!("invalid SPRT bounds {}", value))?;
+                    settings.sprt = Some(Sprt {
+                        elo0: number(elo0)?,
+                        elo1: number(elo1)?,
+                        ..Default::default()
+                    });
+                }
+                "--pgn" => pgn = Some(value()?),
+                "-h" | "--help" => return Err(USAGE.to_string()),
+                flag if flag.starts_with("--") => return Err(format!("unknown option {}", flag)),
+                _ => sides.push(ai_settings(&arg)?),
+            }
+        }
+        let [a, b]: [AiSettings; 2] = sides.try_into().map_err(|_| USAGE.to_string())?;
+        Ok(Self {
+            a,
+            b,
+            settings,
+            pgn,
+        })
+    }
+}
+
+fn number<T: std::str::FromStr>(value: &str) -> Result<T, String> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid number {}", value))
+}
+
+/// A difficulty by name, or `<flags>:<depths>`.
+fn ai_settings(spec: &str) -> Result<AiSettings, String> {
+    let difficulty = match spec {
+        "easy" => Some(Difficulty::Easy),
+        "medium" => Some(Difficulty::Medium),
+        "hard" => Some(Difficulty::Hard),
+        "very-hard" => Some(Difficulty::VeryHard),
+        _ => None,
+    };
+    if let Some(difficulty) = difficulty {
+        return Ok(AiSettings::new(
+            spec,
+            difficulty.to_flags(),
+            difficulty.max_depths(),
+        ));
+    }
+    let (flags, depths) = spec
+        .split_once(':')
+        .ok_or_else(|| format!("invalid AI settings {}", spec))?;
+    let flags = match flags.strip_prefix("0x") {
+        Some(hex) => u16::from_str_radix(hex, 16).ok(),
+        None => flags.parse().ok(),
+    }
+    .ok_or_else(|| format!("invalid flags {}", flags))?;
+    let depths = depths
+        .split(',')
+        .map(number)
+        .collect::<Result<Vec<u8>, _>>()?;
+    if depths.is_empty() || depths.contains(&0) {
+        return Err(format!("invalid depths in {}", spec));
+    }
+    Ok(AiSettings::new(spec, flags, &depths))
+}
+
+fn format_elo(elo: &EloEstimate) -> String {
+    format!("{:+.1} [{:+.1}, {:+.1}]", elo.elo, elo.low, elo.high)
+}
+
+fn run(args: Args) -> Result<(), String> {
+    let mut pgn = match &args.pgn {
+        Some(file) => Some(File::create(file).map_err(|e| format!("{}: {}", file, e))?),
+        None => None,
+    };
+    let mut error = None;
+    let result = run_match(&args.a, &args.b, &args.settings, |game, result| {
+        eprintln!(
+            "game {}: {} - {} {}, +{} ={} -{}",
+            result.games(),
+            game.get_tag("White").unwrap_or("?"),
+            game.get_tag("Black").unwrap_or("?"),
+            game.get_result(),
+            result.wins,
+            result.draws,
+            result.losses
+        );
+        if let Some(file) = &mut pgn {
+            if let Err(e) = writeln!(file, "{}", game) {
+                error.get_or_insert(e.to_string());
+            }
+        }
+    });
+    if let Some(e) = error {
+        return Err(e);
+    }
+
+    println!("{} vs {}", args.a.name, args.b.name);
+    println!(
+        "games {}: +{} ={} -{}, score {:.1}%",
+        result.games(),
+        result.wins,
+        result.draws,
+        result.losses,
+        result.score() * 100.0
+    );
+    println!("elo {}", format_elo(&result.elo()));
+    if let Some(sprt) = args.settings.sprt {
+        let (lower, upper) = sprt.bounds();
+        println!(
+            "sprt [{}, {}]: llr {:.2} ({:.2}, {:.2}), {:?}",
+            sprt.elo0,
+            sprt.elo1,
+            sprt.llr(&result),
+            lower,
+            upper,
+            sprt.status(&result)
+        );
+    }
+    Ok(())
+}
+
+fn main() {
+    let result = Args::parse(std::env::args().skip(1)).and_then(run);
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        exit(1);
+    }
+}
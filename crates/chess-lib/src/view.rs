@@ -42,6 +42,7 @@ impl Chess {
             turn_color: game.get_board().get_turn_color(),
             last_block_height: game.get_last_block_height(),
             has_bets: game.has_bets(),
+            variant: game.get_variant(),
         })
     }
 
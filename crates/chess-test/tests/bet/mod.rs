@@ -1,5 +1,5 @@
 use crate::{bet, util::*};
-use chess_engine::Color;
+use chess_engine::{Color, Variant};
 use chess_lib::{create_challenge_id, BetMsg, ChessEvent, GameId, GameOutcome};
 use near_sdk::json_types::U128;
 use near_workspaces::{types::NearToken, Account, AccountId, Contract};
@@ -1755,6 +1755,8 @@ async fn test_wager_and_bets_combined() -> anyhow::Result<()> {
         wager_amount.into(),
         chess_lib::ChallengeMsg {
             challenged_id: player_b.id().clone(),
+            chess960: false,
+            variant: Variant::Standard,
         },
     )
     .await?;
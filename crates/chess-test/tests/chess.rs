@@ -9,7 +9,7 @@ mod gas;
 
 use base64::Engine;
 use chess_common::ContractEvent;
-use chess_engine::Color;
+use chess_engine::{Color, Variant};
 use chess_lib::{
     create_challenge_id, BetMsg, Challenge, ChessEvent, Difficulty, GameId, GameInfo, GameOutcome,
     Player, AI_EASY_GAS, AI_HARD_GAS, AI_MEDIUM_GAS, AI_VERY_HARD_GAS, MAX_OPEN_CHALLENGES,
@@ -87,6 +87,7 @@ async fn test_migrate() -> anyhow::Result<()> {
         turn_color: Color::White,
         last_block_height: block_height,
         has_bets: false,
+        variant: Variant::Standard,
     })?;
     expected["last_block_height"].take();
     assert_eq!(actual, expected);
@@ -271,7 +272,13 @@ async fn test_accept_challenge() -> anyhow::Result<()> {
     let challenge_ids = view::get_challenges(&contract, player_b.id(), false).await?;
     assert_eq!(challenge_ids, vec![challenge_id.clone()]);
     let challenge = view::get_challenge(&contract, &challenge_id).await?;
-    let expected_challenge = Challenge::new(player_a.id().clone(), player_b.id().clone(), None);
+    let expected_challenge = Challenge::new(
+        player_a.id().clone(),
+        player_b.id().clone(),
+        None,
+        false,
+        Variant::Standard,
+    );
     assert_eq!(&challenge, &expected_challenge);
     assert_event_emits(events, vec![ChessEvent::Challenge(expected_challenge)])?;
 
@@ -321,6 +328,7 @@ async fn test_accept_challenge() -> anyhow::Result<()> {
             game_id: game_id.clone(),
             color: Color::White,
             mv: "e2 to e4".to_string(),
+            san: "e4".to_string(),
             board: [
                 "RNBQKBNR".to_string(),
                 "PPPP PPP".to_string(),
@@ -332,11 +340,71 @@ async fn test_accept_challenge() -> anyhow::Result<()> {
                 "rnbqkbnr".to_string(),
             ],
             outcome: None,
+            search: None,
         }],
     )?;
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_accept_chess960_challenge() -> anyhow::Result<()> {
+    let (worker, _, contract) = initialize_contracts(None).await?;
+
+    let player_a = worker.dev_create_account().await?;
+    let player_b = worker.dev_create_account().await?;
+
+    tokio::try_join!(
+        call::storage_deposit(&contract, &player_a, None, None),
+        call::storage_deposit(&contract, &player_b, None, None)
+    )?;
+
+    call::challenge_chess960(&contract, &player_a, player_b.id()).await?;
+    let challenge_id = create_challenge_id(player_a.id(), player_b.id());
+    let challenge = view::get_challenge(&contract, &challenge_id).await?;
+    assert!(challenge.is_chess960());
+
+    let (game_id, _events) = call::accept_challenge(&contract, &player_b, &challenge_id).await?;
+    let board = view::get_board(&contract, &game_id).await?;
+    // both sides get the same back rank, with the king between the rooks
+    assert_eq!(board[0].to_lowercase(), board[7]);
+    assert_eq!(board[1], "PPPPPPPP");
+    assert_eq!(board[6], "pppppppp");
+    let king = board[0].find('K').unwrap();
+    assert!(board[0][..king].contains('R') && board[0][king..].contains('R'));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_accept_horde_challenge() -> anyhow::Result<()> {
+    let (worker, _, contract) = initialize_contracts(None).await?;
+
+    let player_a = worker.dev_create_account().await?;
+    let player_b = worker.dev_create_account().await?;
+
+    tokio::try_join!(
+        call::storage_deposit(&contract, &player_a, None, None),
+        call::storage_deposit(&contract, &player_b, None, None)
+    )?;
+
+    call::challenge_variant(&contract, &player_a, player_b.id(), Variant::Horde).await?;
+    let challenge_id = create_challenge_id(player_a.id(), player_b.id());
+    let challenge = view::get_challenge(&contract, &challenge_id).await?;
+    assert_eq!(challenge.get_variant(), Variant::Horde);
+
+    let (game_id, _events) = call::accept_challenge(&contract, &player_b, &challenge_id).await?;
+    let game_info = view::get_game_info(&contract, &game_id).await?;
+    assert_eq!(game_info.variant, Variant::Horde);
+    let board = view::get_board(&contract, &game_id).await?;
+    // White has four ranks of pawns and no king
+    assert!(board[..4].iter().all(|rank| rank == "PPPPPPPP"));
+    assert!(!board.iter().any(|rank| rank.contains('K')));
+    assert_eq!(board[7], "rnbqkbnr");
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_reject_challenge() -> anyhow::Result<()> {
     let (worker, _, contract) = initialize_contracts(None).await?;
@@ -359,6 +427,8 @@ async fn test_reject_challenge() -> anyhow::Result<()> {
             player_a.id().clone(),
             player_b.id().clone(),
             None,
+            false,
+            Variant::Standard,
         ))],
     )?;
 
@@ -407,7 +477,13 @@ async fn test_accept_reject_challenge_check_sender() -> anyhow::Result<()> {
     let challenge_ids = view::get_challenges(&contract, player_b.id(), false).await?;
     assert_eq!(challenge_ids, vec![challenge_id.clone()]);
     let challenge = view::get_challenge(&contract, &challenge_id).await?;
-    let expected_challenge = Challenge::new(player_a.id().clone(), player_b.id().clone(), None);
+    let expected_challenge = Challenge::new(
+        player_a.id().clone(),
+        player_b.id().clone(),
+        None,
+        false,
+        Variant::Standard,
+    );
     assert_eq!(&challenge, &expected_challenge);
     assert_event_emits(events, vec![ChessEvent::Challenge(expected_challenge)])?;
 
@@ -904,8 +980,10 @@ async fn test_finish_game() -> anyhow::Result<()> {
             game_id: game_id.clone(),
             color: Color::White,
             mv: "f3 to f7".to_string(),
+            san: "Qxf7#".to_string(),
             board: expected_board,
             outcome: Some(GameOutcome::Victory(Color::White)),
+            search: None,
         }],
     )?;
 
@@ -1,5 +1,6 @@
 use crate::util::*;
 use chess_common::{ChessEvent as ChessEventCommon, ChessEventKind, ContractEvent};
+use chess_engine::Variant;
 use chess_lib::{ChessEvent, GameId, MatchmakingMsg, Player};
 use near_workspaces::{network::Sandbox, types::NearToken, Account, Contract, Worker};
 
@@ -117,6 +118,39 @@ async fn test_matchmaking_elo_no_overlap() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_matchmaking_chess960() -> anyhow::Result<()> {
+    let (worker, _, contract) = initialize_contracts(None).await?;
+    let [player_a, player_b, player_c] = setup_players(&worker, &contract, 3)
+        .await?
+        .try_into()
+        .ok()
+        .unwrap();
+
+    // A wants Chess960, B a standard game -> no match.
+    let (res, _events) =
+        call::join_matchmaking_chess960(&contract, &player_a, 0.0, 2_000.0).await?;
+    assert!(res.is_none());
+    let (res, _events) = call::join_matchmaking(&contract, &player_b, 0.0, 2_000.0).await?;
+    assert!(res.is_none());
+    let entry = view::is_queued(&contract, player_a.id()).await?;
+    assert!(entry.unwrap().chess960);
+
+    // C also wants Chess960 -> matched with A.
+    let (res, _events) =
+        call::join_matchmaking_chess960(&contract, &player_c, 0.0, 2_000.0).await?;
+    let game_id = res.expect("should have matched");
+    let info = view::get_game_info(&contract, &game_id).await?;
+    assert_eq!(info.white, Player::Human(player_a.id().clone()));
+    assert_eq!(info.black, Player::Human(player_c.id().clone()));
+    let board = view::get_board(&contract, &game_id).await?;
+    assert_eq!(board[0].to_lowercase(), board[7]);
+    let entry = view::is_queued(&contract, player_b.id()).await?;
+    assert!(entry.is_some());
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_matchmaking_already_queued() -> anyhow::Result<()> {
     let (worker, _, contract) = initialize_contracts(None).await?;
@@ -226,6 +260,8 @@ async fn test_matchmaking_wager_match() -> anyhow::Result<()> {
         MatchmakingMsg {
             min_elo: 0.0,
             max_elo: 2_000.0,
+            chess960: false,
+            variant: Variant::Standard,
         },
     )
     .await?;
@@ -244,6 +280,8 @@ async fn test_matchmaking_wager_match() -> anyhow::Result<()> {
         MatchmakingMsg {
             min_elo: 0.0,
             max_elo: 2_000.0,
+            chess960: false,
+            variant: Variant::Standard,
         },
     )
     .await?;
@@ -303,6 +341,8 @@ async fn test_matchmaking_wager_mismatch_no_match() -> anyhow::Result<()> {
         MatchmakingMsg {
             min_elo: 0.0,
             max_elo: 2_000.0,
+            chess960: false,
+            variant: Variant::Standard,
         },
     )
     .await?;
@@ -316,6 +356,8 @@ async fn test_matchmaking_wager_mismatch_no_match() -> anyhow::Result<()> {
         MatchmakingMsg {
             min_elo: 0.0,
             max_elo: 2_000.0,
+            chess960: false,
+            variant: Variant::Standard,
         },
     )
     .await?;
@@ -366,6 +408,8 @@ async fn test_cancel_matchmaking_wager_refund() -> anyhow::Result<()> {
         MatchmakingMsg {
             min_elo: 0.0,
             max_elo: 2_000.0,
+            chess960: false,
+            variant: Variant::Standard,
         },
     )
     .await?;
@@ -421,6 +465,8 @@ async fn test_matchmaking_expired_entry_purged_and_refunded() -> anyhow::Result<
         MatchmakingMsg {
             min_elo: 0.0,
             max_elo: 2_000.0,
+            chess960: false,
+            variant: Variant::Standard,
         },
     )
     .await?;
@@ -1,5 +1,6 @@
 use super::log_tx_result;
 use chess_common::ContractEvent;
+use chess_engine::Variant;
 use chess_lib::{
     AcceptChallengeMsg, BetMsg, ChallengeId, ChallengeMsg, Difficulty, FtReceiverMsg, GameId,
     GameOutcome, MatchmakingMsg, MoveStr,
@@ -203,7 +204,42 @@ pub async fn challenge(
         Some("challenge"),
         sender
             .call(contract.id(), "challenge")
-            .args_json((challenged_id,))
+            .args_json(json!({ "challenged_id": challenged_id }))
+            .max_gas()
+            .transact()
+            .await?,
+    )?;
+    Ok((res, events))
+}
+
+pub async fn challenge_chess960(
+    contract: &Contract,
+    sender: &Account,
+    challenged_id: &AccountId,
+) -> anyhow::Result<(ExecutionResult<Value>, Vec<ContractEvent>)> {
+    let (res, events): (ExecutionResult<Value>, Vec<ContractEvent>) = log_tx_result(
+        Some("challenge_chess960"),
+        sender
+            .call(contract.id(), "challenge")
+            .args_json(json!({ "challenged_id": challenged_id, "chess960": true }))
+            .max_gas()
+            .transact()
+            .await?,
+    )?;
+    Ok((res, events))
+}
+
+pub async fn challenge_variant(
+    contract: &Contract,
+    sender: &Account,
+    challenged_id: &AccountId,
+    variant: Variant,
+) -> anyhow::Result<(ExecutionResult<Value>, Vec<ContractEvent>)> {
+    let (res, events): (ExecutionResult<Value>, Vec<ContractEvent>) = log_tx_result(
+        Some("challenge_variant"),
+        sender
+            .call(contract.id(), "challenge")
+            .args_json(json!({ "challenged_id": challenged_id, "variant": variant }))
             .max_gas()
             .transact()
             .await?,
@@ -298,7 +334,25 @@ pub async fn join_matchmaking(
         Some("join_matchmaking"),
         sender
             .call(contract.id(), "join_matchmaking")
-            .args_json((min_elo, max_elo))
+            .args_json(json!({ "min_elo": min_elo, "max_elo": max_elo }))
+            .max_gas()
+            .transact()
+            .await?,
+    )?;
+    Ok((res.json()?, events))
+}
+
+pub async fn join_matchmaking_chess960(
+    contract: &Contract,
+    sender: &Account,
+    min_elo: f64,
+    max_elo: f64,
+) -> anyhow::Result<(Option<GameId>, Vec<ContractEvent>)> {
+    let (res, events): (ExecutionResult<Value>, Vec<ContractEvent>) = log_tx_result(
+        Some("join_matchmaking_chess960"),
+        sender
+            .call(contract.id(), "join_matchmaking")
+            .args_json(json!({ "min_elo": min_elo, "max_elo": max_elo, "chess960": true }))
             .max_gas()
             .transact()
             .await?,
@@ -1,5 +1,5 @@
 use crate::util::*;
-use chess_engine::Color;
+use chess_engine::{Color, Variant};
 use chess_lib::{
     create_challenge_id, AcceptChallengeMsg, Challenge, ChallengeMsg, ChessEvent, GameId,
     GameOutcome, Player,
@@ -54,6 +54,8 @@ async fn test_accept_challenge_success() -> anyhow::Result<()> {
         wager_amount.into(),
         ChallengeMsg {
             challenged_id: player_b.id().clone(),
+            chess960: false,
+            variant: Variant::Standard,
         },
     )
     .await?;
@@ -71,6 +73,8 @@ async fn test_accept_challenge_success() -> anyhow::Result<()> {
         player_a.id().clone(),
         player_b.id().clone(),
         Some((test_token.id().clone(), wager_amount.into())),
+        false,
+        Variant::Standard,
     );
     assert_eq!(&challenge, &expected_challenge);
     assert_event_emits(events, vec![ChessEvent::Challenge(expected_challenge)])?;
@@ -180,6 +184,8 @@ async fn test_accept_challenge_not_enough_wager() -> anyhow::Result<()> {
         wager_amount.into(),
         ChallengeMsg {
             challenged_id: player_b.id().clone(),
+            chess960: false,
+            variant: Variant::Standard,
         },
     )
     .await?;
@@ -253,6 +259,8 @@ async fn test_accept_challenge_refund_too_much_wager() -> anyhow::Result<()> {
         wager_amount.into(),
         ChallengeMsg {
             challenged_id: player_b.id().clone(),
+            chess960: false,
+            variant: Variant::Standard,
         },
     )
     .await?;
@@ -366,6 +374,8 @@ async fn test_reject_challenge_refund_wager() -> anyhow::Result<()> {
         wager_amount.into(),
         ChallengeMsg {
             challenged_id: player_b.id().clone(),
+            chess960: false,
+            variant: Variant::Standard,
         },
     )
     .await?;
@@ -438,6 +448,8 @@ async fn test_reject_challenge_refund_wager_ft_transfer_failure() -> anyhow::Res
         wager_amount.into(),
         ChallengeMsg {
             challenged_id: player_b.id().clone(),
+            chess960: false,
+            variant: Variant::Standard,
         },
     )
     .await?;
@@ -522,6 +534,8 @@ async fn test_challenger_reject_own_wager_challenge() -> anyhow::Result<()> {
         wager_amount.into(),
         ChallengeMsg {
             challenged_id: player_b.id().clone(),
+            chess960: false,
+            variant: Variant::Standard,
         },
     )
     .await?;
@@ -591,6 +605,8 @@ async fn test_reject_wager_no_whitelist() -> anyhow::Result<()> {
         wager_amount.into(),
         ChallengeMsg {
             challenged_id: player_b.id().clone(),
+            chess960: false,
+            variant: Variant::Standard,
         },
     )
     .await?;
@@ -667,6 +683,8 @@ async fn test_reject_wager_wrong_token() -> anyhow::Result<()> {
         wager_amount.into(),
         ChallengeMsg {
             challenged_id: player_b.id().clone(),
+            chess960: false,
+            variant: Variant::Standard,
         },
     )
     .await?;
@@ -733,6 +751,8 @@ async fn test_cancel_game_refund_wager() -> anyhow::Result<()> {
         wager_amount.into(),
         ChallengeMsg {
             challenged_id: player_b.id().clone(),
+            chess960: false,
+            variant: Variant::Standard,
         },
     )
     .await?;
@@ -827,6 +847,8 @@ async fn test_finish_game_payout_wager() -> anyhow::Result<()> {
         wager_amount.into(),
         ChallengeMsg {
             challenged_id: player_b.id().clone(),
+            chess960: false,
+            variant: Variant::Standard,
         },
     )
     .await?;
@@ -868,8 +890,10 @@ async fn test_finish_game_payout_wager() -> anyhow::Result<()> {
             game_id: game_id.clone(),
             color: Color::White,
             mv: "f3 to f7".to_string(),
+            san: "Qxf7#".to_string(),
             board: expected_board,
             outcome: Some(GameOutcome::Victory(Color::White)),
+            search: None,
         }],
     )?;
 
@@ -941,6 +965,8 @@ async fn test_finish_game_payout_fees() -> anyhow::Result<()> {
         wager_amount.into(),
         ChallengeMsg {
             challenged_id: player_b.id().clone(),
+            chess960: false,
+            variant: Variant::Standard,
         },
     )
     .await?;
@@ -982,8 +1008,10 @@ async fn test_finish_game_payout_fees() -> anyhow::Result<()> {
             game_id: game_id.clone(),
             color: Color::White,
             mv: "f3 to f7".to_string(),
+            san: "Qxf7#".to_string(),
             board: expected_board,
             outcome: Some(GameOutcome::Victory(Color::White)),
+            search: None,
         }],
     )?;
 
@@ -1049,6 +1077,8 @@ async fn test_resign_payout_wager() -> anyhow::Result<()> {
         wager_amount.into(),
         ChallengeMsg {
             challenged_id: player_b.id().clone(),
+            chess960: false,
+            variant: Variant::Standard,
         },
     )
     .await?;
@@ -1136,6 +1166,8 @@ async fn test_stalemate_refund_wager() -> anyhow::Result<()> {
         wager_amount.into(),
         ChallengeMsg {
             challenged_id: player_b.id().clone(),
+            chess960: false,
+            variant: Variant::Standard,
         },
     )
     .await?;
@@ -1219,6 +1251,8 @@ async fn test_fee_deducted_from_total_pool() -> anyhow::Result<()> {
         wager_amount.into(),
         ChallengeMsg {
             challenged_id: player_b.id().clone(),
+            chess960: false,
+            variant: Variant::Standard,
         },
     )
     .await?;
@@ -1316,6 +1350,8 @@ async fn test_withdraw_treasury() -> anyhow::Result<()> {
         wager_amount.into(),
         ChallengeMsg {
             challenged_id: player_b.id().clone(),
+            chess960: false,
+            variant: Variant::Standard,
         },
     )
     .await?;
@@ -1445,6 +1481,8 @@ async fn test_cancel_game_refund_wager_ft_transfer_failure() -> anyhow::Result<(
         wager_amount.into(),
         ChallengeMsg {
             challenged_id: player_b.id().clone(),
+            chess960: false,
+            variant: Variant::Standard,
         },
     )
     .await?;
@@ -1617,6 +1655,8 @@ async fn test_withdraw_token_ft_transfer_failure() -> anyhow::Result<()> {
         wager_amount.into(),
         ChallengeMsg {
             challenged_id: player_b.id().clone(),
+            chess960: false,
+            variant: Variant::Standard,
         },
     )
     .await?;
@@ -1772,6 +1812,8 @@ async fn test_victory_payout_wager_ft_transfer_failure() -> anyhow::Result<()> {
         wager_amount.into(),
         ChallengeMsg {
             challenged_id: player_b.id().clone(),
+            chess960: false,
+            variant: Variant::Standard,
         },
     )
     .await?;
@@ -1870,6 +1912,8 @@ async fn test_stalemate_refund_wager_ft_transfer_failure() -> anyhow::Result<()>
         wager_amount.into(),
         ChallengeMsg {
             challenged_id: player_b.id().clone(),
+            chess960: false,
+            variant: Variant::Standard,
         },
     )
     .await?;
@@ -124,6 +124,26 @@ describe('handlers', () => {
       expect((moveRows[0] as Record<string, unknown>).move_notation).toBe('e4');
     });
 
+    it('prefers SAN notation when present', async () => {
+      await processEvent(makeCreateGame(GAME_ID));
+      const event = makePlayMove(GAME_ID, 'White', 'g1 to f3');
+      event.event_data.san = 'Nf3';
+      await processEvent(event);
+
+      const moveRows = await getMoves(GAME_ID);
+      expect((moveRows[0] as Record<string, unknown>).move_notation).toBe('Nf3');
+    });
+
+    it('falls back to the engine notation without SAN', async () => {
+      await processEvent(makeCreateGame(GAME_ID));
+      await processEvent(makePlayMove(GAME_ID, 'White', 'g1 to f3'));
+
+      const moveRows = await getMoves(GAME_ID);
+      expect((moveRows[0] as Record<string, unknown>).move_notation).toBe(
+        'g1 to f3'
+      );
+    });
+
     it('finishes game when outcome is present', async () => {
       await processEvent(makeCreateGame(GAME_ID));
       const outcome = { result: 'Victory', color: 'White' };
@@ -39,7 +39,12 @@ function normalizeOutcome(o: Record<string, unknown>): {
   if ('result' in o)
     return { result: o.result as string, color: o.color as string | undefined };
   const [[key, val]] = Object.entries(o);
-  if (key === 'Stalemate') return { result: 'Stalemate' };
+  if (
+    key === 'Stalemate' ||
+    key === 'ThreefoldRepetition' ||
+    key === 'FiftyMoveRule'
+  )
+    return { result: key };
   return { result: key, color: val as string };
 }
 
@@ -92,6 +97,8 @@ const handlers: Record<string, EventHandler> = {
       return;
     }
     const color = d.color as string;
+    // events before version 1.1.0 only carry the engine's "e2 to e4" notation
+    const notation = (d.san ?? d.mv) as string;
     const outcome = d.outcome
       ? normalizeOutcome(d.outcome as Record<string, unknown>)
       : null;
@@ -109,7 +116,7 @@ const handlers: Record<string, EventHandler> = {
 
     await sql`
       INSERT INTO game_moves (id, game_id, move_number, color, move_notation, fen, outcome, trigger_block_height, trigger_block_timestamp)
-      VALUES (${event.id}, ${gid}, ${moveNum}, ${color}, ${d.mv}, ${fen}, ${outcomeJson}::jsonb, ${event.trigger_block_height}, ${event.trigger_block_timestamp})
+      VALUES (${event.id}, ${gid}, ${moveNum}, ${color}, ${notation}, ${fen}, ${outcomeJson}::jsonb, ${event.trigger_block_height}, ${event.trigger_block_timestamp})
       ON CONFLICT (id) DO NOTHING
     `;
 
@@ -275,7 +282,7 @@ const handlers: Record<string, EventHandler> = {
 
     const payouts: Array<{ id: number; amount: string }> = [];
 
-    if (outcome.result === 'Stalemate') {
+    if (outcome.result !== 'Victory') {
       for (const [, tokenBets] of byToken) {
         for (const b of tokenBets) {
           payouts.push({ id: b.id, amount: b.amount });
@@ -400,51 +400,11 @@ LINES = [
 
 RUST_TEMPLATE = """// Auto-generated by scripts/generate_static_data.py
 // DO NOT EDIT MANUALLY — run `./build.sh --regen-data` to regenerate.
-use crate::{{Move, Piece, Position}};
+pub use crate::book::{{decode_move, lookup_opening}};
 
 pub const OPENING_BOOK: &[(u64, u16, u16, u16)] = &[
     {body}
 ];
-
-/// Look up a position in the opening book. Returns a weighted-random move:
-/// 50% best, 33% second, 17% third (falls back if fewer moves available).
-pub fn lookup_opening(zobrist: u64, seed: u8) -> Option<Move> {{
-    let idx = OPENING_BOOK.binary_search_by_key(&zobrist, |&(k, _, _, _)| k).ok()?;
-    let (_, m1, m2, m3) = OPENING_BOOK[idx];
-    if m1 == 0 {{
-        return None;
-    }}
-    let r = seed % 100;
-    let chosen = if r < 50 || m2 == 0 {{
-        m1
-    }} else if r < 83 || m3 == 0 {{
-        m2
-    }} else {{
-        m3
-    }};
-    Some(decode_move(chosen))
-}}
-
-pub fn decode_move(code: u16) -> Move {{
-    let special = (code >> 12) & 0xF;
-    match special {{
-        5 => Move::KingSideCastle,
-        6 => Move::QueenSideCastle,
-        _ => {{
-            let fi = (code & 0x3F) as usize;
-            let ti = ((code >> 6) & 0x3F) as usize;
-            let f = Position::new((fi / 8) as i32, (fi % 8) as i32);
-            let t = Position::new((ti / 8) as i32, (ti % 8) as i32);
-            match special {{
-                1 => Move::Promotion(f, t, Piece::Knight(crate::Color::White, t)),
-                2 => Move::Promotion(f, t, Piece::Bishop(crate::Color::White, t)),
-                3 => Move::Promotion(f, t, Piece::Rook(crate::Color::White, t)),
-                4 => Move::Promotion(f, t, Piece::Queen(crate::Color::White, t)),
-                _ => Move::Piece(f, t),
-            }}
-        }}
-    }}
-}}
 """
 
 